@@ -0,0 +1,9 @@
+-- hero catalog (names/roles/icons), refreshed from the Deadlock assets API
+CREATE TABLE IF NOT EXISTS heroes (
+  hero_id        INT          PRIMARY KEY,
+  name           TEXT         NOT NULL,
+  class_name     TEXT,
+  role           TEXT,
+  icon_url       TEXT,
+  updated_at     TIMESTAMPTZ  NOT NULL DEFAULT now()
+);
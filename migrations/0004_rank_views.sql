@@ -0,0 +1,34 @@
+-- decode Deadlock ranks: division 0..11 (Obscurus..Eternus), tiers 1..6
+CREATE OR REPLACE FUNCTION deadlock_rank_name(division INT) RETURNS TEXT AS $$
+  SELECT (ARRAY[
+    'Obscurus','Initiate','Seeker','Alchemist','Arcanist','Ritualist',
+    'Emissary','Archon','Oracle','Phantom','Ascendant','Eternus'
+  ])[division + 1]
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION deadlock_badge_name(division INT, tier INT) RETURNS TEXT AS $$
+  SELECT CASE
+    WHEN division = 0 THEN 'Obscurus'
+    WHEN division BETWEEN 1 AND 11 AND tier BETWEEN 1 AND 6 THEN deadlock_rank_name(division) || ' ' || tier
+    ELSE NULL
+  END
+$$ LANGUAGE sql IMMUTABLE;
+
+-- combined badge numbers (e.g. matches.average_badge = 74 -> 'Archon 4')
+CREATE OR REPLACE FUNCTION deadlock_badge_name(badge INT) RETURNS TEXT AS $$
+  SELECT deadlock_badge_name(badge / 10, badge % 10)
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE VIEW latest_mmr_ranked AS
+SELECT m.*,
+       deadlock_rank_name(m.division) AS rank_name,
+       deadlock_badge_name(m.division, m.division_tier) AS badge_name,
+       m.division * 10 + m.division_tier AS badge
+FROM latest_mmr m;
+
+CREATE OR REPLACE VIEW mmr_history_ranked AS
+SELECT h.*,
+       deadlock_rank_name(h.division) AS rank_name,
+       deadlock_badge_name(h.division, h.division_tier) AS badge_name,
+       h.division * 10 + h.division_tier AS badge
+FROM mmr_history h;
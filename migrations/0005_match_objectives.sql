@@ -0,0 +1,12 @@
+-- decoded objectives_mask_team0/team1, one row per team objective
+CREATE TABLE IF NOT EXISTS match_objectives (
+  match_id       BIGINT  NOT NULL REFERENCES matches(match_id) ON DELETE CASCADE,
+  team           TEXT    NOT NULL,
+  objective      TEXT    NOT NULL,
+  lane           INT     NOT NULL DEFAULT 0,
+  bit            INT     NOT NULL,
+  destroyed      BOOLEAN NOT NULL,
+  PRIMARY KEY (match_id, team, bit)
+);
+
+CREATE INDEX IF NOT EXISTS idx_match_objectives_kind ON match_objectives (objective, team) WHERE destroyed;
@@ -0,0 +1,4 @@
+-- when a player was last looked up (online or offline), for the dashboard's recent list
+ALTER TABLE players ADD COLUMN IF NOT EXISTS last_lookup_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_players_last_lookup ON players (last_lookup_at DESC) WHERE last_lookup_at IS NOT NULL;
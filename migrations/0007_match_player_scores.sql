@@ -0,0 +1,44 @@
+-- per-player performance score: the mean standard score of kills, deaths (negated), assists,
+-- networth, damage, objective damage and last hits within the match, scaled so 50 is the lobby
+-- average and 10 points one standard deviation. Only matches with 6+ stored heroes are scored.
+CREATE TABLE IF NOT EXISTS match_player_scores (
+  match_id       BIGINT           NOT NULL,
+  account_id     BIGINT           NOT NULL,
+  hero_id        INT              NOT NULL,
+  score          DOUBLE PRECISION NOT NULL,
+  PRIMARY KEY (match_id, account_id),
+  FOREIGN KEY (match_id, account_id) REFERENCES match_players (match_id, account_id) ON DELETE CASCADE
+);
+
+-- percentiles rank a score among the hero's stored games
+CREATE INDEX IF NOT EXISTS idx_match_player_scores_hero ON match_player_scores (hero_id, score);
+
+-- rescore whole lobbies; ingest calls this with the matches it touched
+CREATE OR REPLACE FUNCTION score_matches(ids BIGINT[]) RETURNS VOID AS $$
+  DELETE FROM match_player_scores WHERE match_id = ANY(ids);
+
+  WITH lobby AS (
+    SELECT match_id, account_id, hero_id,
+           ARRAY[kills::FLOAT8, -(deaths::FLOAT8), assists::FLOAT8, networth::FLOAT8, damage::FLOAT8,
+                 obj_damage::FLOAT8, last_hits::FLOAT8] AS stats,
+           COUNT(*) OVER (PARTITION BY match_id) AS players
+    FROM match_players
+    WHERE match_id = ANY(ids) AND hero_id IS NOT NULL
+  ),
+  stat AS (
+    SELECT l.match_id, l.account_id, l.hero_id, s.i, s.v,
+           COUNT(s.v) OVER w AS n, AVG(s.v) OVER w AS mean, STDDEV_POP(s.v) OVER w AS sd
+    FROM lobby l
+    CROSS JOIN LATERAL UNNEST(l.stats) WITH ORDINALITY AS s(v, i)
+    WHERE l.players >= 6
+    WINDOW w AS (PARTITION BY l.match_id, s.i)
+  )
+  INSERT INTO match_player_scores (match_id, account_id, hero_id, score)
+  SELECT match_id, account_id, hero_id,
+         50 + 10 * AVG(CASE WHEN sd > 0 THEN (v - mean) / sd ELSE 0 END)
+  FROM stat
+  WHERE v IS NOT NULL AND n >= 2
+  GROUP BY match_id, account_id, hero_id;
+$$ LANGUAGE sql;
+
+SELECT score_matches(ARRAY(SELECT match_id FROM matches));
@@ -0,0 +1,128 @@
+use crate::deadlock::{self, DeadlockApi};
+use crate::recorder::Recorder;
+use crate::steam;
+use crate::models::CombinedPayload;
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// One identifier from the input, with its 1-based line number for error reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchInput {
+    pub line: usize,
+    pub input: String,
+}
+
+/// Outcome for one input line: the player, or why there is none.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchRecord {
+    pub line: usize,
+    pub input: String,
+    pub account_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub player: Option<CombinedPayload>,
+}
+
+impl BatchRecord {
+    fn failed(input: &BatchInput, account_id: Option<u32>, error: String) -> Self {
+        Self { line: input.line, input: input.input.clone(), account_id, error: Some(error), player: None }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Identifiers one per line; blank lines and `#` comments are skipped.
+pub fn read_inputs(reader: impl BufRead) -> std::io::Result<Vec<BatchInput>> {
+    let mut out = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let input = line.split('#').next().unwrap_or_default().trim();
+        if !input.is_empty() {
+            out.push(BatchInput { line: i + 1, input: input.to_string() });
+        }
+    }
+    Ok(out)
+}
+
+/// Resolve every input to an account ID, at most `concurrency` Steam lookups at a time.
+/// Results keep the input order.
+pub async fn resolve_all(
+    inputs: &[BatchInput],
+    http: &Client,
+    recorder: Option<&Recorder>,
+    concurrency: usize,
+) -> Vec<Result<u32, String>> {
+    let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    for (i, input) in inputs.iter().enumerate() {
+        let (http, recorder, permits, input) = (http.clone(), recorder.cloned(), permits.clone(), input.input.clone());
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            (i, steam::resolve_account_id(&input, &http, recorder.as_ref()).await.map_err(|e| e.to_string()))
+        });
+    }
+
+    let mut out: Vec<Result<u32, String>> = vec![Err("resolution did not finish".into()); inputs.len()];
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((i, res)) = joined {
+            out[i] = res;
+        }
+    }
+    out
+}
+
+/// Look up every resolved account in multi-ID requests of `chunk_size` and pair the players
+/// back up with their input lines. Repeated accounts are fetched once.
+pub async fn fetch_all(
+    api: &impl DeadlockApi,
+    inputs: &[BatchInput],
+    resolved: Vec<Result<u32, String>>,
+    chunk_size: usize,
+) -> Vec<BatchRecord> {
+    let mut ids: Vec<u32> = resolved.iter().filter_map(|r| r.as_ref().ok().copied()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let mut players: HashMap<u32, Result<CombinedPayload, String>> = HashMap::new();
+    for chunk in ids.chunks(chunk_size.max(1)) {
+        match deadlock::fetch_players(api, chunk).await {
+            Ok(found) => {
+                for p in found {
+                    players.insert(p.account_id, Ok(p));
+                }
+            }
+            Err(e) => {
+                for &id in chunk {
+                    players.insert(id, Err(format!("{:#}", e)));
+                }
+            }
+        }
+    }
+
+    inputs
+        .iter()
+        .zip(resolved)
+        .map(|(input, res)| match res {
+            Err(e) => BatchRecord::failed(input, None, e),
+            Ok(id) => match players.get(&id) {
+                Some(Ok(p)) => BatchRecord {
+                    line: input.line,
+                    input: input.input.clone(),
+                    account_id: Some(id),
+                    error: None,
+                    player: Some(p.clone()),
+                },
+                Some(Err(e)) => BatchRecord::failed(input, Some(id), e.clone()),
+                None => BatchRecord::failed(input, Some(id), "Player not found (no Steam profile).".into()),
+            },
+        })
+        .collect()
+}
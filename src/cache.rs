@@ -0,0 +1,140 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How `DeadlockClient` uses the on-disk response cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// Serve fresh entries, revalidate stale ones, store new responses.
+    #[default]
+    Normal,
+    /// Never read or write the cache (`--no-cache`).
+    Bypass,
+    /// Serve whatever is cached, fresh or not, and never touch the network (`--cache-only`).
+    Only,
+}
+
+/// A stored response body plus the validators needed to revalidate it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    /// Unix seconds when the body was last confirmed by the server
+    pub stored_at: u64,
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+impl CacheEntry {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now_secs().saturating_sub(self.stored_at))
+    }
+}
+
+/// Persistent HTTP response cache keyed by the full request URL (path + query).
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    mode: CacheMode,
+    ttls: Vec<(String, Duration)>,
+    default_ttl: Duration,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, mode: CacheMode) -> Self {
+        let ttls = vec![
+            // match metadata never changes once a match is over
+            ("/v1/matches/metadata".to_string(), Duration::from_secs(30 * 24 * 3600)),
+            ("/v1/players/steam".to_string(), Duration::from_secs(6 * 3600)),
+            ("/v1/players/hero-stats".to_string(), Duration::from_secs(30 * 60)),
+            ("/v1/players/mmr".to_string(), Duration::from_secs(10 * 60)),
+        ];
+        Self { dir: dir.into(), mode, ttls, default_ttl: Duration::from_secs(10 * 60) }
+    }
+
+    /// `DEADLOCK_CACHE_DIR`, else `$XDG_CACHE_HOME/deadlock-cli`, else `~/.cache/deadlock-cli`.
+    pub fn default_dir() -> PathBuf {
+        if let Ok(d) = std::env::var("DEADLOCK_CACHE_DIR") {
+            return PathBuf::from(d);
+        }
+        if let Ok(d) = std::env::var("XDG_CACHE_HOME") {
+            return PathBuf::from(d).join("deadlock-cli");
+        }
+        match std::env::var("HOME") {
+            Ok(h) => PathBuf::from(h).join(".cache").join("deadlock-cli"),
+            Err(_) => std::env::temp_dir().join("deadlock-cli-cache"),
+        }
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    pub fn ttl_for(&self, url: &Url) -> Duration {
+        let path = url.path();
+        self.ttls
+            .iter()
+            .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .map(|(_, ttl)| *ttl)
+            .unwrap_or(self.default_ttl)
+    }
+
+    pub fn is_fresh(&self, url: &Url, entry: &CacheEntry) -> bool {
+        entry.age() < self.ttl_for(url)
+    }
+
+    pub async fn load(&self, url: &Url) -> Option<CacheEntry> {
+        if self.mode == CacheMode::Bypass {
+            return None;
+        }
+        let raw = tokio::fs::read(self.path_for(url)).await.ok()?;
+        let entry: CacheEntry = serde_json::from_slice(&raw).ok()?;
+        // guard against hash collisions
+        (entry.url == url.as_str()).then_some(entry)
+    }
+
+    /// Best-effort write; a cache that cannot be written should never fail a lookup.
+    pub async fn store(&self, entry: &CacheEntry) {
+        if self.mode != CacheMode::Normal {
+            return;
+        }
+        let Ok(url) = Url::parse(&entry.url) else { return };
+        let Ok(raw) = serde_json::to_vec(entry) else { return };
+        if tokio::fs::create_dir_all(&self.dir).await.is_err() {
+            return;
+        }
+        let path = self.path_for(&url);
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        if tokio::fs::write(&tmp, raw).await.is_ok() && tokio::fs::rename(&tmp, &path).await.is_err() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+    }
+
+    /// Mark an entry as confirmed by the server (after a 304) and persist it.
+    pub async fn touch(&self, mut entry: CacheEntry) -> CacheEntry {
+        entry.stored_at = now_secs();
+        self.store(&entry).await;
+        entry
+    }
+
+    fn path_for(&self, url: &Url) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", fnv1a64(url.as_str().as_bytes())))
+    }
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// stable across Rust releases, unlike `DefaultHasher`
+pub fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h
+}
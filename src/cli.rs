@@ -1,3 +1,7 @@
+use crate::meta::MetaSort;
+use crate::models::{MatchMode, Rank, SortKey};
+use crate::synergy::MatrixKind;
+use chrono::NaiveDate;
 use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
@@ -6,6 +10,27 @@ pub struct Args {
     #[arg(long, global = true, help = "Output raw JSON instead of tables")]
     pub json: bool,
 
+    #[arg(long = "no-color", global = true, help = "Disable colours in charts (also honours NO_COLOR)")]
+    pub no_color: bool,
+
+    #[arg(long, global = true, help = "Render lookups from the local database only; no API calls")]
+    pub offline: bool,
+
+    #[arg(long = "no-cache", global = true, conflicts_with = "cache_only", help = "Bypass the on-disk API response cache")]
+    pub no_cache: bool,
+
+    #[arg(long = "cache-only", global = true, help = "Serve API responses from the on-disk cache only; never hit the network")]
+    pub cache_only: bool,
+
+    #[arg(long = "rps", global = true, default_value_t = 4.0, help = "Max Deadlock API requests per second")]
+    pub rps: f64,
+
+    #[arg(long = "record", global = true, value_name = "DIR", conflicts_with = "replay", help = "Write every API request/response to DIR as fixtures")]
+    pub record: Option<std::path::PathBuf>,
+
+    #[arg(long = "replay", global = true, value_name = "DIR", help = "Serve API responses from fixtures in DIR; no network")]
+    pub replay: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
@@ -42,62 +67,391 @@ pub enum Command {
         #[command(subcommand)]
         cmd: MatchesSubcommand,
     },
+
+    /// Hero analysis over every stored match
+    Heroes {
+        #[command(subcommand)]
+        cmd: HeroesSubcommand,
+    },
+
+    /// Look up many players at once from a file (or stdin): one identifier per line
+    Batch(BatchArgs),
+
+    /// Side-by-side comparison of two or more players; the best value per row is highlighted
+    Compare {
+        /// SteamID64s, SteamID3s, Steam2 IDs, account IDs, vanity names or profile URLs
+        #[arg(required = true, num_args = 2..)]
+        players: Vec<String>,
+    },
+
+    /// Head-to-head: stored matches two players shared, split into same team vs opposing teams
+    #[command(alias = "together")]
+    Vs(VsArgs),
+
+    /// Accounts a player most often plays with and against, from stored match scoreboards
+    Mates(MatesArgs),
+
+    /// Win rate and performance by party size (solo to full stack) and by party member
+    Party(PartyArgs),
+
+    /// Play sessions, win/loss streaks, tilt, in-session decay and best/worst times to play
+    Sessions(SessionsArgs),
+
+    /// Hero win rate, pick rate and KDA per game patch, with changes between consecutive patches
+    Patches(PatchesArgs),
+
+    /// Hero pick rate, win rate, KDA, damage and networth over every stored match
+    Meta(MetaArgs),
+
+    /// Interactive prompt: look up several players in one session, then `heroes`, `matches`, `compare`, ...
+    Shell,
+
+    /// Rank progression: per-match deltas, peak/lowest and a rolling average of player_score
+    Mmr(MmrArgs),
 }
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum MatchesSubcommand {
-    Sync {
-        #[arg(long = "id", value_delimiter = ',')]
-        ids: Vec<i64>,
+    Sync(SyncArgs),
 
-        #[arg(long = "from-account-id")]
-        from_account_id: Option<u32>,
+    History(HistoryArgs),
 
-        #[arg(long = "from-steamid")] 
-        from_steamid: Option<String>,
+    /// Browse a player's stored matches with filters, sorting and paging
+    List(ListArgs),
 
-        #[arg(long = "from-id3")]
-        from_id3: Option<String>,
+    /// Full scoreboard for one match, from the DB or the API when it was never synced
+    Show {
+        #[arg(long = "id")]
+        match_id: i64,
+    },
 
-        #[arg(long = "since-id")]
-        since_id: Option<i64>,
+    /// Show which guardians, walkers, shrines and Patron phases each team lost
+    Objectives {
+        #[arg(long = "id")]
+        match_id: i64,
+    },
+}
 
-        #[arg(long = "until-id")]
-        until_id: Option<i64>,
+#[derive(Subcommand, Debug, Clone)]
+pub enum HeroesSubcommand {
+    /// Win rate with each hero on the same team and against each opposing hero, relative to expectation
+    Synergy(SynergyArgs),
+}
 
-        #[arg(long, default_value_t = 500)]
-        limit: usize,
+/// The `--account-id` / `--steamid` / `--id3` trio shared by per-player commands.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct AccountArgs {
+    #[arg(long = "account-id")]
+    pub account_id: Option<u32>,
 
-        #[arg(long = "batch-size", default_value_t = 100)]
-        batch_size: usize,
+    #[arg(long = "steamid")]
+    pub steamid: Option<String>,
 
-        #[arg(long = "include-info", default_value_t = true)]
-        include_info: bool,
+    #[arg(long = "id3")]
+    pub id3: Option<String>,
+}
 
-        #[arg(long = "include-players", default_value_t = true)]
-        include_players: bool,
+impl AccountArgs {
+    pub fn is_empty(&self) -> bool {
+        self.account_id.is_none() && self.steamid.is_none() && self.id3.is_none()
+    }
+}
 
-        #[arg(long = "dry-run", default_value_t = false)]
-        dry_run: bool,
-    },
+#[derive(clap::Args, Debug, Clone)]
+pub struct BatchArgs {
+    /// File with SteamID64s, SteamID3s, Steam2 IDs, account IDs, vanity names or profile URLs; `-` or omitted reads stdin
+    #[arg(long, value_name = "FILE")]
+    pub file: Option<std::path::PathBuf>,
 
-    History {
-        #[arg(long = "account-id")]
-        account_id: Option<u32>,
+    /// Account IDs per multi-ID API request
+    #[arg(long = "chunk-size", default_value_t = 100)]
+    pub chunk_size: usize,
 
-        #[arg(long = "steamid")]
-        steamid: Option<String>,
+    /// Steam resolutions (vanity names, URLs) in flight at once
+    #[arg(long, default_value_t = 8)]
+    pub concurrency: usize,
+}
 
-        #[arg(long = "id3")]
-        id3: Option<String>,
+#[derive(clap::Args, Debug, Clone)]
+pub struct VsArgs {
+    /// First player (any identifier); win rates are from this player's side
+    pub a: String,
 
-        #[arg(long = "force-refetch", default_value_t = false)]
-        force_refetch: bool,
+    /// Second player (any identifier)
+    pub b: String,
 
-        #[arg(long = "only-stored-history", default_value_t = false)]
-        only_stored_history: bool,
+    /// Hero pairings listed per side
+    #[arg(long, default_value_t = 5)]
+    pub pairings: usize,
 
-        #[arg(long = "dry-run", default_value_t = false)]
-        dry_run: bool,
-    },
+    /// Latest shared matches listed
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct MatesArgs {
+    #[command(flatten)]
+    pub account: AccountArgs,
+
+    /// Count only party members as teammates (same non-zero party_id)
+    #[arg(long = "party-only")]
+    pub party_only: bool,
+
+    /// Leave out accounts seen fewer times than this
+    #[arg(long = "min-matches", default_value_t = 2)]
+    pub min_matches: usize,
+
+    /// Rows per list
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Fetch Steam profiles for listed accounts that have no name yet and store them
+    #[arg(long = "fetch-profiles")]
+    pub fetch_profiles: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct PartyArgs {
+    #[command(flatten)]
+    pub account: AccountArgs,
+
+    /// Leave out party members seen fewer times than this
+    #[arg(long = "min-matches", default_value_t = 2)]
+    pub min_matches: usize,
+
+    /// Party members listed
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct SessionsArgs {
+    #[command(flatten)]
+    pub account: AccountArgs,
+
+    /// Minutes between one match's end and the next start that begin a new session
+    #[arg(long, default_value_t = 30)]
+    pub gap: i64,
+
+    /// Sessions with at least this many matches count towards in-session decay
+    #[arg(long = "long-session", default_value_t = 4)]
+    pub long_session: usize,
+
+    /// Matches a time window needs to be named best or worst
+    #[arg(long = "min-matches", default_value_t = 5)]
+    pub min_matches: usize,
+
+    /// Hours from UTC for time-of-day and day-of-week windows (default: the local timezone)
+    #[arg(long = "utc-offset", allow_negative_numbers = true)]
+    pub utc_offset: Option<f64>,
+
+    /// Most recent sessions listed
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct PatchesArgs {
+    #[command(flatten)]
+    pub account: AccountArgs,
+
+    /// Every stored player instead of one account
+    #[arg(long, conflicts_with_all = ["account_id", "steamid", "id3"])]
+    pub all: bool,
+
+    /// Only this hero (name or ID)
+    #[arg(long)]
+    pub hero: Option<String>,
+
+    /// Most recent patches shown
+    #[arg(long, default_value_t = 5)]
+    pub patches: usize,
+
+    /// Leave out heroes with fewer matches on a patch
+    #[arg(long = "min-matches", default_value_t = 1)]
+    pub min_matches: usize,
+
+    /// Heroes listed per patch
+    #[arg(long, default_value_t = 15)]
+    pub limit: usize,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct MetaArgs {
+    /// Only matches whose average badge is in this rank (name or division number)
+    #[arg(long, conflicts_with_all = ["min_badge", "max_badge"])]
+    pub rank: Option<Rank>,
+
+    /// Lowest average badge, e.g. 71 for Archon 1
+    #[arg(long = "min-badge")]
+    pub min_badge: Option<i32>,
+
+    /// Highest average badge
+    #[arg(long = "max-badge")]
+    pub max_badge: Option<i32>,
+
+    /// Only matches from this region, e.g. Europe
+    #[arg(long)]
+    pub region: Option<String>,
+
+    /// Only matches on this patch version
+    #[arg(long)]
+    pub patch: Option<String>,
+
+    /// Heroes with fewer picks are flagged as low sample and listed last
+    #[arg(long = "min-sample", default_value_t = 30)]
+    pub min_sample: usize,
+
+    #[arg(long, value_enum, default_value_t = MetaSort::WinRate)]
+    pub sort: MetaSort,
+
+    /// Heroes listed
+    #[arg(long, default_value_t = 40)]
+    pub limit: usize,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct MmrArgs {
+    #[command(flatten)]
+    pub account: AccountArgs,
+
+    /// Snapshots in the rolling average
+    #[arg(long, default_value_t = 5)]
+    pub window: usize,
+
+    /// Rows shown in the timeline table
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct SyncArgs {
+    #[arg(long = "id", value_delimiter = ',')]
+    pub ids: Vec<i64>,
+
+    #[arg(long = "from-account-id")]
+    pub from_account_id: Option<u32>,
+
+    #[arg(long = "from-steamid")] 
+    pub from_steamid: Option<String>,
+
+    #[arg(long = "from-id3")]
+    pub from_id3: Option<String>,
+
+    #[arg(long = "since-id")]
+    pub since_id: Option<i64>,
+
+    #[arg(long = "until-id")]
+    pub until_id: Option<i64>,
+
+    #[arg(long, default_value_t = 500)]
+    pub limit: usize,
+
+    #[arg(long = "batch-size", default_value_t = 100)]
+    pub batch_size: usize,
+
+    #[arg(long = "include-info", default_value_t = true)]
+    pub include_info: bool,
+
+    #[arg(long = "include-players", default_value_t = true)]
+    pub include_players: bool,
+
+    #[arg(long = "dry-run", default_value_t = false)]
+    pub dry_run: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct HistoryArgs {
+    #[command(flatten)]
+    pub account: AccountArgs,
+
+    #[arg(long = "force-refetch", default_value_t = false)]
+    pub force_refetch: bool,
+
+    #[arg(long = "only-stored-history", default_value_t = false)]
+    pub only_stored_history: bool,
+
+    #[arg(long = "dry-run", default_value_t = false)]
+    pub dry_run: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ListArgs {
+    #[command(flatten)]
+    pub account: AccountArgs,
+
+    /// Hero name or ID
+    #[arg(long)]
+    pub hero: Option<String>,
+
+    /// First day to include (YYYY-MM-DD, UTC)
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+
+    /// Last day to include (YYYY-MM-DD, UTC)
+    #[arg(long)]
+    pub until: Option<NaiveDate>,
+
+    /// Match mode, e.g. ranked, unranked, private
+    #[arg(long)]
+    pub mode: Option<MatchMode>,
+
+    #[arg(long = "wins-only", default_value_t = false, conflicts_with = "losses_only")]
+    pub wins_only: bool,
+
+    #[arg(long = "losses-only", default_value_t = false)]
+    pub losses_only: bool,
+
+    #[arg(long, value_enum, default_value_t = SortKey::Date)]
+    pub sort: SortKey,
+
+    /// Sort ascending instead of descending
+    #[arg(long, default_value_t = false)]
+    pub asc: bool,
+
+    #[arg(long, default_value_t = 1)]
+    pub page: usize,
+
+    #[arg(long = "per-page", default_value_t = 20)]
+    pub per_page: usize,
+
+    /// Print every matching row as CSV instead of a table
+    #[arg(long, default_value_t = false, conflicts_with = "json")]
+    pub csv: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct SynergyArgs {
+    /// Hero to report on (name or ID); leave out to export the full matrix with --csv or --json
+    #[arg(long)]
+    pub hero: Option<String>,
+
+    /// Matrix exported without --hero
+    #[arg(long, value_enum, default_value_t = MatrixKind::Synergy)]
+    pub matrix: MatrixKind,
+
+    /// Print the matrix as CSV (row hero's delta vs expectation, in percentage points)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["json", "hero"])]
+    pub csv: bool,
+
+    /// Leave out pairs seen in fewer matches
+    #[arg(long = "min-matches", default_value_t = 10)]
+    pub min_matches: usize,
+
+    /// Only matches whose average badge is in this rank (name or division number)
+    #[arg(long)]
+    pub rank: Option<Rank>,
+
+    /// Only matches from this region
+    #[arg(long)]
+    pub region: Option<String>,
+
+    /// Only matches on this patch version
+    #[arg(long)]
+    pub patch: Option<String>,
+
+    /// Teammates and opponents listed with --hero
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
 }
@@ -0,0 +1,179 @@
+use crate::models::{Badge, CombinedPayload, HeroStats};
+use serde::Serialize;
+
+/// Heroes listed per player under "most played".
+pub const TOP_HEROES: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopHero {
+    pub hero_id: i32,
+    pub matches_played: i64,
+    pub win_rate: Option<f64>,
+}
+
+/// One player's totals across all heroes. Per-minute rates are weighted by time played,
+/// accuracy by matches.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerSummary {
+    pub account_id: u32,
+    pub personaname: String,
+    /// `division * 10 + tier`
+    pub rank: Option<i32>,
+    pub badge: Option<Badge>,
+    pub matches: i64,
+    pub wins: i64,
+    /// Percent
+    pub win_rate: Option<f64>,
+    pub kills_per_min: Option<f64>,
+    pub deaths_per_min: Option<f64>,
+    pub assists_per_min: Option<f64>,
+    pub damage_per_min: Option<f64>,
+    /// Fraction, 0-1
+    pub accuracy: Option<f64>,
+    pub top_heroes: Vec<TopHero>,
+}
+
+fn weighted(stats: &[HeroStats], value: impl Fn(&HeroStats) -> Option<f64>, weight: impl Fn(&HeroStats) -> Option<f64>) -> Option<f64> {
+    let (sum, total) = stats
+        .iter()
+        .filter_map(|h| Some((value(h)?, weight(h).filter(|w| *w > 0.0)?)))
+        .fold((0.0, 0.0), |(s, t), (v, w)| (s + v * w, t + w));
+    (total > 0.0).then(|| sum / total)
+}
+
+impl PlayerSummary {
+    pub fn from_payload(p: &CombinedPayload) -> Self {
+        let stats = &p.hero_stats;
+        let matches: i64 = stats.iter().filter_map(|h| h.matches_played).sum();
+        let wins: i64 = stats.iter().filter_map(|h| h.wins).sum();
+        let time = |h: &HeroStats| h.time_played.map(|t| t as f64);
+        let games = |h: &HeroStats| h.matches_played.map(|m| m as f64);
+
+        let mut top: Vec<&HeroStats> = stats.iter().filter(|h| h.matches_played.unwrap_or_default() > 0).collect();
+        top.sort_by_key(|h| std::cmp::Reverse(h.matches_played));
+        let top_heroes = top
+            .into_iter()
+            .take(TOP_HEROES)
+            .map(|h| {
+                let played = h.matches_played.unwrap_or_default();
+                TopHero { hero_id: h.hero_id, matches_played: played, win_rate: Some(h.wins.unwrap_or_default() as f64 / played as f64 * 100.0) }
+            })
+            .collect();
+
+        Self {
+            account_id: p.account_id,
+            personaname: p.profile.personaname.clone(),
+            rank: p.latest_mmr.as_ref().map(|m| m.rank),
+            badge: p.latest_mmr.as_ref().and_then(|m| m.badge()),
+            matches,
+            wins,
+            win_rate: (matches > 0).then(|| wins as f64 / matches as f64 * 100.0),
+            kills_per_min: weighted(stats, |h| h.kills_per_min, time),
+            deaths_per_min: weighted(stats, |h| h.deaths_per_min, time),
+            assists_per_min: weighted(stats, |h| h.assists_per_min, time),
+            damage_per_min: weighted(stats, |h| h.damage_per_min, time),
+            accuracy: weighted(stats, |h| h.accuracy, games),
+            top_heroes,
+        }
+    }
+}
+
+/// A comparable row of the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    Rank,
+    Matches,
+    WinRate,
+    KillsPerMin,
+    DeathsPerMin,
+    AssistsPerMin,
+    DamagePerMin,
+    Accuracy,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 8] = [
+        Metric::Rank,
+        Metric::Matches,
+        Metric::WinRate,
+        Metric::KillsPerMin,
+        Metric::DeathsPerMin,
+        Metric::AssistsPerMin,
+        Metric::DamagePerMin,
+        Metric::Accuracy,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Rank => "Rank",
+            Metric::Matches => "Matches",
+            Metric::WinRate => "Win Rate",
+            Metric::KillsPerMin => "KPM",
+            Metric::DeathsPerMin => "Deaths/min",
+            Metric::AssistsPerMin => "APM",
+            Metric::DamagePerMin => "Damage/min",
+            Metric::Accuracy => "Accuracy",
+        }
+    }
+
+    /// Fewer deaths is the only row where lower wins.
+    pub fn higher_is_better(self) -> bool {
+        self != Metric::DeathsPerMin
+    }
+
+    pub fn value(self, s: &PlayerSummary) -> Option<f64> {
+        match self {
+            Metric::Rank => s.rank.map(f64::from),
+            Metric::Matches => Some(s.matches as f64),
+            Metric::WinRate => s.win_rate,
+            Metric::KillsPerMin => s.kills_per_min,
+            Metric::DeathsPerMin => s.deaths_per_min,
+            Metric::AssistsPerMin => s.assists_per_min,
+            Metric::DamagePerMin => s.damage_per_min,
+            Metric::Accuracy => s.accuracy,
+        }
+    }
+}
+
+/// Which players lead a metric; ties share it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Leader {
+    pub metric: Metric,
+    pub account_ids: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub players: Vec<PlayerSummary>,
+    pub best: Vec<Leader>,
+}
+
+impl Comparison {
+    pub fn new(payloads: &[&CombinedPayload]) -> Self {
+        let players: Vec<PlayerSummary> = payloads.iter().map(|p| PlayerSummary::from_payload(p)).collect();
+        let best = Metric::ALL
+            .iter()
+            .map(|&metric| Leader {
+                metric,
+                account_ids: best_indices(&players, metric).into_iter().map(|i| players[i].account_id).collect(),
+            })
+            .collect();
+        Self { players, best }
+    }
+
+    pub fn is_best(&self, metric: Metric, account_id: u32) -> bool {
+        self.best.iter().any(|l| l.metric == metric && l.account_ids.contains(&account_id))
+    }
+}
+
+/// Indices of the players with the best value; none when fewer than two players have one.
+pub fn best_indices(players: &[PlayerSummary], metric: Metric) -> Vec<usize> {
+    let values: Vec<(usize, f64)> = players.iter().enumerate().filter_map(|(i, p)| Some((i, metric.value(p)?))).collect();
+    if values.len() < 2 {
+        return Vec::new();
+    }
+    let pick = if metric.higher_is_better() { f64::max } else { f64::min };
+    let Some(target) = values.iter().map(|(_, v)| *v).reduce(pick) else { return Vec::new() };
+    values.into_iter().filter(|(_, v)| (v - target).abs() < 1e-9).map(|(i, _)| i).collect()
+}
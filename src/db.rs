@@ -1,5 +1,7 @@
-use crate::models::{HeroStats, MMRHistory, MatchMeta, PlayerInMatch, SteamProfile};
-use crate::ui::CombinedPayload;
+use crate::models::{
+    CoPlayer, CombinedPayload, Hero, HeroRecord, HeroStats, HeroTotals, MMRHistory, MatchMeta, MatchMode, MatchObjectives, MatchQuery, MetaFilter, MetaTotals, ObjectivesMask, PairRecord,
+    PerformanceScore, PlayerInMatch, PlayerMatch, RecentLookup, SortKey, SteamProfile, StoredScore, SynergyTotals, Team,
+};
 use anyhow::Result;
 use chrono::{DateTime, TimeZone, Utc};
 use serde_json::Value;
@@ -105,13 +107,14 @@ pub struct MatchesIngestResult {
     pub matches_upserted: usize,
     pub match_players_upserted: usize,
     pub players_upserted: usize,
+    pub objectives_upserted: usize,
 }
 
 pub async fn ingest_matches_batch(pool: &PgPool, metas: &[MatchMeta]) -> Result<MatchesIngestResult> {
     let mut tx = pool.begin().await?;
     let mut out = MatchesIngestResult::default();
     for m in metas {
-        let start_time = m.start_time.map(|s| ts_from_epoch_secs(s as i64));
+        let start_time = m.start_time.map(ts_from_epoch_secs);
         let info_json = m.info.clone().unwrap_or_else(|| serde_json::json!({}));
         sqlx::query(
             r#"
@@ -132,7 +135,7 @@ ON CONFLICT (match_id) DO UPDATE SET
         .bind(m.match_id)
         .bind(start_time)
         .bind(m.duration_s)
-        .bind(&m.winner_team)
+        .bind(m.winner_team.map(|t| t.to_string()))
         .bind(m.average_badge)
         .bind(&m.region)
         .bind(&m.patch_version)
@@ -141,6 +144,10 @@ ON CONFLICT (match_id) DO UPDATE SET
         .await?;
         out.matches_upserted += 1;
 
+        if let Some(objectives) = m.objectives() {
+            out.objectives_upserted += upsert_match_objectives(&mut tx, m.match_id, &objectives).await?;
+        }
+
         if let Some(players) = &m.players {
             for p in players {
                 ensure_player_stub(&mut tx, p.account_id as i64).await?;
@@ -150,10 +157,585 @@ ON CONFLICT (match_id) DO UPDATE SET
             }
         }
     }
+
+    // a new or updated row shifts its whole lobby's scores
+    let rescore: Vec<i64> = metas.iter().filter(|m| m.players.as_ref().is_some_and(|p| !p.is_empty())).map(|m| m.match_id).collect();
+    if !rescore.is_empty() {
+        sqlx::query("SELECT score_matches($1)").bind(&rescore).execute(&mut *tx).await?;
+    }
     tx.commit().await?;
     Ok(out)
 }
 
+async fn upsert_match_objectives(tx: &mut Transaction<'_, Postgres>, match_id: i64, o: &MatchObjectives) -> Result<usize> {
+    let mut n = 0;
+    for (team, mask) in [(Team::Team0, o.team0), (Team::Team1, o.team1)] {
+        for s in mask.statuses() {
+            sqlx::query!(
+                r#"
+INSERT INTO match_objectives (match_id, team, objective, lane, bit, destroyed)
+VALUES ($1,$2,$3,$4,$5,$6)
+ON CONFLICT (match_id, team, bit) DO UPDATE SET
+  objective = EXCLUDED.objective,
+  lane = EXCLUDED.lane,
+  destroyed = EXCLUDED.destroyed;
+                "#,
+                match_id,
+                team.to_string(),
+                s.objective.kind.as_str(),
+                s.objective.lane.map(i32::from).unwrap_or(0),
+                i32::from(s.objective.bit),
+                s.destroyed
+            )
+            .execute(&mut **tx)
+            .await?;
+            n += 1;
+        }
+    }
+    Ok(n)
+}
+
+/// Objective state of a stored match, rebuilt from `match_objectives`.
+pub async fn load_match_objectives(pool: &PgPool, match_id: i64) -> Result<Option<MatchObjectives>> {
+    let rows = sqlx::query!(
+        r#"SELECT team, bit, destroyed FROM match_objectives WHERE match_id = $1 ORDER BY team, bit"#,
+        match_id
+    )
+    .fetch_all(pool)
+    .await?;
+    if rows.is_empty() {
+        return Ok(None);
+    }
+    let mut out = MatchObjectives::default();
+    for r in rows.iter().filter(|r| r.destroyed) {
+        let mask = match parse_team(Some(&r.team))? {
+            Some(Team::Team0) => &mut out.team0,
+            Some(Team::Team1) => &mut out.team1,
+            _ => continue,
+        };
+        *mask = ObjectivesMask(mask.0 | (1 << r.bit));
+    }
+    Ok(Some(out))
+}
+
+/// A stored match with every stored participant. Returns `None` when the match was never synced.
+pub async fn load_match(pool: &PgPool, match_id: i64) -> Result<Option<MatchMeta>> {
+    let m = sqlx::query!(
+        r#"
+SELECT start_time, duration_s, winner_team, average_badge, region, patch_version, info_json
+FROM matches
+WHERE match_id = $1
+        "#,
+        match_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    let Some(m) = m else { return Ok(None) };
+
+    let players = sqlx::query!(
+        r#"
+SELECT account_id, hero_id, team, party_id, lane, is_victory, kills, deaths, assists,
+       networth, damage, damage_taken, obj_damage, last_hits, accuracy, crit_shot_rate, extra_json
+FROM match_players
+WHERE match_id = $1
+ORDER BY team, account_id
+        "#,
+        match_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|p| {
+        Ok(PlayerInMatch {
+            account_id: p.account_id as i32,
+            hero_id: p.hero_id,
+            team: parse_team(p.team.as_deref())?,
+            party_id: p.party_id,
+            lane: p.lane,
+            is_victory: p.is_victory,
+            kills: p.kills,
+            deaths: p.deaths,
+            assists: p.assists,
+            networth: p.networth,
+            damage: p.damage,
+            damage_taken: p.damage_taken,
+            obj_damage: p.obj_damage,
+            last_hits: p.last_hits,
+            accuracy: p.accuracy,
+            crit_shot_rate: p.crit_shot_rate,
+            extra: Some(p.extra_json),
+        })
+    })
+    .collect::<Result<_>>()?;
+
+    let info = Some(m.info_json).filter(|v| v.as_object().is_some_and(|o| !o.is_empty()));
+    Ok(Some(MatchMeta {
+        match_id,
+        start_time: m.start_time.map(|t| t.timestamp()),
+        duration_s: m.duration_s,
+        winner_team: parse_team(m.winner_team.as_deref())?,
+        average_badge: m.average_badge,
+        region: m.region,
+        patch_version: m.patch_version,
+        info,
+        players: Some(players),
+    }))
+}
+
+/// Every stored match of one account, newest first.
+pub async fn load_player_matches(pool: &PgPool, account_id: u32) -> Result<Vec<PlayerMatch>> {
+    load_match_rows(pool, Some(account_id as i64)).await
+}
+
+/// Every stored participant row of every match, newest first.
+pub async fn load_all_player_matches(pool: &PgPool) -> Result<Vec<PlayerMatch>> {
+    load_match_rows(pool, None).await
+}
+
+/// How many of the account's stored matches pass `query`.
+pub async fn count_player_matches(pool: &PgPool, query: &MatchQuery) -> Result<usize> {
+    let n = sqlx::query_scalar!(
+        r#"
+SELECT COUNT(*) AS "n!"
+FROM (
+  SELECT mp.hero_id, mp.is_victory, m.start_time,
+         COALESCE(
+           CASE WHEN jsonb_typeof(mp.extra_json->'match_mode') = 'number' THEN (mp.extra_json->>'match_mode')::int END,
+           CASE WHEN jsonb_typeof(m.info_json->'match_mode') = 'number' THEN (m.info_json->>'match_mode')::int END
+         ) AS match_mode
+  FROM match_players mp
+  JOIN matches m ON m.match_id = mp.match_id
+  WHERE mp.account_id = $1
+) r
+WHERE ($2::INT IS NULL OR r.hero_id = $2)
+  AND ($3::TIMESTAMPTZ IS NULL OR r.start_time >= $3)
+  AND ($4::TIMESTAMPTZ IS NULL OR r.start_time < $4)
+  AND ($5::INT IS NULL OR r.match_mode = $5)
+  AND ($6::BOOLEAN IS NULL OR r.is_victory = $6)
+        "#,
+        query.account_id as i64,
+        query.hero_id,
+        query.since.map(ts_from_epoch_secs),
+        query.until.map(ts_from_epoch_secs),
+        query.mode.map(i32::from),
+        query.victory
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(n as usize)
+}
+
+/// The account's stored matches that pass `query`, in its order; `limit: None` loads them all.
+pub async fn load_player_matches_filtered(pool: &PgPool, query: &MatchQuery, limit: Option<usize>, offset: usize) -> Result<Vec<PlayerMatch>> {
+    let sort = match query.sort {
+        SortKey::Date => "date",
+        SortKey::Duration => "duration",
+        SortKey::Kills => "kills",
+        SortKey::Deaths => "deaths",
+        SortKey::Assists => "assists",
+        SortKey::Kda => "kda",
+        SortKey::Networth => "networth",
+        SortKey::Damage => "damage",
+        SortKey::LastHits => "last_hits",
+        SortKey::Accuracy => "accuracy",
+    };
+    let rows = sqlx::query!(
+        r#"
+SELECT r.match_id AS "match_id!", r.start_time, r.duration_s, r.hero_id, r.team, r.party_id, r.is_victory, r.match_mode,
+       r.kills, r.deaths, r.assists, r.networth, r.damage, r.last_hits, r.accuracy,
+       r.average_badge, r.region, r.patch_version
+FROM (
+  SELECT mp.match_id, m.start_time, m.duration_s, mp.hero_id, mp.team, mp.party_id, mp.is_victory,
+         COALESCE(
+           CASE WHEN jsonb_typeof(mp.extra_json->'match_mode') = 'number' THEN (mp.extra_json->>'match_mode')::int END,
+           CASE WHEN jsonb_typeof(m.info_json->'match_mode') = 'number' THEN (m.info_json->>'match_mode')::int END
+         ) AS match_mode,
+         mp.kills, mp.deaths, mp.assists, mp.networth, mp.damage, mp.last_hits, mp.accuracy,
+         m.average_badge, m.region, m.patch_version
+  FROM match_players mp
+  JOIN matches m ON m.match_id = mp.match_id
+  WHERE mp.account_id = $1
+) r
+CROSS JOIN LATERAL (
+  SELECT CASE $7::TEXT
+           WHEN 'duration' THEN r.duration_s::FLOAT8
+           WHEN 'kills' THEN r.kills::FLOAT8
+           WHEN 'deaths' THEN r.deaths::FLOAT8
+           WHEN 'assists' THEN r.assists::FLOAT8
+           WHEN 'kda' THEN CASE WHEN r.deaths IS NOT NULL THEN (r.kills + r.assists)::FLOAT8 / GREATEST(r.deaths, 1) END
+           WHEN 'networth' THEN r.networth::FLOAT8
+           WHEN 'damage' THEN r.damage::FLOAT8
+           WHEN 'last_hits' THEN r.last_hits::FLOAT8
+           WHEN 'accuracy' THEN r.accuracy::FLOAT8
+           ELSE EXTRACT(EPOCH FROM r.start_time)::FLOAT8
+         END AS v
+) s
+WHERE ($2::INT IS NULL OR r.hero_id = $2)
+  AND ($3::TIMESTAMPTZ IS NULL OR r.start_time >= $3)
+  AND ($4::TIMESTAMPTZ IS NULL OR r.start_time < $4)
+  AND ($5::INT IS NULL OR r.match_mode = $5)
+  AND ($6::BOOLEAN IS NULL OR r.is_victory = $6)
+ORDER BY s.v IS NULL,
+         CASE WHEN $8::BOOLEAN THEN s.v END ASC,
+         CASE WHEN NOT $8::BOOLEAN THEN s.v END DESC,
+         r.start_time DESC NULLS LAST, r.match_id DESC
+LIMIT $9 OFFSET $10
+        "#,
+        query.account_id as i64,
+        query.hero_id,
+        query.since.map(ts_from_epoch_secs),
+        query.until.map(ts_from_epoch_secs),
+        query.mode.map(i32::from),
+        query.victory,
+        sort,
+        query.ascending,
+        limit.map(|n| n as i64),
+        offset as i64
+    )
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(|r| {
+            Ok(PlayerMatch {
+                match_id: r.match_id,
+                start_time: r.start_time.map(|t| t.timestamp()),
+                duration_s: r.duration_s,
+                hero_id: r.hero_id,
+                team: parse_team(r.team.as_deref())?,
+                party_id: r.party_id,
+                is_victory: r.is_victory,
+                match_mode: r.match_mode.map(MatchMode::from),
+                kills: r.kills,
+                deaths: r.deaths,
+                assists: r.assists,
+                networth: r.networth,
+                damage: r.damage,
+                last_hits: r.last_hits,
+                accuracy: r.accuracy,
+                average_badge: r.average_badge,
+                region: r.region,
+                patch_version: r.patch_version,
+                score: None,
+            })
+        })
+        .collect()
+}
+
+async fn load_match_rows(pool: &PgPool, account_id: Option<i64>) -> Result<Vec<PlayerMatch>> {
+    let rows = sqlx::query!(
+        r#"
+SELECT mp.match_id, m.start_time, m.duration_s, mp.hero_id, mp.team, mp.party_id, mp.is_victory,
+       COALESCE(
+         CASE WHEN jsonb_typeof(mp.extra_json->'match_mode') = 'number' THEN (mp.extra_json->>'match_mode')::int END,
+         CASE WHEN jsonb_typeof(m.info_json->'match_mode') = 'number' THEN (m.info_json->>'match_mode')::int END
+       ) AS match_mode,
+       mp.kills, mp.deaths, mp.assists, mp.networth, mp.damage, mp.last_hits, mp.accuracy,
+       m.average_badge, m.region, m.patch_version
+FROM match_players mp
+JOIN matches m ON m.match_id = mp.match_id
+WHERE $1::BIGINT IS NULL OR mp.account_id = $1
+ORDER BY m.start_time DESC NULLS LAST, mp.match_id DESC, mp.account_id
+        "#,
+        account_id
+    )
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(|r| {
+            Ok(PlayerMatch {
+                match_id: r.match_id,
+                start_time: r.start_time.map(|t| t.timestamp()),
+                duration_s: r.duration_s,
+                hero_id: r.hero_id,
+                team: parse_team(r.team.as_deref())?,
+                party_id: r.party_id,
+                is_victory: r.is_victory,
+                match_mode: r.match_mode.map(MatchMode::from),
+                kills: r.kills,
+                deaths: r.deaths,
+                assists: r.assists,
+                networth: r.networth,
+                damage: r.damage,
+                last_hits: r.last_hits,
+                accuracy: r.accuracy,
+                average_badge: r.average_badge,
+                region: r.region,
+                patch_version: r.patch_version,
+                score: None,
+            })
+        })
+        .collect()
+}
+
+/// Per-hero totals over the stored matches that pass `filter`, grouped by the database.
+pub async fn load_meta_totals(pool: &PgPool, filter: &MetaFilter) -> Result<MetaTotals> {
+    let matches = sqlx::query_scalar!(
+        r#"
+SELECT COUNT(DISTINCT mp.match_id) AS "matches!"
+FROM match_players mp
+JOIN matches m ON m.match_id = mp.match_id
+WHERE ($1::INT IS NULL OR m.average_badge >= $1)
+  AND ($2::INT IS NULL OR m.average_badge <= $2)
+  AND ($3::TEXT IS NULL OR LOWER(m.region) = LOWER($3))
+  AND ($4::TEXT IS NULL OR m.patch_version = $4)
+        "#,
+        filter.min_badge,
+        filter.max_badge,
+        filter.region,
+        filter.patch
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let rows = sqlx::query!(
+        r#"
+SELECT mp.hero_id AS "hero_id!",
+       COUNT(*) AS "picks!",
+       COUNT(mp.is_victory) AS "decided!",
+       COUNT(*) FILTER (WHERE mp.is_victory) AS "wins!",
+       COALESCE(SUM(mp.kills), 0)::BIGINT AS "kills!",
+       COALESCE(SUM(mp.deaths), 0)::BIGINT AS "deaths!",
+       COALESCE(SUM(mp.assists), 0)::BIGINT AS "assists!",
+       AVG(mp.damage)::FLOAT8 AS avg_damage,
+       AVG(mp.networth)::FLOAT8 AS avg_networth
+FROM match_players mp
+JOIN matches m ON m.match_id = mp.match_id
+WHERE mp.hero_id IS NOT NULL
+  AND ($1::INT IS NULL OR m.average_badge >= $1)
+  AND ($2::INT IS NULL OR m.average_badge <= $2)
+  AND ($3::TEXT IS NULL OR LOWER(m.region) = LOWER($3))
+  AND ($4::TEXT IS NULL OR m.patch_version = $4)
+GROUP BY mp.hero_id
+        "#,
+        filter.min_badge,
+        filter.max_badge,
+        filter.region,
+        filter.patch
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(MetaTotals {
+        matches: matches as usize,
+        heroes: rows
+            .into_iter()
+            .map(|r| HeroTotals {
+                hero_id: r.hero_id,
+                picks: r.picks as usize,
+                decided: r.decided as usize,
+                wins: r.wins as usize,
+                kills: r.kills,
+                deaths: r.deaths,
+                assists: r.assists,
+                avg_damage: r.avg_damage,
+                avg_networth: r.avg_networth,
+            })
+            .collect(),
+    })
+}
+
+/// Hero and hero-pair win counts over the rows of the stored matches that pass `filter` and
+/// have a hero, a known team and a result; pairs come from a self-join on the match.
+pub async fn load_synergy_totals(pool: &PgPool, filter: &MetaFilter) -> Result<SynergyTotals> {
+    let matches = sqlx::query_scalar!(
+        r#"
+SELECT COUNT(DISTINCT mp.match_id) AS "matches!"
+FROM match_players mp
+JOIN matches m ON m.match_id = mp.match_id
+WHERE mp.hero_id IS NOT NULL
+  AND mp.team IN ('team0', 'team1')
+  AND mp.is_victory IS NOT NULL
+  AND ($1::INT IS NULL OR m.average_badge >= $1)
+  AND ($2::INT IS NULL OR m.average_badge <= $2)
+  AND ($3::TEXT IS NULL OR LOWER(m.region) = LOWER($3))
+  AND ($4::TEXT IS NULL OR m.patch_version = $4)
+        "#,
+        filter.min_badge,
+        filter.max_badge,
+        filter.region,
+        filter.patch
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let heroes = sqlx::query!(
+        r#"
+SELECT mp.hero_id AS "hero_id!", COUNT(*) AS "matches!", COUNT(*) FILTER (WHERE mp.is_victory) AS "wins!"
+FROM match_players mp
+JOIN matches m ON m.match_id = mp.match_id
+WHERE mp.hero_id IS NOT NULL
+  AND mp.team IN ('team0', 'team1')
+  AND mp.is_victory IS NOT NULL
+  AND ($1::INT IS NULL OR m.average_badge >= $1)
+  AND ($2::INT IS NULL OR m.average_badge <= $2)
+  AND ($3::TEXT IS NULL OR LOWER(m.region) = LOWER($3))
+  AND ($4::TEXT IS NULL OR m.patch_version = $4)
+GROUP BY mp.hero_id
+        "#,
+        filter.min_badge,
+        filter.max_badge,
+        filter.region,
+        filter.patch
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let pairs = sqlx::query!(
+        r#"
+WITH usable AS (
+  SELECT mp.match_id, mp.hero_id, mp.team, mp.is_victory
+  FROM match_players mp
+  JOIN matches m ON m.match_id = mp.match_id
+  WHERE mp.hero_id IS NOT NULL
+    AND mp.team IN ('team0', 'team1')
+    AND mp.is_victory IS NOT NULL
+    AND ($1::INT IS NULL OR m.average_badge >= $1)
+    AND ($2::INT IS NULL OR m.average_badge <= $2)
+    AND ($3::TEXT IS NULL OR LOWER(m.region) = LOWER($3))
+    AND ($4::TEXT IS NULL OR m.patch_version = $4)
+)
+SELECT a.hero_id AS "hero_id!", b.hero_id AS "other_hero_id!", (a.team = b.team) AS "same_team!",
+       COUNT(*) AS "matches!", COUNT(*) FILTER (WHERE a.is_victory) AS "wins!"
+FROM usable a
+JOIN usable b ON b.match_id = a.match_id AND b.hero_id <> a.hero_id
+GROUP BY a.hero_id, b.hero_id, (a.team = b.team)
+        "#,
+        filter.min_badge,
+        filter.max_badge,
+        filter.region,
+        filter.patch
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(SynergyTotals {
+        matches: matches as usize,
+        heroes: heroes
+            .into_iter()
+            .map(|r| HeroRecord { hero_id: r.hero_id, matches: r.matches as usize, wins: r.wins as usize })
+            .collect(),
+        pairs: pairs
+            .into_iter()
+            .map(|r| PairRecord {
+                hero_id: r.hero_id,
+                other_hero_id: r.other_hero_id,
+                same_team: r.same_team,
+                matches: r.matches as usize,
+                wins: r.wins as usize,
+            })
+            .collect(),
+    })
+}
+
+/// Everyone else in the account's stored matches, with the account's own team, party and result.
+pub async fn load_co_players(pool: &PgPool, account_id: u32) -> Result<Vec<CoPlayer>> {
+    let rows = sqlx::query!(
+        r#"
+SELECT o.match_id, m.start_time, o.account_id, p.personaname, o.team, o.party_id,
+       me.team AS my_team, me.party_id AS my_party_id, me.is_victory
+FROM match_players me
+JOIN match_players o ON o.match_id = me.match_id AND o.account_id <> me.account_id
+JOIN matches m ON m.match_id = me.match_id
+LEFT JOIN players p ON p.account_id = o.account_id
+WHERE me.account_id = $1
+        "#,
+        account_id as i64
+    )
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(|r| {
+            Ok(CoPlayer {
+                match_id: r.match_id,
+                start_time: r.start_time.map(|t| t.timestamp()),
+                account_id: r.account_id as u32,
+                personaname: r.personaname,
+                team: parse_team(r.team.as_deref())?,
+                party_id: r.party_id,
+                my_team: parse_team(r.my_team.as_deref())?,
+                my_party_id: r.my_party_id,
+                is_victory: r.is_victory,
+            })
+        })
+        .collect()
+}
+
+/// Persisted scores of `match_ids` (only `account_id`'s when given), each ranked among the
+/// stored games on the same hero.
+pub async fn load_scores(pool: &PgPool, match_ids: &[i64], account_id: Option<u32>) -> Result<Vec<StoredScore>> {
+    let rows = sqlx::query!(
+        r#"
+SELECT s.match_id, s.account_id, s.score, h.below AS "below!", h.equal AS "equal!", h.total AS "total!"
+FROM match_player_scores s
+CROSS JOIN LATERAL (
+  SELECT COUNT(*) FILTER (WHERE o.score < s.score) AS below,
+         COUNT(*) FILTER (WHERE o.score = s.score) AS equal,
+         COUNT(*) AS total
+  FROM match_player_scores o
+  WHERE o.hero_id = s.hero_id
+) h
+WHERE s.match_id = ANY($1) AND ($2::BIGINT IS NULL OR s.account_id = $2)
+        "#,
+        match_ids,
+        account_id.map(i64::from)
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| StoredScore {
+            match_id: r.match_id,
+            account_id: r.account_id as u32,
+            score: PerformanceScore {
+                score: r.score,
+                // ties count half
+                percentile: (r.below as f64 + r.equal as f64 / 2.0) / r.total as f64 * 100.0,
+                hero_matches: r.total as usize,
+            },
+        })
+        .collect())
+}
+
+/// Stored MMR snapshots of one account, oldest first.
+pub async fn load_mmr_history(pool: &PgPool, account_id: u32) -> Result<Vec<MMRHistory>> {
+    let rows = sqlx::query!(
+        r#"
+SELECT match_id, start_time, player_score, rank, division, division_tier
+FROM mmr_history
+WHERE account_id = $1
+ORDER BY start_time
+        "#,
+        account_id as i64
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|m| MMRHistory {
+            account_id: account_id as i32,
+            match_id: m.match_id.unwrap_or_default(),
+            start_time: m.start_time.timestamp() as i32,
+            player_score: m.player_score.unwrap_or_default(),
+            rank: m.rank.unwrap_or_default(),
+            division: m.division.unwrap_or_default(),
+            division_tier: m.division_tier.unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Append MMR snapshots to `mmr_history`; already stored snapshots are skipped. Returns how
+/// many were new.
+pub async fn store_mmr_history(pool: &PgPool, account_id: u32, history: &[MMRHistory]) -> Result<usize> {
+    let mut tx = pool.begin().await?;
+    ensure_player_stub(&mut tx, account_id as i64).await?;
+    let mut added = 0;
+    for m in history {
+        added += insert_mmr_history(&mut tx, account_id as i64, m).await? as usize;
+    }
+    tx.commit().await?;
+    Ok(added)
+}
+
 async fn ensure_player_stub(tx: &mut Transaction<'_, Postgres>, account_id: i64) -> Result<()> {
     let steamid64 = crate::steam::account_id_to_steamid64(account_id as u32);
     sqlx::query(
@@ -205,7 +787,7 @@ ON CONFLICT (match_id, account_id) DO UPDATE SET
     .bind(match_id)
     .bind(p.account_id as i64)
     .bind(p.hero_id)
-    .bind(&p.team)
+    .bind(p.team.map(|t| t.to_string()))
     .bind(p.party_id)
     .bind(&p.lane)
     .bind(p.is_victory)
@@ -237,9 +819,9 @@ async fn upsert_player(tx: &mut Transaction<'_, Postgres>, account_id: i64, stea
         r#"
 INSERT INTO players (
   account_id, steamid64, personaname, profileurl, avatar, avatarmedium, avatarfull,
-  countrycode, realname, profile_extra, profile_updated_at
+  countrycode, realname, profile_extra, profile_updated_at, last_lookup_at
 ) VALUES (
-  $1,$2,$3,$4,$5,$6,$7,$8,$9, COALESCE($10::jsonb, '{}'::jsonb), $11
+  $1,$2,$3,$4,$5,$6,$7,$8,$9, COALESCE($10::jsonb, '{}'::jsonb), $11, now()
 )
 ON CONFLICT (account_id) DO UPDATE SET
   steamid64 = EXCLUDED.steamid64,
@@ -251,7 +833,8 @@ ON CONFLICT (account_id) DO UPDATE SET
   countrycode = EXCLUDED.countrycode,
   realname = EXCLUDED.realname,
   profile_extra = players.profile_extra || EXCLUDED.profile_extra,
-  profile_updated_at = GREATEST(players.profile_updated_at, EXCLUDED.profile_updated_at);
+  profile_updated_at = GREATEST(players.profile_updated_at, EXCLUDED.profile_updated_at),
+  last_lookup_at = now();
         "#,
         account_id,
         steamid64,
@@ -301,10 +884,11 @@ ON CONFLICT (account_id) DO UPDATE SET
     Ok(())
 }
 
-async fn insert_mmr_history(tx: &mut Transaction<'_, Postgres>, account_id: i64, m: &MMRHistory) -> Result<()> {
+/// Rows inserted: 0 when the snapshot was already stored.
+async fn insert_mmr_history(tx: &mut Transaction<'_, Postgres>, account_id: i64, m: &MMRHistory) -> Result<u64> {
     let start_time = ts_from_epoch_secs(m.start_time as i64);
     let extra: Value = serde_json::json!({});
-    sqlx::query!(
+    let res = sqlx::query!(
         r#"
 INSERT INTO mmr_history (
   account_id, start_time, match_id, player_score, rank, division, division_tier, extra
@@ -322,11 +906,11 @@ ON CONFLICT (account_id, start_time) DO NOTHING;
     )
     .execute(&mut **tx)
     .await?;
-    Ok(())
+    Ok(res.rows_affected())
 }
 
 async fn upsert_hero_current(tx: &mut Transaction<'_, Postgres>, account_id: i64, h: &HeroStats) -> Result<()> {
-    let last_played = h.last_played.map(|s| ts_from_epoch_secs(s as i64));
+    let last_played = h.last_played.map(ts_from_epoch_secs);
     let extra: Value = serde_json::json!({});
     sqlx::query!(
         // language=PostgreSQL
@@ -393,7 +977,7 @@ ON CONFLICT (account_id, hero_id) DO UPDATE SET
 }
 
 async fn insert_hero_snapshot(tx: &mut Transaction<'_, Postgres>, account_id: i64, h: &HeroStats) -> Result<()> {
-    if let Some(last_played) = h.last_played.map(|s| ts_from_epoch_secs(s as i64)) {
+    if let Some(last_played) = h.last_played.map(ts_from_epoch_secs) {
         let snapshot = serde_json::to_value(h)?;
         sqlx::query!(
             // language=PostgreSQL
@@ -414,9 +998,248 @@ ON CONFLICT (account_id, hero_id, last_played) DO NOTHING;
     Ok(())
 }
 
+/// A player reconstructed from the DB for `--offline` lookups.
+#[derive(Debug, Clone)]
+pub struct StoredPlayer {
+    pub payload: CombinedPayload,
+    pub profile_updated_at: Option<DateTime<Utc>>,
+}
+
+/// Rebuild the lookup payload from `players`, `latest_mmr` and `hero_stats_current`.
+/// Returns `None` when the account has never been looked up (stubs without a profile count as missing).
+pub async fn load_player(pool: &PgPool, account_id: u32) -> Result<Option<StoredPlayer>> {
+    let account_id_i64 = account_id as i64;
+    let player = sqlx::query!(
+        r#"
+SELECT steamid64, personaname, profileurl, avatar, avatarmedium, avatarfull,
+       countrycode, realname, profile_updated_at
+FROM players
+WHERE account_id = $1 AND personaname IS NOT NULL
+        "#,
+        account_id_i64
+    )
+    .fetch_optional(pool)
+    .await?;
+    let Some(player) = player else { return Ok(None) };
+
+    let latest_mmr = sqlx::query!(
+        r#"
+SELECT match_id, start_time, player_score, rank, division, division_tier
+FROM latest_mmr
+WHERE account_id = $1
+        "#,
+        account_id_i64
+    )
+    .fetch_optional(pool)
+    .await?
+    .map(|m| MMRHistory {
+        account_id: account_id as i32,
+        match_id: m.match_id.unwrap_or_default(),
+        start_time: m.start_time.map(|t| t.timestamp() as i32).unwrap_or_default(),
+        player_score: m.player_score.unwrap_or_default(),
+        rank: m.rank.unwrap_or_default(),
+        division: m.division.unwrap_or_default(),
+        division_tier: m.division_tier.unwrap_or_default(),
+    });
+
+    let hero_stats = sqlx::query!(
+        r#"
+SELECT hero_id, matches_played, wins, last_played, time_played, ending_level,
+       kills, deaths, assists, kills_per_min, deaths_per_min, assists_per_min,
+       networth_per_min, last_hits_per_min, damage_per_min, damage_taken_per_min,
+       obj_damage_per_min, accuracy, crit_shot_rate
+FROM hero_stats_current
+WHERE account_id = $1
+ORDER BY matches_played DESC NULLS LAST, hero_id
+        "#,
+        account_id_i64
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|h| HeroStats {
+        account_id: account_id as i32,
+        hero_id: h.hero_id,
+        matches_played: h.matches_played.map(i64::from),
+        wins: h.wins.map(i64::from),
+        last_played: h.last_played.map(|t| t.timestamp()),
+        time_played: h.time_played.map(i64::from),
+        ending_level: h.ending_level,
+        kills: h.kills.map(i64::from),
+        deaths: h.deaths.map(i64::from),
+        assists: h.assists.map(i64::from),
+        kills_per_min: h.kills_per_min,
+        deaths_per_min: h.deaths_per_min,
+        assists_per_min: h.assists_per_min,
+        networth_per_min: h.networth_per_min,
+        last_hits_per_min: h.last_hits_per_min,
+        damage_per_min: h.damage_per_min,
+        damage_taken_per_min: h.damage_taken_per_min,
+        obj_damage_per_min: h.obj_damage_per_min,
+        accuracy: h.accuracy,
+        crit_shot_rate: h.crit_shot_rate,
+    })
+    .collect();
+
+    let profile = SteamProfile {
+        account_id: account_id as i32,
+        personaname: player.personaname.unwrap_or_default(),
+        profileurl: player.profileurl.unwrap_or_default(),
+        avatar: player.avatar.unwrap_or_default(),
+        avatarmedium: player.avatarmedium.unwrap_or_default(),
+        avatarfull: player.avatarfull.unwrap_or_default(),
+        countrycode: player.countrycode,
+        realname: player.realname,
+        last_updated: player.profile_updated_at.map(|t| t.timestamp().to_string()),
+    };
+
+    Ok(Some(StoredPlayer {
+        payload: CombinedPayload {
+            steamid64: player.steamid64,
+            account_id,
+            profile,
+            latest_mmr,
+            hero_stats,
+        },
+        profile_updated_at: player.profile_updated_at,
+    }))
+}
+
+/// Fill in profile fields of existing players (e.g. stubs from match ingestion) without
+/// counting as a lookup.
+pub async fn fill_player_profiles(pool: &PgPool, profiles: &[SteamProfile]) -> Result<usize> {
+    let mut n = 0;
+    for p in profiles {
+        let updated_at = p.last_updated.as_ref().and_then(|s| s.parse::<i64>().ok()).map(ts_from_epoch_secs);
+        n += sqlx::query!(
+            r#"
+UPDATE players SET
+  personaname = $2,
+  profileurl = $3,
+  avatar = $4,
+  avatarmedium = $5,
+  avatarfull = $6,
+  countrycode = $7,
+  realname = $8,
+  profile_updated_at = GREATEST(profile_updated_at, $9)
+WHERE account_id = $1
+            "#,
+            p.account_id as i64,
+            p.personaname,
+            p.profileurl,
+            p.avatar,
+            p.avatarmedium,
+            p.avatarfull,
+            p.countrycode,
+            p.realname,
+            updated_at
+        )
+        .execute(pool)
+        .await?
+        .rows_affected() as usize;
+    }
+    Ok(n)
+}
+
+/// Record an offline lookup; online lookups are stamped by `ingest_player`.
+pub async fn mark_lookup(pool: &PgPool, account_id: u32) -> Result<()> {
+    sqlx::query!(r#"UPDATE players SET last_lookup_at = now() WHERE account_id = $1"#, account_id as i64)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Most recently looked-up players, newest first.
+pub async fn recent_lookups(pool: &PgPool, limit: i64) -> Result<Vec<RecentLookup>> {
+    let rows = sqlx::query!(
+        r#"
+SELECT account_id, personaname, last_lookup_at AS "last_lookup_at!"
+FROM players
+WHERE last_lookup_at IS NOT NULL
+ORDER BY last_lookup_at DESC
+LIMIT $1
+        "#,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| RecentLookup {
+            account_id: r.account_id as u32,
+            personaname: r.personaname.unwrap_or_default(),
+            looked_up_at: r.last_lookup_at,
+        })
+        .collect())
+}
+
+/// Every stored player with a persona name, most recently looked up first; feeds `shell` completion.
+pub async fn known_players(pool: &PgPool) -> Result<Vec<(u32, String)>> {
+    let rows = sqlx::query!(
+        r#"
+SELECT account_id, personaname AS "personaname!"
+FROM players
+WHERE personaname IS NOT NULL AND personaname <> ''
+ORDER BY last_lookup_at DESC NULLS LAST, account_id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|r| (r.account_id as u32, r.personaname)).collect())
+}
+
+pub async fn upsert_heroes(pool: &PgPool, heroes: &[Hero]) -> Result<usize> {
+    let mut tx = pool.begin().await?;
+    for h in heroes {
+        sqlx::query!(
+            r#"
+INSERT INTO heroes (hero_id, name, class_name, role, icon_url, updated_at)
+VALUES ($1,$2,$3,$4,$5, now())
+ON CONFLICT (hero_id) DO UPDATE SET
+  name = EXCLUDED.name,
+  class_name = COALESCE(EXCLUDED.class_name, heroes.class_name),
+  role = COALESCE(EXCLUDED.role, heroes.role),
+  icon_url = COALESCE(EXCLUDED.icon_url, heroes.icon_url),
+  updated_at = now();
+            "#,
+            h.id,
+            h.name,
+            h.class_name,
+            h.role,
+            h.icon_url
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(heroes.len())
+}
+
+pub async fn load_heroes(pool: &PgPool) -> Result<Vec<Hero>> {
+    let rows = sqlx::query!(r#"SELECT hero_id, name, class_name, role, icon_url FROM heroes ORDER BY hero_id"#)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| Hero { id: r.hero_id, name: r.name, class_name: r.class_name, role: r.role, icon_url: r.icon_url })
+        .collect())
+}
+
+/// When the catalog was last refreshed. Heroes dropped from the assets API keep their old
+/// `updated_at`, so the newest row is the one that dates the latest fetch.
+pub async fn heroes_refreshed_at(pool: &PgPool) -> Result<Option<DateTime<Utc>>> {
+    let t = sqlx::query_scalar!(r#"SELECT MAX(updated_at) FROM heroes"#).fetch_one(pool).await?;
+    Ok(t)
+}
+
+/// Decode a stored `team` column; anything but `team<N>` is bad data and fails the load.
+fn parse_team(s: Option<&str>) -> Result<Option<Team>> {
+    s.map(|t| t.parse().map_err(|e: String| anyhow::anyhow!("invalid stored team: {}", e))).transpose()
+}
+
 pub fn ts_from_epoch_secs<T: Into<i64>>(secs: T) -> DateTime<Utc> {
     let s = secs.into();
-    let s = if s < 0 { 0 } else { s } as i64;
+    let s = if s < 0 { 0 } else { s };
     Utc
         .timestamp_opt(s, 0)
         .single()
@@ -1,7 +1,11 @@
-use crate::models::{HeroStats, MMRHistory, MatchMeta, PlayerMatchHistoryEntry, SteamProfile};
-use anyhow::Result;
+use crate::cache::{self, CacheEntry, CacheMode, ResponseCache};
+use crate::ratelimit::RateLimiter;
+use crate::recorder::{Exchange, Recorder};
+use crate::models::{self, CombinedPayload, HeroStats, MMRHistory, MatchMeta, PlayerMatchHistoryEntry, SteamProfile};
+use anyhow::{bail, Result};
 use reqwest::{header, Client, StatusCode, Url};
 use serde::de::DeserializeOwned;
+use std::future::Future;
 use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
@@ -13,42 +17,218 @@ pub enum DeadlockError {
     #[error("Rate limited: {0}")]
     RateLimited(String),
 
+    #[error("Not in cache (--cache-only): {0}")]
+    CacheMiss(String),
+
+    #[error("No recorded response (--replay): {0}")]
+    ReplayMiss(String),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
+/// The subset of the Deadlock API the CLI depends on.
+///
+/// `DeadlockClient` talks to the real HTTP API; `fixture::FixtureApi` serves
+/// canned data so lookups and match ingestion can run without a network.
+pub trait DeadlockApi {
+    fn get_steam_profiles(&self, account_ids: &[u32]) -> impl Future<Output = Result<Vec<SteamProfile>, DeadlockError>> + Send;
+
+    fn get_mmr(&self, account_ids: &[u32]) -> impl Future<Output = Result<Vec<MMRHistory>, DeadlockError>> + Send;
+
+    /// Every ranked MMR snapshot of one account, not just the latest.
+    fn get_mmr_history(&self, account_id: u32) -> impl Future<Output = Result<Vec<MMRHistory>, DeadlockError>> + Send;
+
+    fn get_player_hero_stats(&self, account_ids: &[u32]) -> impl Future<Output = Result<Vec<HeroStats>, DeadlockError>> + Send;
+
+    fn get_matches_metadata(
+        &self,
+        match_ids: &[i64],
+        include_info: bool,
+        include_players: bool,
+    ) -> impl Future<Output = Result<Vec<MatchMeta>, DeadlockError>> + Send;
+
+    fn get_player_match_history(
+        &self,
+        account_id: u32,
+        force_refetch: bool,
+        only_stored_history: bool,
+    ) -> impl Future<Output = Result<Vec<PlayerMatchHistoryEntry>, DeadlockError>> + Send;
+}
+
 #[derive(Clone)]
 pub struct DeadlockClient {
     base: Url,
     api_key: Option<String>,
     http: Client,
+    cache: Option<ResponseCache>,
+    limiter: RateLimiter,
+    recorder: Option<Recorder>,
 }
 
 impl DeadlockClient {
     pub fn new(base: impl AsRef<str>, api_key: Option<String>, http: Client) -> Self {
         let base = Url::parse(base.as_ref()).expect("Invalid DEADLOCK_API_BASE");
-        Self { base, api_key, http }
+        Self { base, api_key, http, cache: None, limiter: RateLimiter::default(), recorder: None }
+    }
+
+    /// Replace the default request budget; clones made afterwards share it.
+    pub fn with_rate_limit(mut self, requests_per_sec: f64, burst: u32) -> Self {
+        self.limiter = RateLimiter::new(requests_per_sec, burst);
+        self
+    }
+
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
-    pub async fn get_steam_profiles(&self, account_ids: &[u32]) -> Result<Vec<SteamProfile>, DeadlockError> {
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, mut url: Url, query: Vec<(&str, String)>) -> Result<T, DeadlockError> {
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&query);
+        }
+
+        let cached = match &self.cache {
+            Some(c) => c.load(&url).await,
+            None => None,
+        };
+        if let (Some(c), Some(entry)) = (&self.cache, &cached)
+            && (c.mode() == CacheMode::Only || c.is_fresh(&url, entry))
+        {
+            return parse_body(&entry.body);
+        }
+        if matches!(&self.cache, Some(c) if c.mode() == CacheMode::Only) {
+            return Err(DeadlockError::CacheMiss(url.to_string()));
+        }
+
+        let replaying = self.recorder.as_ref().is_some_and(Recorder::is_replay);
+        let mut last_err: Option<DeadlockError> = None;
+        let mut delay = Duration::from_millis(400);
+        for attempt in 0..4 {
+            let ex = match (&self.recorder, replaying) {
+                (Some(r), true) => r.load(&url).await.ok_or_else(|| DeadlockError::ReplayMiss(url.to_string()))?,
+                _ => match self.send(&url, cached.as_ref()).await {
+                    Ok(ex) => ex,
+                    Err(e) => {
+                        last_err = Some(DeadlockError::Other(e.into()));
+                        if attempt < 3 {
+                            tokio::time::sleep(delay).await;
+                            delay = delay.saturating_mul(2);
+                        }
+                        continue;
+                    }
+                },
+            };
+            let status = ex.status();
+            let headers = ex.header_map();
+
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                if replaying {
+                    return Err(DeadlockError::RateLimited(ex.body));
+                }
+                if let Some(wait_dur) = headers
+                    .get(header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_retry_after)
+                {
+                    self.limiter.pause_for(wait_dur);
+                } else if attempt < 3 {
+                    self.limiter.pause_for(delay);
+                    delay = delay.saturating_mul(2);
+                }
+                last_err = Some(DeadlockError::RateLimited(ex.body));
+                continue;
+            }
+
+            if status == StatusCode::NOT_MODIFIED
+                && let (Some(c), Some(entry)) = (&self.cache, cached.clone())
+            {
+                let entry = c.touch(entry).await;
+                return parse_body(&entry.body);
+            }
+
+            if !status.is_success() {
+                return Err(DeadlockError::Http { status, message: ex.body });
+            }
+
+            let out = parse_body(&ex.body)?;
+            if let Some(c) = &self.cache {
+                let header_str = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+                c.store(&CacheEntry {
+                    url: url.to_string(),
+                    stored_at: cache::now_secs(),
+                    etag: header_str(header::ETAG),
+                    last_modified: header_str(header::LAST_MODIFIED),
+                    body: ex.body,
+                })
+                .await;
+            }
+            return Ok(out);
+        }
+        Err(last_err.unwrap_or_else(|| DeadlockError::Other(anyhow::anyhow!("HTTP failed"))))
+    }
+
+    /// Send one live request, recording the exchange when `--record` is active.
+    async fn send(&self, url: &Url, cached: Option<&CacheEntry>) -> Result<Exchange, reqwest::Error> {
+        self.limiter.acquire().await;
+        let mut req = self.http.get(url.clone());
+        if let Some(key) = &self.api_key {
+            req = req.header("X-API-KEY", key);
+        }
+        if let Some(entry) = cached {
+            if let Some(etag) = &entry.etag {
+                req = req.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(lm) = &entry.last_modified {
+                req = req.header(header::IF_MODIFIED_SINCE, lm);
+            }
+        }
+        let rsp = req.send().await?;
+        let status = rsp.status();
+        let headers = rsp.headers().clone();
+        self.limiter.observe_headers(&headers);
+        let body = rsp.text().await?;
+
+        let ex = Recorder::exchange(url, status, &headers, body);
+        if let Some(r) = &self.recorder
+            && let Err(e) = r.save(&ex).await
+        {
+            eprintln!("Warning: failed to record {}: {}", ex.url, e);
+        }
+        Ok(ex)
+    }
+}
+
+impl DeadlockApi for DeadlockClient {
+    async fn get_steam_profiles(&self, account_ids: &[u32]) -> Result<Vec<SteamProfile>, DeadlockError> {
         let url = self.base.join("/v1/players/steam").unwrap();
         let ids = join_ids(account_ids);
         self.get_json(url, vec![("account_ids", ids)]).await
     }
 
-    pub async fn get_mmr(&self, account_ids: &[u32]) -> Result<Vec<MMRHistory>, DeadlockError> {
+    async fn get_mmr(&self, account_ids: &[u32]) -> Result<Vec<MMRHistory>, DeadlockError> {
         let url = self.base.join("/v1/players/mmr").unwrap();
         let ids = join_ids(account_ids);
         self.get_json(url, vec![("account_ids", ids)]).await
     }
 
-    pub async fn get_player_hero_stats(&self, account_ids: &[u32]) -> Result<Vec<HeroStats>, DeadlockError> {
+    async fn get_mmr_history(&self, account_id: u32) -> Result<Vec<MMRHistory>, DeadlockError> {
+        let url = self.base.join(&format!("/v1/players/{}/mmr-history", account_id)).unwrap();
+        self.get_json(url, Vec::new()).await
+    }
+
+    async fn get_player_hero_stats(&self, account_ids: &[u32]) -> Result<Vec<HeroStats>, DeadlockError> {
         let url = self.base.join("/v1/players/hero-stats").unwrap();
         let ids = join_ids(account_ids);
         self.get_json(url, vec![("account_ids", ids)]).await
     }
 
-    pub async fn get_matches_metadata(
+    async fn get_matches_metadata(
         &self,
         match_ids: &[i64],
         include_info: bool,
@@ -62,7 +242,7 @@ impl DeadlockClient {
         self.get_json(url, q).await
     }
 
-    pub async fn get_player_match_history(
+    async fn get_player_match_history(
         &self,
         account_id: u32,
         force_refetch: bool,
@@ -74,61 +254,10 @@ impl DeadlockClient {
         if only_stored_history { q.push(("only_stored_history", "true".into())); }
         self.get_json(url, q).await
     }
+}
 
-    async fn get_json<T: DeserializeOwned>(&self, url: Url, query: Vec<(&str, String)>) -> Result<T, DeadlockError> {
-        let mut last_err: Option<DeadlockError> = None;
-        let mut delay = Duration::from_millis(400);
-        for attempt in 0..4 {
-            let mut req = self.http.get(url.clone()).query(&query);
-            if let Some(key) = &self.api_key {
-                req = req.header("X-API-KEY", key);
-            }
-            let resp = req.send().await;
-            match resp {
-                Ok(rsp) => {
-                    let status = rsp.status();
-                    let headers = rsp.headers().clone();
-
-                    if status == StatusCode::TOO_MANY_REQUESTS {
-                        let msg = rsp.text().await.unwrap_or_default();
-
-                        if let Some(wait_dur) = headers
-                            .get(header::RETRY_AFTER)
-                            .and_then(|v| v.to_str().ok())
-                            .and_then(parse_retry_after)
-                        {
-                            tokio::time::sleep(wait_dur).await;
-                        } else if attempt < 3 {
-                            tokio::time::sleep(delay).await;
-                            delay = delay.saturating_mul(2);
-                        }
-                        last_err = Some(DeadlockError::RateLimited(msg));
-                        continue;
-                    }
-
-                    if !status.is_success() {
-                        let msg = rsp.text().await.unwrap_or_default();
-                        return Err(DeadlockError::Http { status, message: msg });
-                    }
-
-                    let out = rsp
-                        .json::<T>()
-                        .await
-                        .map_err(|e| DeadlockError::Other(e.into()))?;
-                    return Ok(out);
-                }
-                Err(e) => {
-                    last_err = Some(DeadlockError::Other(e.into()));
-                    if attempt < 3 {
-                        tokio::time::sleep(delay).await;
-                        delay = delay.saturating_mul(2);
-                        continue;
-                    }
-                }
-            }
-        }
-        Err(last_err.unwrap_or_else(|| DeadlockError::Other(anyhow::anyhow!("HTTP failed"))))
-    }
+fn parse_body<T: DeserializeOwned>(body: &str) -> Result<T, DeadlockError> {
+    serde_json::from_str(body).map_err(|e| DeadlockError::Other(e.into()))
 }
 
 fn join_ids(ids: &[u32]) -> String {
@@ -148,3 +277,90 @@ fn parse_retry_after(s: &str) -> Option<Duration> {
     }
     None
 }
+
+/// Fetch profile, latest MMR and hero stats for one player in parallel.
+///
+/// A missing profile is an error; MMR and hero stat failures are reported as
+/// warnings and yield empty values so the lookup can still render.
+pub async fn fetch_player(api: &impl DeadlockApi, steamid64: &str, account_id: u32) -> Result<CombinedPayload> {
+    let ids = [account_id];
+    let (steam_profiles_res, mmr_res, hero_stats_res) = tokio::join!(
+        api.get_steam_profiles(&ids),
+        api.get_mmr(&ids),
+        api.get_player_hero_stats(&ids)
+    );
+
+    // handle 404/empty profiles explicitly
+    let profile = match steam_profiles_res {
+        Ok(mut v) if !v.is_empty() => v.remove(0),
+        Ok(_) => bail!("Player not found (no Steam profile)."),
+        Err(DeadlockError::Http { status, .. }) if status == StatusCode::NOT_FOUND => {
+            bail!("Player not found.")
+        }
+        Err(other) => return Err(anyhow::Error::from(other)),
+    };
+
+    let latest_mmr = match mmr_res {
+        Ok(v) => models::latest_mmr_for(&v, account_id),
+        Err(e) => {
+            eprintln!("Warning: failed to fetch MMR: {}", e);
+            None
+        }
+    };
+
+    let hero_stats = match hero_stats_res {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Warning: failed to fetch hero stats: {}", e);
+            Vec::new()
+        }
+    };
+
+    Ok(CombinedPayload {
+        steamid64: steamid64.to_string(),
+        account_id,
+        profile,
+        latest_mmr,
+        hero_stats,
+    })
+}
+
+/// Multi-ID variant of [`fetch_player`]: one request per endpoint for all of `account_ids`.
+///
+/// Players without a Steam profile are left out of the result; MMR and hero stat failures
+/// are warnings, as for a single lookup.
+pub async fn fetch_players(api: &impl DeadlockApi, account_ids: &[u32]) -> Result<Vec<CombinedPayload>> {
+    let (steam_profiles_res, mmr_res, hero_stats_res) = tokio::join!(
+        api.get_steam_profiles(account_ids),
+        api.get_mmr(account_ids),
+        api.get_player_hero_stats(account_ids)
+    );
+
+    let profiles = match steam_profiles_res {
+        Ok(v) => v,
+        Err(DeadlockError::Http { status, .. }) if status == StatusCode::NOT_FOUND => Vec::new(),
+        Err(other) => return Err(anyhow::Error::from(other)),
+    };
+    let mmr = mmr_res.unwrap_or_else(|e| {
+        eprintln!("Warning: failed to fetch MMR: {}", e);
+        Vec::new()
+    });
+    let hero_stats = hero_stats_res.unwrap_or_else(|e| {
+        eprintln!("Warning: failed to fetch hero stats: {}", e);
+        Vec::new()
+    });
+
+    Ok(account_ids
+        .iter()
+        .filter_map(|&account_id| {
+            let profile = profiles.iter().find(|p| p.account_id as u32 == account_id)?.clone();
+            Some(CombinedPayload {
+                steamid64: crate::steam::account_id_to_steamid64(account_id),
+                account_id,
+                profile,
+                latest_mmr: models::latest_mmr_for(&mmr, account_id),
+                hero_stats: hero_stats.iter().filter(|h| h.account_id as u32 == account_id).cloned().collect(),
+            })
+        })
+        .collect())
+}
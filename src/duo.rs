@@ -0,0 +1,116 @@
+use crate::models::{KdaAverages, PairedHero, PlayerMatch, Team};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// A match both players appear in, with each one's row.
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedMatch {
+    pub match_id: i64,
+    pub start_time: Option<i64>,
+    pub same_team: bool,
+    pub a: PlayerMatch,
+    pub b: PlayerMatch,
+}
+
+impl SharedMatch {
+    /// Both teams are recorded, so the match counts as together or against.
+    pub fn teams_known(&self) -> bool {
+        self.a.team.is_some_and(Team::is_known) && self.b.team.is_some_and(Team::is_known)
+    }
+}
+
+/// How often a hero combination came up, and how often player A won with it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Pairing {
+    pub a: PairedHero,
+    pub b: PairedHero,
+    pub matches: usize,
+    pub wins: usize,
+}
+
+/// Shared matches on one side of the split. Wins are player A's: won together, or beat B.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Side {
+    pub matches: usize,
+    pub wins: usize,
+    /// Percent
+    pub win_rate: Option<f64>,
+    pub a: KdaAverages,
+    pub b: KdaAverages,
+    /// Most frequent first
+    pub pairings: Vec<Pairing>,
+}
+
+impl Side {
+    fn of(shared: &[&SharedMatch]) -> Self {
+        let decided: Vec<bool> = shared.iter().filter_map(|m| m.a.is_victory).collect();
+        let wins = decided.iter().filter(|w| **w).count();
+
+        let mut pairs: BTreeMap<(i32, i32), (usize, usize)> = BTreeMap::new();
+        for m in shared {
+            if let (Some(ha), Some(hb)) = (m.a.hero_id, m.b.hero_id) {
+                let e = pairs.entry((ha, hb)).or_default();
+                e.0 += 1;
+                e.1 += usize::from(m.a.is_victory == Some(true));
+            }
+        }
+        let mut pairings: Vec<Pairing> = pairs
+            .into_iter()
+            .map(|((a, b), (matches, wins))| Pairing { a: PairedHero { hero_id: a }, b: PairedHero { hero_id: b }, matches, wins })
+            .collect();
+        pairings.sort_by(|x, y| y.matches.cmp(&x.matches).then(y.wins.cmp(&x.wins)));
+
+        Self {
+            matches: shared.len(),
+            wins,
+            win_rate: (!decided.is_empty()).then(|| wins as f64 / decided.len() as f64 * 100.0),
+            a: KdaAverages::of(shared.iter().map(|m| &m.a)),
+            b: KdaAverages::of(shared.iter().map(|m| &m.b)),
+            pairings,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuoReport {
+    pub account_a: u32,
+    pub account_b: u32,
+    pub together: Side,
+    pub against: Side,
+    /// Shared matches where either team is unknown; left out of both sides
+    pub unknown_team: usize,
+    /// Newest first
+    pub shared: Vec<SharedMatch>,
+}
+
+/// Join two players' stored matches on `match_id` and split them by team.
+pub fn analyze(account_a: u32, a: &[PlayerMatch], account_b: u32, b: &[PlayerMatch]) -> DuoReport {
+    let by_id: HashMap<i64, &PlayerMatch> = b.iter().map(|m| (m.match_id, m)).collect();
+    let mut shared: Vec<SharedMatch> = a
+        .iter()
+        .filter_map(|ma| {
+            let mb = by_id.get(&ma.match_id)?;
+            Some(SharedMatch {
+                match_id: ma.match_id,
+                start_time: ma.start_time.or(mb.start_time),
+                same_team: ma.team.is_some_and(Team::is_known) && ma.team == mb.team,
+                a: ma.clone(),
+                b: (*mb).clone(),
+            })
+        })
+        .collect();
+    shared.sort_by_key(|m| std::cmp::Reverse((m.start_time, m.match_id)));
+
+    let together: Vec<&SharedMatch> = shared.iter().filter(|m| m.teams_known() && m.same_team).collect();
+    let against: Vec<&SharedMatch> = shared.iter().filter(|m| m.teams_known() && !m.same_team).collect();
+    let unknown_team = shared.len() - together.len() - against.len();
+
+    DuoReport {
+        account_a,
+        account_b,
+        together: Side::of(&together),
+        against: Side::of(&against),
+        unknown_team,
+        shared,
+    }
+}
@@ -0,0 +1,106 @@
+use crate::deadlock::{DeadlockApi, DeadlockError};
+use crate::models::{HeroStats, MMRHistory, MatchMeta, PlayerMatchHistoryEntry, SteamProfile};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// In-memory `DeadlockApi` backed by canned data.
+///
+/// Every call filters the stored rows by the requested IDs, mirroring what the
+/// real endpoints return. Can be built in code or loaded from a JSON file with
+/// the same field names.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FixtureApi {
+    pub profiles: Vec<SteamProfile>,
+    pub mmr: Vec<MMRHistory>,
+    pub hero_stats: Vec<HeroStats>,
+    pub matches: Vec<MatchMeta>,
+    pub match_history: Vec<PlayerMatchHistoryEntry>,
+}
+
+impl FixtureApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&raw)?)
+    }
+
+    pub fn with_profile(mut self, p: SteamProfile) -> Self {
+        self.profiles.push(p);
+        self
+    }
+
+    pub fn with_mmr(mut self, m: MMRHistory) -> Self {
+        self.mmr.push(m);
+        self
+    }
+
+    pub fn with_hero_stats(mut self, h: HeroStats) -> Self {
+        self.hero_stats.push(h);
+        self
+    }
+
+    pub fn with_match(mut self, m: MatchMeta) -> Self {
+        self.matches.push(m);
+        self
+    }
+
+    pub fn with_history_entry(mut self, e: PlayerMatchHistoryEntry) -> Self {
+        self.match_history.push(e);
+        self
+    }
+}
+
+fn has_account(account_ids: &[u32], account_id: i32) -> bool {
+    account_ids.contains(&(account_id as u32))
+}
+
+impl DeadlockApi for FixtureApi {
+    async fn get_steam_profiles(&self, account_ids: &[u32]) -> Result<Vec<SteamProfile>, DeadlockError> {
+        Ok(self.profiles.iter().filter(|p| has_account(account_ids, p.account_id)).cloned().collect())
+    }
+
+    async fn get_mmr(&self, account_ids: &[u32]) -> Result<Vec<MMRHistory>, DeadlockError> {
+        Ok(self.mmr.iter().filter(|m| has_account(account_ids, m.account_id)).cloned().collect())
+    }
+
+    async fn get_mmr_history(&self, account_id: u32) -> Result<Vec<MMRHistory>, DeadlockError> {
+        self.get_mmr(&[account_id]).await
+    }
+
+    async fn get_player_hero_stats(&self, account_ids: &[u32]) -> Result<Vec<HeroStats>, DeadlockError> {
+        Ok(self.hero_stats.iter().filter(|h| has_account(account_ids, h.account_id)).cloned().collect())
+    }
+
+    async fn get_matches_metadata(
+        &self,
+        match_ids: &[i64],
+        include_info: bool,
+        include_players: bool,
+    ) -> Result<Vec<MatchMeta>, DeadlockError> {
+        Ok(self
+            .matches
+            .iter()
+            .filter(|m| match_ids.contains(&m.match_id))
+            .cloned()
+            .map(|mut m| {
+                if !include_info { m.info = None; }
+                if !include_players { m.players = None; }
+                m
+            })
+            .collect())
+    }
+
+    async fn get_player_match_history(
+        &self,
+        account_id: u32,
+        _force_refetch: bool,
+        _only_stored_history: bool,
+    ) -> Result<Vec<PlayerMatchHistoryEntry>, DeadlockError> {
+        Ok(self.match_history.iter().filter(|e| e.account_id as u32 == account_id).cloned().collect())
+    }
+}
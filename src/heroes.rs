@@ -0,0 +1,133 @@
+use crate::models::Hero;
+use anyhow::Result;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Snapshot shipped with the binary; used when neither the DB nor the assets API has heroes.
+const BUNDLED_HEROES: &str = include_str!("../assets/heroes.json");
+
+/// Hero ID -> name/role lookup used by every table and JSON output.
+#[derive(Debug, Clone, Default)]
+pub struct HeroCatalog {
+    by_id: HashMap<i32, Hero>,
+}
+
+impl HeroCatalog {
+    pub fn from_heroes(heroes: impl IntoIterator<Item = Hero>) -> Self {
+        Self { by_id: heroes.into_iter().map(|h| (h.id, h)).collect() }
+    }
+
+    pub fn bundled() -> Self {
+        let heroes: Vec<Hero> = serde_json::from_str(BUNDLED_HEROES).expect("assets/heroes.json is valid");
+        Self::from_heroes(heroes)
+    }
+
+    pub fn get(&self, hero_id: i32) -> Option<&Hero> {
+        self.by_id.get(&hero_id)
+    }
+
+    /// Hero name, or `Hero #<id>` for heroes the catalog does not know yet.
+    pub fn name(&self, hero_id: i32) -> String {
+        self.get(hero_id).map(|h| h.name.clone()).unwrap_or_else(|| format!("Hero #{}", hero_id))
+    }
+
+    /// Look a hero up by ID or case-insensitive name (`"mo & krill"`, `"Mo and Krill"`, `"13"`).
+    pub fn find(&self, query: &str) -> Option<&Hero> {
+        if let Ok(id) = query.trim().parse::<i32>() {
+            return self.get(id);
+        }
+        let norm = |s: &str| s.to_ascii_lowercase().replace('&', "and").split_whitespace().collect::<Vec<_>>().join(" ");
+        let wanted = norm(query);
+        self.by_id.values().find(|h| norm(&h.name) == wanted)
+    }
+
+    pub fn role(&self, hero_id: i32) -> Option<&str> {
+        self.get(hero_id).and_then(|h| h.role.as_deref())
+    }
+
+    /// Add `hero_name` / `hero_role` next to every `hero_id` in a JSON document.
+    pub fn annotate(&self, v: &mut Value) {
+        match v {
+            Value::Object(map) => {
+                if let Some(id) = map.get("hero_id").and_then(Value::as_i64) {
+                    let id = id as i32;
+                    map.insert("hero_name".into(), Value::String(self.name(id)));
+                    map.insert("hero_role".into(), self.role(id).map(|r| Value::String(r.into())).unwrap_or(Value::Null));
+                }
+                for child in map.values_mut() {
+                    self.annotate(child);
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|i| self.annotate(i)),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AssetsHero {
+    id: i32,
+    name: String,
+    #[serde(default)]
+    class_name: Option<String>,
+    #[serde(default)]
+    hero_type: Option<String>,
+    #[serde(default)]
+    images: AssetsHeroImages,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AssetsHeroImages {
+    #[serde(default)]
+    icon_image_small: Option<String>,
+    #[serde(default)]
+    icon_hero_card: Option<String>,
+}
+
+/// Fetch the hero list from the Deadlock assets API (`DEADLOCK_ASSETS_BASE`).
+pub async fn fetch_assets(http: &Client) -> Result<Vec<Hero>> {
+    let base = std::env::var("DEADLOCK_ASSETS_BASE").unwrap_or_else(|_| "https://assets.deadlock-api.com".to_string());
+    let url = format!("{}/v2/heroes", base.trim_end_matches('/'));
+    let resp = http.get(url).send().await?.error_for_status()?;
+    let raw: Vec<AssetsHero> = resp.json().await?;
+    Ok(raw
+        .into_iter()
+        .map(|h| Hero {
+            id: h.id,
+            name: h.name,
+            class_name: h.class_name,
+            role: h.hero_type,
+            icon_url: h.images.icon_image_small.or(h.images.icon_hero_card),
+        })
+        .collect())
+}
+
+/// Load the catalog from the `heroes` table, refreshing it from the assets API when
+/// it is empty or older than a week. Falls back to the bundled snapshot.
+#[cfg(feature = "db")]
+pub async fn load_catalog(pool: &sqlx::PgPool, http: &Client, offline: bool) -> HeroCatalog {
+    let stored = crate::db::load_heroes(pool).await.unwrap_or_default();
+    let refreshed_at = crate::db::heroes_refreshed_at(pool).await.ok().flatten();
+    let stale = refreshed_at.is_none_or(|t| chrono::Utc::now() - t > chrono::Duration::days(7));
+
+    if !offline && stale {
+        match fetch_assets(http).await {
+            Ok(heroes) if !heroes.is_empty() => {
+                if let Err(e) = crate::db::upsert_heroes(pool, &heroes).await {
+                    eprintln!("Warning: failed to store hero catalog: {}", e);
+                }
+                return HeroCatalog::from_heroes(heroes);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Warning: failed to fetch hero catalog: {}", e),
+        }
+    }
+
+    if stored.is_empty() {
+        HeroCatalog::bundled()
+    } else {
+        HeroCatalog::from_heroes(stored)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::models::{MatchMode, MatchQuery, SortKey};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Filters for `matches list`; every `None` field matches everything.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub hero_id: Option<i32>,
+    /// Unix seconds, inclusive
+    pub since: Option<i64>,
+    /// Unix seconds, exclusive
+    pub until: Option<i64>,
+    pub mode: Option<MatchMode>,
+    /// `Some(true)` keeps wins only, `Some(false)` losses only
+    pub victory: Option<bool>,
+}
+
+impl HistoryFilter {
+    /// Date bounds are whole UTC days, both inclusive.
+    pub fn with_dates(mut self, since: Option<NaiveDate>, until: Option<NaiveDate>) -> Self {
+        self.since = since.map(day_start);
+        self.until = until.map(|d| day_start(d) + 86_400);
+        self
+    }
+
+    /// The database query for `account_id`'s matches that pass this filter.
+    pub fn query(self, account_id: u32, sort: SortKey, ascending: bool) -> MatchQuery {
+        MatchQuery { account_id, hero_id: self.hero_id, since: self.since, until: self.until, mode: self.mode, victory: self.victory, sort, ascending }
+    }
+}
+
+fn day_start(d: NaiveDate) -> i64 {
+    d.and_hms_opt(0, 0, 0).map(|t| t.and_utc().timestamp()).unwrap_or_default()
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    /// 1-based
+    pub page: usize,
+    pub per_page: usize,
+    pub pages: usize,
+    pub total: usize,
+    pub items: Vec<T>,
+}
+
+impl<T> Page<T> {
+    /// Page `page` of `total` rows, clamped to the pages there are, before its items are loaded.
+    pub fn empty(total: usize, page: usize, per_page: usize) -> Self {
+        let per_page = per_page.max(1);
+        let pages = total.div_ceil(per_page).max(1);
+        Page { page: page.clamp(1, pages), per_page, pages, total, items: Vec::new() }
+    }
+
+    /// Rows before this page's first one.
+    pub fn offset(&self) -> usize {
+        (self.page - 1) * self.per_page
+    }
+}
+
+pub fn paginate<T>(rows: Vec<T>, page: usize, per_page: usize) -> Page<T> {
+    let mut p = Page::empty(rows.len(), page, per_page);
+    p.items = rows.into_iter().skip(p.offset()).take(p.per_page).collect();
+    p
+}
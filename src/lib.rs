@@ -1,7 +1,26 @@
+pub mod batch;
+pub mod cache;
 pub mod cli;
+pub mod compare;
 #[cfg(feature = "db")]
 pub mod db;
 pub mod deadlock;
+pub mod duo;
+pub mod fixture;
+pub mod heroes;
+pub mod history;
+pub mod mates;
+pub mod meta;
+pub mod mmr;
 pub mod models;
+pub mod party;
+pub mod patches;
+pub mod ratelimit;
+pub mod recorder;
+pub mod repl;
+pub mod score;
+pub mod sessions;
 pub mod steam;
+pub mod synergy;
+pub mod tui;
 pub mod ui;
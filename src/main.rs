@@ -1,16 +1,36 @@
+mod batch;
+mod cache;
 mod cli;
+mod compare;
 #[cfg(feature = "db")]
 mod db;
 mod deadlock;
+mod duo;
+mod heroes;
+mod history;
+mod mates;
+mod meta;
+mod mmr;
 mod models;
+mod party;
+mod patches;
+mod ratelimit;
+mod recorder;
+mod repl;
+mod score;
+mod sessions;
 mod steam;
+mod synergy;
+mod tui;
 mod ui;
 
-use anyhow::{bail, Context, Result};
+use anyhow::{Context, Result};
 use clap::Parser;
-use deadlock::DeadlockClient;
-use sqlx::Row;
-use std::io::{self, Write};
+use cli::{Command, MatchesSubcommand};
+use deadlock::{DeadlockApi, DeadlockClient};
+#[cfg(feature = "db")]
+use sqlx::{PgPool, Row};
+use std::io::{self, IsTerminal, Write};
 use tokio::runtime::Runtime;
 
 fn main() {
@@ -21,441 +41,887 @@ fn main() {
     let res = rt.block_on(async_main());
     if let Err(err) = res {
         // map rate limiting 
-        if let Some(code) = err.downcast_ref::<deadlock::DeadlockError>() {
-            if matches!(code, deadlock::DeadlockError::RateLimited { .. }) {
-                eprintln!("Rate limit hit. Please try again later.");
-                std::process::exit(29);
-            }
+        if let Some(deadlock::DeadlockError::RateLimited { .. }) = err.downcast_ref::<deadlock::DeadlockError>() {
+            eprintln!("Rate limit hit. Please try again later.");
+            std::process::exit(29);
         }
         eprintln!("Error: {:#}", err);
         std::process::exit(1);
     }
 }
 
-async fn async_main() -> Result<()> {
-    use cli::{Args, Command};
-
-    let args = Args::parse();
+/// Clients and global flags every command handler works with.
+struct Ctx {
+    http: reqwest::Client,
+    dl: DeadlockClient,
+    recorder: Option<recorder::Recorder>,
+    offline: bool,
+    json: bool,
+    style: ui::ChartStyle,
+    /// Skip optional network fetches (e.g. the hero catalog) when the user asked for none
+    #[cfg(feature = "db")]
+    no_network: bool,
+}
 
-    //build http clients
-    let http = reqwest::Client::builder()
-        .user_agent("deadlock-cli/0.1")
-        .timeout(std::time::Duration::from_secs(15))
-        .build()?;
+impl Ctx {
+    fn new(args: &cli::Args) -> Result<Self> {
+        //build http clients
+        let http = reqwest::Client::builder()
+            .user_agent("deadlock-cli/0.1")
+            .timeout(std::time::Duration::from_secs(15))
+            .build()?;
+
+        let base = std::env::var("DEADLOCK_API_BASE").unwrap_or_else(|_| "https://api.deadlock-api.com".to_string());
+        let api_key = std::env::var("DEADLOCK_API_KEY").ok();
+        let recorder = match (&args.record, &args.replay) {
+            (Some(dir), _) => Some(recorder::Recorder::record(dir)),
+            (_, Some(dir)) => Some(recorder::Recorder::replay(dir)),
+            _ => None,
+        };
 
-    let base = std::env::var("DEADLOCK_API_BASE").unwrap_or_else(|_| "https://api.deadlock-api.com".to_string());
-    let api_key = std::env::var("DEADLOCK_API_KEY").ok();
-    let dl = DeadlockClient::new(base, api_key, http.clone());
+        // recording and replaying must see every request, so they bypass the cache
+        let cache_mode = if args.no_cache || recorder.is_some() {
+            cache::CacheMode::Bypass
+        } else if args.cache_only {
+            cache::CacheMode::Only
+        } else {
+            cache::CacheMode::Normal
+        };
+        let mut dl = DeadlockClient::new(base, api_key, http.clone())
+            .with_rate_limit(args.rps, args.rps.ceil().max(1.0) as u32)
+            .with_cache(cache::ResponseCache::new(cache::ResponseCache::default_dir(), cache_mode));
+        if let Some(r) = &recorder {
+            dl = dl.with_recorder(r.clone());
+        }
 
-    let mut want_json = args.json;
+        Ok(Ctx {
+            http,
+            dl,
+            recorder,
+            offline: args.offline,
+            json: args.json,
+            style: ui::ChartStyle::detect(args.no_color),
+            #[cfg(feature = "db")]
+            no_network: args.offline || args.cache_only || args.replay.is_some(),
+        })
+    }
 
-    if let Some(Command::Migrate) = args.command {
-        #[cfg(feature = "db")]
-        {
-            let db::DbPool(pool) = db::connect().await?;
-            db::migrate(&pool).await?;
-            println!("Migrations completed.");
-            return Ok(());
+    /// Account ID from whichever of `--account-id`, `--steamid` or `--id3` was given.
+    async fn resolve(&self, account: &cli::AccountArgs) -> Result<u32> {
+        if let Some(a) = account.account_id {
+            Ok(a)
+        } else if let Some(s) = &account.steamid {
+            let sid64 = steam::to_steamid64_recorded(s, &self.http, self.recorder.as_ref()).await?;
+            Ok(steam::steamid64_to_account_id(&sid64)?)
+        } else if let Some(s) = &account.id3 {
+            Ok(steam::parse_steamid3_or_account_id(s)?)
+        } else {
+            anyhow::bail!("Provide one of --account-id, --steamid, or --id3");
         }
-        #[cfg(not(feature = "db"))]
-        {
-            anyhow::bail!("DB feature not enabled. Rebuild with `--features db`.");
+    }
+
+    fn env<'a>(&'a self, #[cfg(feature = "db")] pool: &'a PgPool) -> tui::Env<'a, DeadlockClient> {
+        tui::Env {
+            api: &self.dl,
+            http: &self.http,
+            recorder: self.recorder.as_ref(),
+            offline: self.offline,
+            #[cfg(feature = "db")]
+            pool: Some(pool),
         }
     }
 
-    // Matches sync
-    if let Some(Command::Matches { cmd }) = args.command.clone() {
-        match cmd {
-            cli::MatchesSubcommand::Sync { ids, from_account_id, from_steamid, from_id3, since_id, until_id, limit, batch_size, include_info, include_players, dry_run } => {
-                #[cfg(feature = "db")]
-                {
-                    let db::DbPool(pool) = db::connect().await?;
-                    db::migrate(&pool).await?;
-
-                    //candidate match ids
-                    let mut candidate_ids: Vec<i64> = Vec::new();
-
-                    if !ids.is_empty() {
-                        candidate_ids.extend(ids);
-                    }
-
-                    //derive from player sources -> use mmr history match_ids
-                    let account_id_opt: Option<u32> = if let Some(acc) = from_account_id {
-                        Some(acc)
-                    } else if let Some(sid) = from_steamid {
-                        let http2 = http.clone();
-                        let sid64 = steam::to_steamid64_with_client(&sid, &http2).await?;
-                        Some(steam::steamid64_to_account_id(&sid64)?)
-                    } else if let Some(id3) = from_id3 {
-                        Some(steam::parse_steamid3_or_account_id(&id3)?)
-                    } else {
-                        None
-                    };
-
-                    if let Some(account_id) = account_id_opt {
-                        let ids_slice = &[account_id];
-                        match dl.get_mmr(ids_slice).await {
-                            Ok(mmr) => {
-                                for m in mmr {
-                                    candidate_ids.push(m.match_id);
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("Warning: failed to fetch MMR for account {}: {}", account_id, e);
-                            }
-                        }
-                    }
-
-                    //fallback sequential window if still empty
-                    if candidate_ids.is_empty() {
-                        let start_from: i64 = if let Some(s) = since_id {
-                            s
-                        } else {
-                            let row = sqlx::query(r#"SELECT COALESCE(MAX(match_id), 0) AS max FROM matches"#)
-                                .fetch_one(&pool)
-                                .await
-                                .ok();
-                            row.as_ref().map(|r| r.get::<i64, _>("max")).unwrap_or(0)
-                        };
-
-                        let until_cap = until_id.unwrap_or(i64::MAX);
-                        let mut next = start_from + 1;
-                        while candidate_ids.len() < limit && next <= until_cap {
-                            candidate_ids.push(next);
-                            next += 1;
-                        }
-                        if candidate_ids.is_empty() {
-                            println!("No IDs to fetch (start_from={}, until_id={:?})", start_from, until_id);
-                            return Ok(());
-                        }
-                    }
-
-                    // normalize and limit
-                    candidate_ids.sort_unstable();
-                    candidate_ids.dedup();
-                    if candidate_ids.len() > limit { candidate_ids.truncate(limit); }
-
-                    // dedupe matches
-                    let rows = sqlx::query(r#"SELECT match_id FROM matches WHERE match_id = ANY($1)"#)
-                        .bind(&candidate_ids)
-                        .fetch_all(&pool)
-                        .await
-                        .unwrap_or_default();
-                    let existing: std::collections::HashSet<i64> = rows.into_iter().map(|r| r.get::<i64, _>("match_id")).collect();
-                    candidate_ids.retain(|id| !existing.contains(id));
-                    if candidate_ids.is_empty() {
-                        println!("All candidate IDs already present or none discovered. Nothing to do.");
-                        return Ok(());
-                    }
-
-                    // chunk and ingest
-                    let mut total_matches = 0usize;
-                    let mut total_players = 0usize;
-                    for chunk in candidate_ids.chunks(batch_size.max(1)) {
-                        let metas = match dl.get_matches_metadata(chunk, include_info, include_players).await {
-                            Ok(m) => m,
-                            Err(deadlock::DeadlockError::Http { status, .. }) if status == reqwest::StatusCode::NOT_FOUND => {
-                                eprintln!("chunk {:?} -> no matches found (404)", &chunk[..chunk.len().min(3)]);
-                                Vec::new()
-                            }
-                            Err(e) => return Err(anyhow::Error::from(e)),
-                        };
-                        if dry_run {
-                            println!("dry-run: fetched {} matches in chunk ({} IDs)", metas.len(), chunk.len());
-                        } else {
-                            let res = db::ingest_matches_batch(&pool, &metas).await?;
-                            total_matches += res.matches_upserted;
-                            total_players += res.match_players_upserted;
-                            eprintln!(
-                                "batch: matches_upserted={}, match_players_upserted={}, players_referenced={}",
-                                res.matches_upserted, res.match_players_upserted, res.players_upserted
-                            );
-                        }
-                    }
-                    if !dry_run {
-                        println!("Done. Total matches upserted={}, match_players upserted={}.", total_matches, total_players);
-                    }
-                    return Ok(());
-                }
-                #[cfg(not(feature = "db"))]
-                {
-                    anyhow::bail!("DB feature not enabled. Rebuild with `--features db`.");
-                }
-            }
-            cli::MatchesSubcommand::History { account_id, steamid, id3, force_refetch, only_stored_history, dry_run } => {
-                if force_refetch && only_stored_history {
-                    anyhow::bail!("--force-refetch and --only-stored-history cannot be used together");
-                }
+    #[cfg(feature = "db")]
+    async fn catalog(&self, pool: &PgPool) -> heroes::HeroCatalog {
+        heroes::load_catalog(pool, &self.http, self.no_network).await
+    }
 
-                #[cfg(feature = "db")]
-                {
-                    let db::DbPool(pool) = db::connect().await?;
-                    db::migrate(&pool).await?;
-
-                    // resolve account_id
-                    let acc: u32 = if let Some(a) = account_id {
-                        a
-                    } else if let Some(s) = steamid {
-                        let sid64 = steam::to_steamid64_with_client(&s, &http).await?;
-                        steam::steamid64_to_account_id(&sid64)?
-                    } else if let Some(s) = id3 {
-                        steam::parse_steamid3_or_account_id(&s)?
-                    } else {
-                        anyhow::bail!("Provide one of --account-id, --steamid, or --id3");
-                    };
-
-                    // fetch history
-                    let entries = dl.get_player_match_history(acc, force_refetch, only_stored_history).await?;
-                    if entries.is_empty() {
-                        println!("No history entries returned for account {}", acc);
-                        return Ok(());
-                    }
-
-                    // group by match_id into MatchMeta with PlayerInMatch list
-                    use std::collections::BTreeMap;
-                    let mut grouped: BTreeMap<i64, (Option<i64>, Option<i32>, Vec<crate::models::PlayerInMatch>)> = BTreeMap::new();
-                    for e in entries {
-                        let start = Some(e.start_time as i64);
-                        let dur = Some(e.match_duration_s);
-                        let ent = grouped.entry(e.match_id).or_insert((start, dur, Vec::new()));
-                        if ent.0.is_none() { ent.0 = start; }
-                        if ent.1.is_none() { ent.1 = dur; }
-                        let extra = serde_json::json!({
-                            "denies": e.denies,
-                            "game_mode": e.game_mode,
-                            "match_mode": e.match_mode,
-                            "match_result": e.match_result,
-                            "objectives_mask_team0": e.objectives_mask_team0,
-                            "objectives_mask_team1": e.objectives_mask_team1,
-                            "hero_level": e.hero_level
-                        });
-                        let pim = crate::models::PlayerInMatch {
-                            account_id: e.account_id,
-                            hero_id: Some(e.hero_id),
-                            team: Some(format!("team{}", e.player_team)),
-                            party_id: None,
-                            lane: None,
-                            is_victory: None,
-                            kills: Some(e.player_kills),
-                            deaths: Some(e.player_deaths),
-                            assists: Some(e.player_assists),
-                            networth: Some(e.net_worth as i64),
-                            damage: None,
-                            damage_taken: None,
-                            obj_damage: None,
-                            last_hits: Some(e.last_hits),
-                            accuracy: None,
-                            crit_shot_rate: None,
-                            extra: Some(extra),
-                        };
-                        ent.2.push(pim);
-                    }
-
-                    let metas: Vec<crate::models::MatchMeta> = grouped.into_iter().map(|(mid, (st, du, players))| {
-                        crate::models::MatchMeta {
-                            match_id: mid,
-                            start_time: st,
-                            duration_s: du,
-                            winner_team: None,
-                            average_badge: None,
-                            region: None,
-                            patch_version: None,
-                            info: None,
-                            players: Some(players),
-                        }
-                    }).collect();
-
-                    if dry_run {
-                        println!("dry-run: would persist {} matches ({} participants)", metas.len(), metas.iter().map(|m| m.players.as_ref().map(|v| v.len()).unwrap_or(0)).sum::<usize>());
-                        return Ok(());
-                    }
-
-                    let res = db::ingest_matches_batch(&pool, &metas).await?;
-                    println!("History persisted. matches_upserted={}, match_players_upserted={}", res.matches_upserted, res.match_players_upserted);
-                    return Ok(());
-                }
-                #[cfg(not(feature = "db"))]
-                {
-                    anyhow::bail!("DB feature not enabled. Rebuild with `--features db`.");
-                }
+    /// Store the account's API match history so the DB reports include its latest games.
+    #[cfg(feature = "db")]
+    async fn sync_history(&self, pool: &PgPool, account_id: u32) -> Result<()> {
+        match self.dl.get_player_match_history(account_id, false, true).await {
+            Ok(entries) if !entries.is_empty() => {
+                db::ingest_matches_batch(pool, &models::history_to_metas(entries)).await?;
             }
+            Ok(_) => {}
+            Err(e) => eprintln!("Warning: failed to fetch match history for {}: {}", account_id, e),
         }
+        Ok(())
     }
+}
 
-    let steamid64 = match args.command {
-        Some(Command::BySteamId { id }) => id,
+/// Connect to the database and bring its schema up to date.
+#[cfg(feature = "db")]
+async fn open_db() -> Result<PgPool> {
+    let db::DbPool(pool) = db::connect().await?;
+    db::migrate(&pool).await?;
+    Ok(pool)
+}
+
+async fn async_main() -> Result<()> {
+    let args = cli::Args::parse();
+    let ctx = Ctx::new(&args)?;
+
+    match args.command {
+        #[cfg(feature = "db")]
+        Some(Command::Migrate) => run_migrate().await,
+        #[cfg(feature = "db")]
+        Some(Command::Matches { cmd: MatchesSubcommand::Sync(a) }) => run_matches_sync(a, &ctx).await,
+        #[cfg(feature = "db")]
+        Some(Command::Matches { cmd: MatchesSubcommand::History(a) }) => run_matches_history(a, &ctx).await,
+        #[cfg(feature = "db")]
+        Some(Command::Matches { cmd: MatchesSubcommand::List(a) }) => run_matches_list(a, &ctx).await,
+        Some(Command::Matches { cmd: MatchesSubcommand::Show { match_id } }) => run_matches_show(match_id, &ctx).await,
+        Some(Command::Matches { cmd: MatchesSubcommand::Objectives { match_id } }) => run_matches_objectives(match_id, &ctx).await,
+        Some(Command::Mmr(a)) => run_mmr(a, &ctx).await,
+        Some(Command::Batch(a)) => run_batch(a, &ctx).await,
+        Some(Command::Compare { players }) => run_compare(players, &ctx).await,
+        #[cfg(feature = "db")]
+        Some(Command::Vs(a)) => run_vs(a, &ctx).await,
+        #[cfg(feature = "db")]
+        Some(Command::Mates(a)) => run_mates(a, &ctx).await,
+        #[cfg(feature = "db")]
+        Some(Command::Party(a)) => run_party(a, &ctx).await,
+        #[cfg(feature = "db")]
+        Some(Command::Sessions(a)) => run_sessions(a, &ctx).await,
+        #[cfg(feature = "db")]
+        Some(Command::Patches(a)) => run_patches(a, &ctx).await,
+        #[cfg(feature = "db")]
+        Some(Command::Meta(a)) => run_meta(a, &ctx).await,
+        #[cfg(feature = "db")]
+        Some(Command::Heroes { cmd: cli::HeroesSubcommand::Synergy(a) }) => run_heroes_synergy(a, &ctx).await,
+        #[cfg(not(feature = "db"))]
+        Some(
+            Command::Migrate
+            | Command::Matches { cmd: MatchesSubcommand::Sync(_) | MatchesSubcommand::History(_) | MatchesSubcommand::List(_) }
+            | Command::Vs(_)
+            | Command::Mates(_)
+            | Command::Party(_)
+            | Command::Sessions(_)
+            | Command::Patches(_)
+            | Command::Meta(_)
+            | Command::Heroes { .. },
+        ) => anyhow::bail!("DB feature not enabled. Rebuild with `--features db`."),
+        Some(Command::Shell) => run_shell(&ctx).await,
+        Some(Command::BySteamId { id }) => run_lookup(&id, &ctx).await,
         Some(Command::BySteamId3 { id3 }) => {
             let acc = steam::parse_steamid3_or_account_id(&id3)?;
-            steam::account_id_to_steamid64(acc)
+            run_lookup(&steam::account_id_to_steamid64(acc), &ctx).await
         }
         Some(Command::ByVanity { name }) => {
-            steam::to_steamid64_with_client(&name, &http).await?
+            run_lookup(&steam::to_steamid64_recorded(&name, &ctx.http, ctx.recorder.as_ref()).await?, &ctx).await
         }
         Some(Command::ByUrl { url }) => {
-            steam::to_steamid64_with_client(&url, &http).await?
+            run_lookup(&steam::to_steamid64_recorded(&url, &ctx.http, ctx.recorder.as_ref()).await?, &ctx).await
         }
-        Some(Command::Migrate) => unreachable!("handled above"),
-        Some(Command::Matches { .. }) => unreachable!("handled above"),
-        None => {
-
-            loop {
-                println!("Deadlock CLI");
-                println!("1) Lookup by SteamID64");
-                println!("2) Lookup by Steam Community ID (vanity name)");
-                println!("3) Lookup by Steam Community URL");
-                println!("4) Lookup by SteamID3 / Account ID");
-                println!("j) Toggle JSON output [{}]", if want_json { "on" } else { "off" });
-                println!("q) Quit");
-                print!("> ");
-                io::stdout().flush().ok();
-                let mut line = String::new();
-                io::stdin().read_line(&mut line).ok();
-                let choice = line.trim();
-                match choice {
-                    "1" => {
-                        let id = prompt("Enter SteamID64: ")?;
-                        break id;
-                    }
-                    "2" => {
-                        let name = prompt("Enter Steam Community ID (vanity name): ")?;
-                        let sid = steam::to_steamid64_with_client(&name, &http).await?;
-                        break sid;
-                    }
-                    "3" => {
-                        let url = prompt("Enter full Steam Community URL: ")?;
-                        let sid = steam::to_steamid64_with_client(&url, &http).await?;
-                        break sid;
-                    }
-                    "4" => {
-                        let id3 = prompt("Enter SteamID3 (e.g. [U:1:123456]) or Account ID: ")?;
-                        let acc = steam::parse_steamid3_or_account_id(&id3)?;
-                        let sid = steam::account_id_to_steamid64(acc);
-                        break sid;
-                    }
-                    "j" | "J" => {
-                        want_json = !want_json;
-                        continue;
-                    }
-                    "q" | "Q" => return Ok(()),
-                    _ => {
-                        println!("Invalid choice. Please try again.\n");
-                        continue;
-                    }
+        None => run_dashboard(&ctx).await,
+    }
+}
+
+#[cfg(feature = "db")]
+async fn run_migrate() -> Result<()> {
+    open_db().await?;
+    println!("Migrations completed.");
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_matches_sync(args: cli::SyncArgs, ctx: &Ctx) -> Result<()> {
+    let cli::SyncArgs { ids, from_account_id, from_steamid, from_id3, since_id, until_id, limit, batch_size, include_info, include_players, dry_run } = args;
+    let pool = open_db().await?;
+
+    //candidate match ids
+    let mut candidate_ids: Vec<i64> = Vec::new();
+
+    if !ids.is_empty() {
+        candidate_ids.extend(ids);
+    }
+
+    //derive from player sources -> use mmr history match_ids
+    let account_id_opt: Option<u32> = if let Some(acc) = from_account_id {
+        Some(acc)
+    } else if let Some(sid) = from_steamid {
+        let sid64 = steam::to_steamid64_recorded(&sid, &ctx.http, ctx.recorder.as_ref()).await?;
+        Some(steam::steamid64_to_account_id(&sid64)?)
+    } else if let Some(id3) = from_id3 {
+        Some(steam::parse_steamid3_or_account_id(&id3)?)
+    } else {
+        None
+    };
+
+    if let Some(account_id) = account_id_opt {
+        let ids_slice = &[account_id];
+        match ctx.dl.get_mmr(ids_slice).await {
+            Ok(mmr) => {
+                for m in mmr {
+                    candidate_ids.push(m.match_id);
                 }
             }
+            Err(e) => {
+                eprintln!("Warning: failed to fetch MMR for account {}: {}", account_id, e);
+            }
         }
-    };
+    }
 
-    let steamid64 = steamid64.trim();
-    steam::validate_steamid64(steamid64)?;
-    let account_id = steam::steamid64_to_account_id(steamid64)?;
+    //fallback sequential window if still empty
+    if candidate_ids.is_empty() {
+        let start_from: i64 = if let Some(s) = since_id {
+            s
+        } else {
+            let row = sqlx::query(r#"SELECT COALESCE(MAX(match_id), 0) AS max FROM matches"#)
+                .fetch_one(&pool)
+                .await
+                .ok();
+            row.as_ref().map(|r| r.get::<i64, _>("max")).unwrap_or(0)
+        };
 
-    let ids_vec = vec![account_id];
-    let ids = ids_vec.as_slice();
-    let (steam_profiles_res, mmr_res, hero_stats_res) = tokio::join!(
-        dl.get_steam_profiles(ids),
-        dl.get_mmr(ids),
-        dl.get_player_hero_stats(ids)
-    );
+        let until_cap = until_id.unwrap_or(i64::MAX);
+        let mut next = start_from + 1;
+        while candidate_ids.len() < limit && next <= until_cap {
+            candidate_ids.push(next);
+            next += 1;
+        }
+        if candidate_ids.is_empty() {
+            println!("No IDs to fetch (start_from={}, until_id={:?})", start_from, until_id);
+            return Ok(());
+        }
+    }
+
+    // normalize and limit
+    candidate_ids.sort_unstable();
+    candidate_ids.dedup();
+    if candidate_ids.len() > limit { candidate_ids.truncate(limit); }
+
+    // dedupe matches
+    let rows = sqlx::query(r#"SELECT match_id FROM matches WHERE match_id = ANY($1)"#)
+        .bind(&candidate_ids)
+        .fetch_all(&pool)
+        .await
+        .unwrap_or_default();
+    let existing: std::collections::HashSet<i64> = rows.into_iter().map(|r| r.get::<i64, _>("match_id")).collect();
+    candidate_ids.retain(|id| !existing.contains(id));
+    if candidate_ids.is_empty() {
+        println!("All candidate IDs already present or none discovered. Nothing to do.");
+        return Ok(());
+    }
 
-    // handle 404/empty profiles explicitly
-    let steam_profile = match steam_profiles_res {
-        Ok(mut v) if !v.is_empty() => v.remove(0),
-        Ok(_) => bail!("Player not found (no Steam profile)."),
-        Err(e) => match e {
-            deadlock::DeadlockError::Http { status, .. } if status == reqwest::StatusCode::NOT_FOUND => {
-                bail!("Player not found.")
+    // chunk and ingest
+    let mut total_matches = 0usize;
+    let mut total_players = 0usize;
+    for chunk in candidate_ids.chunks(batch_size.max(1)) {
+        let metas = match ctx.dl.get_matches_metadata(chunk, include_info, include_players).await {
+            Ok(m) => m,
+            Err(deadlock::DeadlockError::Http { status, .. }) if status == reqwest::StatusCode::NOT_FOUND => {
+                eprintln!("chunk {:?} -> no matches found (404)", &chunk[..chunk.len().min(3)]);
+                Vec::new()
             }
-            other => return Err(anyhow::Error::from(other)),
-        },
+            Err(e) => return Err(anyhow::Error::from(e)),
+        };
+        if dry_run {
+            println!("dry-run: fetched {} matches in chunk ({} IDs)", metas.len(), chunk.len());
+        } else {
+            let res = db::ingest_matches_batch(&pool, &metas).await?;
+            total_matches += res.matches_upserted;
+            total_players += res.match_players_upserted;
+            eprintln!(
+                "batch: matches_upserted={}, match_players_upserted={}, players_referenced={}",
+                res.matches_upserted, res.match_players_upserted, res.players_upserted
+            );
+        }
+    }
+    if !dry_run {
+        println!("Done. Total matches upserted={}, match_players upserted={}.", total_matches, total_players);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_matches_history(args: cli::HistoryArgs, ctx: &Ctx) -> Result<()> {
+    if args.force_refetch && args.only_stored_history {
+        anyhow::bail!("--force-refetch and --only-stored-history cannot be used together");
+    }
+    let pool = open_db().await?;
+
+    let acc = ctx.resolve(&args.account).await?;
+
+    // fetch history
+    let entries = ctx.dl.get_player_match_history(acc, args.force_refetch, args.only_stored_history).await?;
+    if entries.is_empty() {
+        println!("No history entries returned for account {}", acc);
+        return Ok(());
+    }
+
+    let metas = models::history_to_metas(entries);
+
+    if args.dry_run {
+        println!("dry-run: would persist {} matches ({} participants)", metas.len(), metas.iter().map(|m| m.players.as_ref().map(|v| v.len()).unwrap_or(0)).sum::<usize>());
+        return Ok(());
+    }
+
+    let res = db::ingest_matches_batch(&pool, &metas).await?;
+    println!("History persisted. matches_upserted={}, match_players_upserted={}", res.matches_upserted, res.match_players_upserted);
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_matches_list(args: cli::ListArgs, ctx: &Ctx) -> Result<()> {
+    let pool = open_db().await?;
+
+    let acc = ctx.resolve(&args.account).await?;
+    let catalog = ctx.catalog(&pool).await;
+    let hero_id = match args.hero {
+        Some(h) => Some(catalog.find(&h).map(|h| h.id).with_context(|| format!("Unknown hero '{}'", h))?),
+        None => None,
     };
+    let filter = history::HistoryFilter {
+        hero_id,
+        mode: args.mode,
+        victory: if args.wins_only { Some(true) } else if args.losses_only { Some(false) } else { None },
+        ..Default::default()
+    }
+    .with_dates(args.since, args.until);
+
+    let query = filter.query(acc, args.sort, args.asc);
+
+    if args.csv {
+        let mut rows = db::load_player_matches_filtered(&pool, &query, None, 0).await?;
+        let ids: Vec<i64> = rows.iter().map(|r| r.match_id).collect();
+        score::Scores::new(db::load_scores(&pool, &ids, Some(acc)).await?).annotate(acc, &mut rows);
+        ui::write_match_csv(&rows, &catalog, &mut io::stdout().lock())?;
+        return Ok(());
+    }
+    let mut page = history::Page::empty(db::count_player_matches(&pool, &query).await?, args.page, args.per_page);
+    page.items = db::load_player_matches_filtered(&pool, &query, Some(page.per_page), page.offset()).await?;
+    let ids: Vec<i64> = page.items.iter().map(|r| r.match_id).collect();
+    score::Scores::new(db::load_scores(&pool, &ids, Some(acc)).await?).annotate(acc, &mut page.items);
+    if ctx.json {
+        return ui::print_json(&page, &catalog);
+    }
+    if page.total == 0 {
+        println!("No stored matches for account {} match these filters. Run `matches history` to sync.", acc);
+        return Ok(());
+    }
+    ui::print_match_list(&page, &catalog, ctx.style);
+    Ok(())
+}
 
-    let latest_mmr = match mmr_res {
-        Ok(v) => ui::latest_mmr_for(&v, account_id),
-        Err(e) => {
-            eprintln!("Warning: failed to fetch MMR: {}", e);
-            None
+async fn run_matches_show(match_id: i64, ctx: &Ctx) -> Result<()> {
+    #[cfg(feature = "db")]
+    let pool = open_db().await?;
+    #[cfg(feature = "db")]
+    let mut meta = db::load_match(&pool, match_id).await?;
+    #[cfg(not(feature = "db"))]
+    let mut meta: Option<models::MatchMeta> = None;
+
+    // history syncs only store the looked-up player, so partial rosters are refetched
+    let partial = meta.as_ref().is_none_or(|m| m.players.as_ref().is_none_or(|p| p.len() < models::PLAYERS_PER_MATCH));
+    if partial && !ctx.offline {
+        match ctx.dl.get_matches_metadata(&[match_id], true, true).await {
+            Ok(metas) => {
+                if let Some(fetched) = metas.into_iter().next() {
+                    #[cfg(feature = "db")]
+                    db::ingest_matches_batch(&pool, std::slice::from_ref(&fetched)).await?;
+                    meta = Some(fetched);
+                }
+            }
+            Err(e) if meta.is_some() => eprintln!("Warning: failed to fetch full scoreboard: {}", e),
+            Err(e) => return Err(e.into()),
         }
+    }
+    let Some(meta) = meta else {
+        anyhow::bail!("Match {} not found", match_id);
     };
 
-    let hero_stats = match hero_stats_res {
-        Ok(v) => v,
-        Err(e) => {
-            eprintln!("Warning: failed to fetch hero stats: {}", e);
+    #[cfg(feature = "db")]
+    let (catalog, scores) = (ctx.catalog(&pool).await, score::Scores::new(db::load_scores(&pool, &[match_id], None).await?));
+    #[cfg(not(feature = "db"))]
+    let (catalog, scores) = (heroes::HeroCatalog::bundled(), score::Scores::default());
+
+    let objectives = meta.objectives();
+    if ctx.json {
+        let payload = ui::MatchDetailPayload {
+            meta: &meta,
+            objectives: objectives.map(|o| ui::ObjectivesPayload::new(match_id, &o)),
+            scores: ui::PlayerScore::of(&meta, &scores),
+        };
+        return ui::print_json(&payload, &catalog);
+    }
+    ui::print_match_summary(&meta);
+    ui::print_scoreboard(&meta, &catalog, &scores);
+    if let Some(o) = objectives {
+        ui::print_match_objectives(match_id, &o);
+    }
+    Ok(())
+}
+
+async fn run_matches_objectives(match_id: i64, ctx: &Ctx) -> Result<()> {
+    #[cfg(feature = "db")]
+    let pool = open_db().await?;
+    #[cfg(feature = "db")]
+    let mut objectives = db::load_match_objectives(&pool, match_id).await?;
+    #[cfg(not(feature = "db"))]
+    let mut objectives = None;
+
+    if objectives.is_none() && !ctx.offline {
+        let metas = ctx.dl.get_matches_metadata(&[match_id], true, true).await?;
+        #[cfg(feature = "db")]
+        db::ingest_matches_batch(&pool, &metas).await?;
+        objectives = metas.first().and_then(|m| m.objectives());
+    }
+    let Some(objectives) = objectives else {
+        anyhow::bail!("No objective data for match {}. Sync it with `matches sync --id {}` first.", match_id, match_id);
+    };
+
+    if ctx.json {
+        println!("{}", serde_json::to_string_pretty(&ui::ObjectivesPayload::new(match_id, &objectives))?);
+    } else {
+        ui::print_match_objectives(match_id, &objectives);
+    }
+    Ok(())
+}
+
+async fn run_mmr(args: cli::MmrArgs, ctx: &Ctx) -> Result<()> {
+    let acc = ctx.resolve(&args.account).await?;
+    let fetched = if ctx.offline {
+        Vec::new()
+    } else {
+        ctx.dl.get_mmr_history(acc).await.unwrap_or_else(|e| {
+            eprintln!("Warning: failed to fetch MMR history: {}", e);
             Vec::new()
+        })
+    };
+
+    #[cfg(feature = "db")]
+    let series = {
+        let pool = open_db().await?;
+        if !fetched.is_empty() {
+            db::store_mmr_history(&pool, acc, &fetched).await?;
+        }
+        mmr::merge([db::load_mmr_history(&pool, acc).await?, fetched])
+    };
+    #[cfg(not(feature = "db"))]
+    let series = mmr::merge([fetched]);
+
+    if series.is_empty() {
+        anyhow::bail!("No MMR history for account {}", acc);
+    }
+    let report = mmr::analyze(acc, &series, args.window);
+    if ctx.json {
+        return ui::print_json(&report, &heroes::HeroCatalog::default());
+    }
+    ui::print_mmr_report(&report, args.limit, ctx.style);
+    Ok(())
+}
+
+async fn run_batch(args: cli::BatchArgs, ctx: &Ctx) -> Result<()> {
+    if ctx.offline {
+        anyhow::bail!("batch looks players up through the Deadlock API; drop --offline");
+    }
+    let inputs = match args.file.as_deref() {
+        Some(path) if path != std::path::Path::new("-") => {
+            let f = std::fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+            batch::read_inputs(io::BufReader::new(f))?
         }
+        _ => batch::read_inputs(io::stdin().lock())?,
     };
+    if inputs.is_empty() {
+        anyhow::bail!("No identifiers to look up");
+    }
+
+    let resolved = batch::resolve_all(&inputs, &ctx.http, ctx.recorder.as_ref(), args.concurrency).await;
+    let records = batch::fetch_all(&ctx.dl, &inputs, resolved, args.chunk_size).await;
 
     #[cfg(feature = "db")]
-    {
-        let combined = ui::CombinedPayload {
-            steamid64: steamid64.to_string(),
-            account_id,
-            profile: steam_profile.clone(),
-            latest_mmr: latest_mmr.clone(),
-            hero_stats: hero_stats.clone(),
-        };
-        let db::DbPool(pool) = db::connect().await?;
-        db::migrate(&pool).await?; // ensure schema
+    let (records, catalog) = {
+        let mut records = records;
+        let pool = open_db().await?;
+        let mut saved = std::collections::HashSet::new();
+        for r in records.iter_mut() {
+            let Some(p) = &r.player else { continue };
+            if saved.contains(&p.account_id) {
+                continue;
+            }
+            match db::ingest_player(&pool, p).await {
+                Ok(_) => {
+                    saved.insert(p.account_id);
+                }
+                Err(e) => r.error = Some(format!("failed to save: {:#}", e)),
+            }
+        }
+        eprintln!("Saved to DB: {} players", saved.len());
+        (records, ctx.catalog(&pool).await)
+    };
+    #[cfg(not(feature = "db"))]
+    let catalog = heroes::HeroCatalog::bundled();
+
+    for r in records.iter().filter(|r| !r.is_ok()) {
+        eprintln!("line {}: {}: {}", r.line, r.input, r.error.as_deref().unwrap_or_default());
+    }
+    if ctx.json {
+        for r in &records {
+            ui::print_json_line(r, &catalog)?;
+        }
+    } else {
+        ui::print_batch_table(&records);
+    }
+
+    let failed = records.iter().filter(|r| !r.is_ok()).count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} lines failed", failed, records.len());
+    }
+    Ok(())
+}
+
+async fn run_compare(players: Vec<String>, ctx: &Ctx) -> Result<()> {
+    let inputs: Vec<batch::BatchInput> =
+        players.into_iter().enumerate().map(|(i, input)| batch::BatchInput { line: i + 1, input }).collect();
+    let mut ids = Vec::new();
+    for (input, res) in inputs.iter().zip(batch::resolve_all(&inputs, &ctx.http, ctx.recorder.as_ref(), inputs.len()).await) {
+        let id = res.map_err(|e| anyhow::anyhow!("{}: {}", input.input, e))?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    if ids.len() < 2 {
+        anyhow::bail!("Need at least two different players to compare");
+    }
+
+    #[cfg(feature = "db")]
+    let pool = open_db().await?;
+    let payloads = if ctx.offline {
+        #[cfg(feature = "db")]
+        {
+            let mut stored = Vec::new();
+            for &id in &ids {
+                let s = db::load_player(&pool, id)
+                    .await?
+                    .with_context(|| format!("No stored data for account {} (run an online lookup first)", id))?;
+                stored.push(s.payload);
+            }
+            stored
+        }
+        #[cfg(not(feature = "db"))]
+        anyhow::bail!("DB feature not enabled. Rebuild with `--features db`.");
+    } else {
+        let fetched = deadlock::fetch_players(&ctx.dl, &ids).await?;
+        if let Some(missing) = ids.iter().find(|id| !fetched.iter().any(|p| p.account_id == **id)) {
+            anyhow::bail!("Player {} not found (no Steam profile).", missing);
+        }
+        #[cfg(feature = "db")]
+        for p in &fetched {
+            db::ingest_player(&pool, p).await?;
+        }
+        fetched
+    };
+
+    #[cfg(feature = "db")]
+    let catalog = ctx.catalog(&pool).await;
+    #[cfg(not(feature = "db"))]
+    let catalog = heroes::HeroCatalog::bundled();
+
+    let comparison = compare::Comparison::new(&payloads.iter().collect::<Vec<_>>());
+    if ctx.json {
+        return ui::print_json(&comparison, &catalog);
+    }
+    ui::print_comparison(&comparison, &catalog, ctx.style);
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_vs(args: cli::VsArgs, ctx: &Ctx) -> Result<()> {
+    let pool = open_db().await?;
+
+    let acc_a = steam::resolve_account_id(&args.a, &ctx.http, ctx.recorder.as_ref()).await?;
+    let acc_b = steam::resolve_account_id(&args.b, &ctx.http, ctx.recorder.as_ref()).await?;
+    if acc_a == acc_b {
+        anyhow::bail!("Both identifiers resolve to account {}", acc_a);
+    }
+
+    // each history sync stores that player's row, so syncing both fills in shared matches
+    if !ctx.offline {
+        for acc in [acc_a, acc_b] {
+            ctx.sync_history(&pool, acc).await?;
+        }
+    }
+
+    let report = duo::analyze(
+        acc_a,
+        &db::load_player_matches(&pool, acc_a).await?,
+        acc_b,
+        &db::load_player_matches(&pool, acc_b).await?,
+    );
+    let catalog = ctx.catalog(&pool).await;
+    if ctx.json {
+        return ui::print_json(&report, &catalog);
+    }
+    if report.shared.is_empty() {
+        println!("No stored matches shared by {} and {}.", acc_a, acc_b);
+        return Ok(());
+    }
+    let name = |p: Option<db::StoredPlayer>, acc: u32| {
+        p.map(|s| s.payload.profile.personaname).filter(|n| !n.is_empty()).unwrap_or_else(|| acc.to_string())
+    };
+    let name_a = name(db::load_player(&pool, acc_a).await?, acc_a);
+    let name_b = name(db::load_player(&pool, acc_b).await?, acc_b);
+    ui::print_duo_report(&report, &name_a, &name_b, &catalog, args.pairings, args.limit);
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_mates(args: cli::MatesArgs, ctx: &Ctx) -> Result<()> {
+    let pool = open_db().await?;
+
+    let acc = ctx.resolve(&args.account).await?;
+    let mut report = mates::analyze(acc, &db::load_co_players(&pool, acc).await?, args.min_matches, args.party_only, args.limit);
+    if report.matches == 0 {
+        anyhow::bail!(
+            "No stored scoreboards for account {}. Ingest full matches first, e.g. `matches sync --from-account-id {}`.",
+            acc,
+            acc
+        );
+    }
+
+    let stubs = report.stubs();
+    if args.fetch_profiles && !stubs.is_empty() {
+        if ctx.offline {
+            anyhow::bail!("--fetch-profiles calls the Deadlock API; drop --offline");
+        }
+        for chunk in stubs.chunks(100) {
+            let profiles = ctx.dl.get_steam_profiles(chunk).await?;
+            db::fill_player_profiles(&pool, &profiles).await?;
+            for p in &profiles {
+                report.set_name(p.account_id as u32, &p.personaname);
+            }
+        }
+    }
+
+    if ctx.json {
+        return ui::print_json(&report, &heroes::HeroCatalog::default());
+    }
+    ui::print_mates_report(&report);
+    let unnamed = report.stubs().len();
+    if unnamed > 0 && !args.fetch_profiles {
+        eprintln!("{} accounts have no stored name; add --fetch-profiles to look them up.", unnamed);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_party(args: cli::PartyArgs, ctx: &Ctx) -> Result<()> {
+    let pool = open_db().await?;
+
+    let acc = ctx.resolve(&args.account).await?;
+    let own = db::load_player_matches(&pool, acc).await?;
+    if own.is_empty() {
+        anyhow::bail!("No stored matches for account {}. Run `matches history` or `matches sync` first.", acc);
+    }
+    let report = party::analyze(acc, &own, &db::load_co_players(&pool, acc).await?, args.min_matches, args.limit);
+    if ctx.json {
+        return ui::print_json(&report, &heroes::HeroCatalog::default());
+    }
+    ui::print_party_report(&report, ctx.style);
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_sessions(args: cli::SessionsArgs, ctx: &Ctx) -> Result<()> {
+    let pool = open_db().await?;
+
+    let offset = match args.utc_offset {
+        Some(h) => chrono::FixedOffset::east_opt((h * 3600.0).round() as i32).context("--utc-offset must be between -24 and 24 hours")?,
+        None => *chrono::Local::now().offset(),
+    };
+    let acc = ctx.resolve(&args.account).await?;
+    if !ctx.offline {
+        ctx.sync_history(&pool, acc).await?;
+    }
+    let rows = db::load_player_matches(&pool, acc).await?;
+    if rows.is_empty() {
+        anyhow::bail!("No stored matches for account {}. Run `matches history` or `matches sync` first.", acc);
+    }
+    let mut report = sessions::analyze(acc, &rows, args.gap, args.long_session, args.min_matches, offset);
+    report.sessions.truncate(args.limit);
+    if ctx.json {
+        return ui::print_json(&report, &heroes::HeroCatalog::default());
+    }
+    ui::print_sessions_report(&report, ctx.style);
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_patches(args: cli::PatchesArgs, ctx: &Ctx) -> Result<()> {
+    let pool = open_db().await?;
+
+    let (acc, rows) = if args.all {
+        (None, db::load_all_player_matches(&pool).await?)
+    } else if args.account.is_empty() {
+        anyhow::bail!("Provide one of --account-id, --steamid, --id3, or --all");
+    } else {
+        let acc = ctx.resolve(&args.account).await?;
+        (Some(acc), db::load_player_matches(&pool, acc).await?)
+    };
+    let catalog = ctx.catalog(&pool).await;
+    let hero_id = match args.hero {
+        Some(h) => Some(catalog.find(&h).map(|h| h.id).with_context(|| format!("Unknown hero '{}'", h))?),
+        None => None,
+    };
+
+    // deltas need every patch, so trimming happens after the analysis
+    let mut report = patches::analyze(acc, &rows);
+    let skip = report.patches.len().saturating_sub(args.patches);
+    report.patches.drain(..skip);
+    for p in report.patches.iter_mut() {
+        p.heroes.retain(|h| h.matches >= args.min_matches && hero_id.is_none_or(|id| h.hero_id == id));
+        p.heroes.truncate(args.limit);
+    }
+
+    if ctx.json {
+        return ui::print_json(&report, &catalog);
+    }
+    if report.patches.is_empty() {
+        println!("No stored matches with a patch version. Sync some with `matches sync` or `matches history`.");
+        return Ok(());
+    }
+    ui::print_patch_report(&report, &catalog);
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_meta(args: cli::MetaArgs, ctx: &Ctx) -> Result<()> {
+    let pool = open_db().await?;
+
+    let catalog = ctx.catalog(&pool).await;
+    let filter = models::MetaFilter { min_badge: args.min_badge, max_badge: args.max_badge, region: args.region, patch: args.patch }
+        .with_rank(args.rank);
+    let totals = db::load_meta_totals(&pool, &filter).await?;
+    let mut report = meta::analyze(totals, filter, args.min_sample, args.sort);
+    report.heroes.truncate(args.limit);
+
+    if ctx.json {
+        return ui::print_json(&report, &catalog);
+    }
+    if report.matches == 0 {
+        println!("No stored matches pass the filters. Sync some with `matches sync` or `matches history`.");
+        return Ok(());
+    }
+    ui::print_meta_report(&report, &catalog);
+    Ok(())
+}
+
+#[cfg(feature = "db")]
+async fn run_heroes_synergy(args: cli::SynergyArgs, ctx: &Ctx) -> Result<()> {
+    if args.hero.is_none() && !args.csv && !ctx.json {
+        anyhow::bail!("Pass --hero <name|id>, or --csv / --json to export the full matrix");
+    }
+    let pool = open_db().await?;
+
+    let catalog = ctx.catalog(&pool).await;
+    let hero_id = match args.hero {
+        Some(h) => Some(catalog.find(&h).map(|h| h.id).with_context(|| format!("Unknown hero '{}'", h))?),
+        None => None,
+    };
+    let filter = models::MetaFilter { region: args.region, patch: args.patch, ..Default::default() }.with_rank(args.rank);
+    let report = synergy::analyze(&db::load_synergy_totals(&pool, &filter).await?);
+
+    let Some(hero_id) = hero_id else {
+        let m = report.matrix(args.matrix, args.min_matches);
+        if args.csv {
+            ui::write_matrix_csv(&m, &catalog, &mut io::stdout().lock())?;
+            return Ok(());
+        }
+        return ui::print_json(&m, &catalog);
+    };
+    let h = report.for_hero(hero_id, args.min_matches, args.limit);
+    if ctx.json {
+        return ui::print_json(&h, &catalog);
+    }
+    if h.baseline.is_none() {
+        println!("No stored matches with {}. Sync some with `matches sync`.", catalog.name(hero_id));
+        return Ok(());
+    }
+    ui::print_hero_synergy(hero_id, &h, &catalog);
+    Ok(())
+}
+
+async fn run_shell(ctx: &Ctx) -> Result<()> {
+    #[cfg(feature = "db")]
+    let pool = open_db().await?;
+    #[cfg(feature = "db")]
+    let (catalog, known) = (ctx.catalog(&pool).await, db::known_players(&pool).await?);
+    #[cfg(not(feature = "db"))]
+    let (catalog, known) = (heroes::HeroCatalog::bundled(), Vec::new());
+    let env = ctx.env(
+        #[cfg(feature = "db")]
+        &pool,
+    );
+    let out = repl::Output { catalog: &catalog, style: ctx.style, json: ctx.json };
+    repl::run(&env, known, &out).await
+}
+
+async fn run_dashboard(ctx: &Ctx) -> Result<()> {
+    if !io::stdout().is_terminal() {
+        anyhow::bail!("The dashboard needs a terminal; use a subcommand instead (see --help).");
+    }
+    #[cfg(feature = "db")]
+    let pool = open_db().await?;
+    #[cfg(feature = "db")]
+    let catalog = ctx.catalog(&pool).await;
+    #[cfg(not(feature = "db"))]
+    let catalog = heroes::HeroCatalog::bundled();
+    let env = ctx.env(
+        #[cfg(feature = "db")]
+        &pool,
+    );
+    tui::run(&env, catalog, ctx.style.color).await
+}
+
+/// Single-player lookup: the API (or the DB with `--offline`), persisted and printed.
+async fn run_lookup(steamid64: &str, ctx: &Ctx) -> Result<()> {
+    let steamid64 = steamid64.trim();
+    steam::validate_steamid64(steamid64)?;
+    let account_id = steam::steamid64_to_account_id(steamid64)?;
+
+    if ctx.offline {
+        #[cfg(feature = "db")]
+        {
+            let pool = open_db().await?;
+            let stored = db::load_player(&pool, account_id)
+                .await?
+                .with_context(|| format!("No stored data for account {} (run an online lookup first)", account_id))?;
+            db::mark_lookup(&pool, account_id).await?;
+            let catalog = heroes::load_catalog(&pool, &ctx.http, true).await;
+            if ctx.json {
+                let payload = ui::OfflinePayload {
+                    payload: &stored.payload,
+                    offline: true,
+                    profile_updated_at: stored.profile_updated_at,
+                    staleness: ui::Staleness::from_updated_at(stored.profile_updated_at, chrono::Utc::now()),
+                };
+                return ui::print_json(&payload, &catalog);
+            }
+            ui::print_offline_banner(stored.profile_updated_at);
+            return print_player(&stored.payload, &catalog, ctx.style);
+        }
+        #[cfg(not(feature = "db"))]
+        {
+            anyhow::bail!("DB feature not enabled. Rebuild with `--features db`.");
+        }
+    }
+
+    let combined = deadlock::fetch_player(&ctx.dl, steamid64, account_id).await?;
+
+    #[cfg(feature = "db")]
+    let catalog = {
+        let pool = open_db().await?; // ensure schema
         let res = db::ingest_player(&pool, &combined).await?;
         eprintln!(
             "Saved to DB: heroes_upserted={}, hero_history_added={}, mmr_updated={}",
             res.heroes_upserted, res.hero_history_added, res.mmr_updated
         );
 
-        match dl.get_player_match_history(account_id, false, true).await {
+        match ctx.dl.get_player_match_history(account_id, false, true).await {
             Ok(entries) if !entries.is_empty() => {
-                use std::collections::BTreeMap;
-                let mut grouped: BTreeMap<i64, (Option<i64>, Option<i32>, Vec<crate::models::PlayerInMatch>)> = BTreeMap::new();
-                for e in entries {
-                    let start = Some(e.start_time as i64);
-                    let dur = Some(e.match_duration_s);
-                    let ent = grouped.entry(e.match_id).or_insert((start, dur, Vec::new()));
-                    if ent.0.is_none() { ent.0 = start; }
-                    if ent.1.is_none() { ent.1 = dur; }
-                    let extra = serde_json::json!({
-                        "denies": e.denies,
-                        "game_mode": e.game_mode,
-                        "match_mode": e.match_mode,
-                        "match_result": e.match_result,
-                        "objectives_mask_team0": e.objectives_mask_team0,
-                        "objectives_mask_team1": e.objectives_mask_team1,
-                        "hero_level": e.hero_level
-                    });
-                    let pim = crate::models::PlayerInMatch {
-                        account_id: e.account_id,
-                        hero_id: Some(e.hero_id),
-                        team: Some(format!("team{}", e.player_team)),
-                        party_id: None,
-                        lane: None,
-                        is_victory: None,
-                        kills: Some(e.player_kills),
-                        deaths: Some(e.player_deaths),
-                        assists: Some(e.player_assists),
-                        networth: Some(e.net_worth as i64),
-                        damage: None,
-                        damage_taken: None,
-                        obj_damage: None,
-                        last_hits: Some(e.last_hits),
-                        accuracy: None,
-                        crit_shot_rate: None,
-                        extra: Some(extra),
-                    };
-                    ent.2.push(pim);
-                }
-                let metas: Vec<crate::models::MatchMeta> = grouped.into_iter().map(|(mid, (st, du, players))| {
-                    crate::models::MatchMeta {
-                        match_id: mid,
-                        start_time: st,
-                        duration_s: du,
-                        winner_team: None,
-                        average_badge: None,
-                        region: None,
-                        patch_version: None,
-                        info: None,
-                        players: Some(players),
-                    }
-                }).collect();
+                let metas = models::history_to_metas(entries);
                 if !metas.is_empty() {
                     let mres = db::ingest_matches_batch(&pool, &metas).await?;
                     eprintln!(
@@ -471,27 +937,27 @@ async fn async_main() -> Result<()> {
                 eprintln!("Warning: failed to fetch match history: {}", e);
             }
         }
-    }
 
-    if want_json {
-        let payload = ui::CombinedPayload {
-            steamid64: steamid64.to_string(),
-            account_id,
-            profile: steam_profile.clone(),
-            latest_mmr: latest_mmr.clone(),
-            hero_stats: hero_stats.clone(),
-        };
-        let s = serde_json::to_string_pretty(&payload)?;
-        println!("{}", s);
-        return Ok(());
+        ctx.catalog(&pool).await
+    };
+    #[cfg(not(feature = "db"))]
+    let catalog = heroes::HeroCatalog::bundled();
+
+    if ctx.json {
+        return ui::print_json(&combined, &catalog);
     }
 
-    ui::print_profile_table(&steam_profile, steamid64, account_id, latest_mmr.as_ref());
-    ui::print_stats_table(&hero_stats);
+    print_player(&combined, &catalog, ctx.style)
+}
+
+fn print_player(combined: &models::CombinedPayload, catalog: &heroes::HeroCatalog, style: ui::ChartStyle) -> Result<()> {
+    ui::print_profile_table(&combined.profile, &combined.steamid64, combined.account_id, combined.latest_mmr.as_ref());
+    ui::print_stats_table(&combined.hero_stats);
 
-    let show_details = confirm("Show detailed hero stats? [y/N] ")?;
+    // only ask when someone can answer; piped/scripted runs get the summary tables
+    let show_details = io::stdin().is_terminal() && io::stdout().is_terminal() && confirm("Show detailed hero stats? [y/N] ")?;
     if show_details {
-        ui::print_detailed_hero_stats(&hero_stats);
+        ui::print_detailed_hero_stats(&combined.hero_stats, catalog, style);
     }
 
     Ok(())
@@ -0,0 +1,123 @@
+use crate::models::CoPlayer;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// How one account relates to the player across shared matches.
+#[derive(Debug, Clone, Serialize)]
+pub struct Associate {
+    pub account_id: u32,
+    pub personaname: Option<String>,
+    pub matches: usize,
+    /// Same team (or same party with `party_only`)
+    pub with: usize,
+    /// Queued in the same party
+    pub party: usize,
+    pub against: usize,
+    pub wins_with: usize,
+    pub wins_against: usize,
+    /// Percent, the player's
+    pub win_rate_with: Option<f64>,
+    pub win_rate_against: Option<f64>,
+    /// Unix seconds
+    pub last_seen: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatesReport {
+    pub account_id: u32,
+    /// Stored matches that have other participants
+    pub matches: usize,
+    pub party_only: bool,
+    /// Most frequent first
+    pub mates: Vec<Associate>,
+    pub opponents: Vec<Associate>,
+}
+
+impl MatesReport {
+    /// Listed accounts without a persona name.
+    pub fn stubs(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .mates
+            .iter()
+            .chain(&self.opponents)
+            .filter(|a| a.personaname.as_deref().is_none_or(str::is_empty))
+            .map(|a| a.account_id)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    pub fn set_name(&mut self, account_id: u32, name: &str) {
+        for a in self.mates.iter_mut().chain(self.opponents.iter_mut()).filter(|a| a.account_id == account_id) {
+            a.personaname = Some(name.to_string());
+        }
+    }
+}
+
+fn rate(wins: usize, n: usize) -> Option<f64> {
+    (n > 0).then(|| wins as f64 / n as f64 * 100.0)
+}
+
+/// Group co-player rows by account. Each list keeps accounts seen at least `min_matches`
+/// times on that side, at most `limit` of them.
+pub fn analyze(account_id: u32, rows: &[CoPlayer], min_matches: usize, party_only: bool, limit: usize) -> MatesReport {
+    let mut by_account: HashMap<u32, Associate> = HashMap::new();
+    let mut match_ids = HashSet::new();
+    for r in rows.iter().filter(|r| r.account_id != account_id) {
+        match_ids.insert(r.match_id);
+        let a = by_account.entry(r.account_id).or_insert_with(|| Associate {
+            account_id: r.account_id,
+            personaname: None,
+            matches: 0,
+            with: 0,
+            party: 0,
+            against: 0,
+            wins_with: 0,
+            wins_against: 0,
+            win_rate_with: None,
+            win_rate_against: None,
+            last_seen: None,
+        });
+        if a.personaname.is_none() {
+            a.personaname = r.personaname.clone().filter(|n| !n.is_empty());
+        }
+        a.matches += 1;
+        a.last_seen = a.last_seen.max(r.start_time);
+        let won = usize::from(r.is_victory == Some(true));
+        if r.same_party() {
+            a.party += 1;
+        }
+        let with = if party_only { r.same_party() } else { r.same_team() == Some(true) };
+        if with {
+            a.with += 1;
+            a.wins_with += won;
+        } else if r.same_team() == Some(false) {
+            a.against += 1;
+            a.wins_against += won;
+        }
+    }
+
+    let all: Vec<Associate> = by_account
+        .into_values()
+        .map(|mut a| {
+            a.win_rate_with = rate(a.wins_with, a.with);
+            a.win_rate_against = rate(a.wins_against, a.against);
+            a
+        })
+        .collect();
+    let top = |count: fn(&Associate) -> usize| {
+        let mut list: Vec<Associate> = all.iter().filter(|a| count(a) >= min_matches.max(1)).cloned().collect();
+        list.sort_by(|x, y| count(y).cmp(&count(x)).then(y.last_seen.cmp(&x.last_seen)).then(x.account_id.cmp(&y.account_id)));
+        list.truncate(limit);
+        list
+    };
+
+    MatesReport {
+        account_id,
+        matches: match_ids.len(),
+        party_only,
+        mates: top(|a| a.with),
+        opponents: top(|a| a.against),
+    }
+}
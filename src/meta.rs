@@ -0,0 +1,118 @@
+use crate::models::{MetaFilter, MetaTotals};
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// z for a two-sided 95% interval.
+const Z_95: f64 = 1.96;
+
+/// A 95% confidence interval, in percent.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Interval {
+    pub low: f64,
+    pub high: f64,
+}
+
+/// Wilson score interval for `successes` out of `n`; stays inside 0-100% and behaves on
+/// small samples, unlike the normal approximation.
+pub fn wilson(successes: usize, n: usize) -> Option<Interval> {
+    if n == 0 {
+        return None;
+    }
+    let (n, p) = (n as f64, successes as f64 / n as f64);
+    let z2 = Z_95 * Z_95;
+    let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+    Some(Interval { low: (centre - half).max(0.0) * 100.0, high: (centre + half).min(1.0) * 100.0 })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HeroMeta {
+    pub hero_id: i32,
+    pub picks: usize,
+    pub wins: usize,
+    /// Percent, over picks with a known result
+    pub win_rate: Option<f64>,
+    pub win_rate_ci: Option<Interval>,
+    /// Percent of matches the hero was picked in
+    pub pick_rate: f64,
+    pub pick_rate_ci: Option<Interval>,
+    pub avg_kills: f64,
+    pub avg_deaths: f64,
+    pub avg_assists: f64,
+    /// `(kills + assists) / max(deaths, 1)` over the summed totals
+    pub kda: f64,
+    pub avg_damage: Option<f64>,
+    pub avg_networth: Option<f64>,
+    /// Fewer picks than the report's `min_sample`; its rates are not reliable
+    pub low_sample: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetaReport {
+    pub filter: MetaFilter,
+    /// Distinct matches after filtering
+    pub matches: usize,
+    pub picks: usize,
+    pub min_sample: usize,
+    pub heroes: Vec<HeroMeta>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MetaSort {
+    WinRate,
+    PickRate,
+    Picks,
+    Kda,
+    Damage,
+    Networth,
+}
+
+impl MetaSort {
+    fn key(self, h: &HeroMeta) -> Option<f64> {
+        match self {
+            MetaSort::WinRate => h.win_rate,
+            MetaSort::PickRate => Some(h.pick_rate),
+            MetaSort::Picks => Some(h.picks as f64),
+            MetaSort::Kda => Some(h.kda),
+            MetaSort::Damage => h.avg_damage,
+            MetaSort::Networth => h.avg_networth,
+        }
+    }
+}
+
+/// Per-hero meta from the totals of the matches that passed `filter`, sorted descending by
+/// `sort` with low-sample heroes after the rest.
+pub fn analyze(totals: MetaTotals, filter: MetaFilter, min_sample: usize, sort: MetaSort) -> MetaReport {
+    let MetaTotals { matches, heroes } = totals;
+    let picks = heroes.iter().map(|t| t.picks).sum();
+    let mut heroes: Vec<HeroMeta> = heroes
+        .into_iter()
+        .map(|t| {
+            let per = |v: i64| v as f64 / t.picks as f64;
+            HeroMeta {
+                hero_id: t.hero_id,
+                picks: t.picks,
+                wins: t.wins,
+                win_rate: (t.decided > 0).then(|| t.wins as f64 / t.decided as f64 * 100.0),
+                win_rate_ci: wilson(t.wins, t.decided),
+                pick_rate: t.picks as f64 / matches.max(1) as f64 * 100.0,
+                pick_rate_ci: wilson(t.picks.min(matches), matches),
+                avg_kills: per(t.kills),
+                avg_deaths: per(t.deaths),
+                avg_assists: per(t.assists),
+                kda: (t.kills + t.assists) as f64 / t.deaths.max(1) as f64,
+                avg_damage: t.avg_damage,
+                avg_networth: t.avg_networth,
+                low_sample: t.picks < min_sample,
+            }
+        })
+        .collect();
+    heroes.sort_by(|a, b| {
+        a.low_sample
+            .cmp(&b.low_sample)
+            .then_with(|| sort.key(b).unwrap_or(f64::MIN).total_cmp(&sort.key(a).unwrap_or(f64::MIN)))
+            .then(a.hero_id.cmp(&b.hero_id))
+    });
+
+    MetaReport { filter, matches, picks, min_sample, heroes }
+}
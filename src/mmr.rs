@@ -0,0 +1,86 @@
+use crate::models::{Badge, MMRHistory};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One MMR snapshot with its change from the previous one.
+#[derive(Debug, Clone, Serialize)]
+pub struct MmrPoint {
+    pub match_id: i64,
+    /// Unix seconds
+    pub start_time: i32,
+    pub player_score: f64,
+    /// `division * 10 + tier`
+    pub rank: i32,
+    pub badge: Option<Badge>,
+    /// `player_score` change since the previous snapshot
+    pub delta: Option<f64>,
+    /// Mean `player_score` over the last `window` snapshots, this one included
+    pub rolling_avg: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MmrReport {
+    pub account_id: u32,
+    pub window: usize,
+    pub peak: Option<MmrPoint>,
+    pub lowest: Option<MmrPoint>,
+    /// `player_score` change from the first to the latest snapshot
+    pub net_change: f64,
+    pub timeline: Vec<MmrPoint>,
+}
+
+impl MmrReport {
+    pub fn first(&self) -> Option<&MmrPoint> {
+        self.timeline.first()
+    }
+
+    pub fn latest(&self) -> Option<&MmrPoint> {
+        self.timeline.last()
+    }
+}
+
+/// Combine API and stored snapshots, one per `start_time`, oldest first. Later sources win.
+pub fn merge(sources: impl IntoIterator<Item = Vec<MMRHistory>>) -> Vec<MMRHistory> {
+    let mut by_time: BTreeMap<i32, MMRHistory> = BTreeMap::new();
+    for m in sources.into_iter().flatten() {
+        by_time.insert(m.start_time, m);
+    }
+    by_time.into_values().collect()
+}
+
+/// Build the timeline for `series` (oldest first). Peak and lowest compare ranks, then scores;
+/// ties go to the earliest snapshot.
+pub fn analyze(account_id: u32, series: &[MMRHistory], window: usize) -> MmrReport {
+    let window = window.max(1);
+    let mut timeline: Vec<MmrPoint> = Vec::with_capacity(series.len());
+    for (i, m) in series.iter().enumerate() {
+        let recent = &series[i.saturating_sub(window - 1)..=i];
+        timeline.push(MmrPoint {
+            match_id: m.match_id,
+            start_time: m.start_time,
+            player_score: m.player_score,
+            rank: m.rank,
+            badge: m.badge(),
+            delta: i.checked_sub(1).map(|p| m.player_score - series[p].player_score),
+            rolling_avg: recent.iter().map(|r| r.player_score).sum::<f64>() / recent.len() as f64,
+        });
+    }
+
+    let key = |p: &MmrPoint| (p.rank, p.player_score);
+    let mut peak: Option<&MmrPoint> = None;
+    let mut lowest: Option<&MmrPoint> = None;
+    for p in &timeline {
+        if peak.is_none_or(|best| key(p) > key(best)) {
+            peak = Some(p);
+        }
+        if lowest.is_none_or(|worst| key(p) < key(worst)) {
+            lowest = Some(p);
+        }
+    }
+    let net_change = match (timeline.first(), timeline.last()) {
+        (Some(a), Some(b)) => b.player_score - a.player_score,
+        _ => 0.0,
+    };
+
+    MmrReport { account_id, window, peak: peak.cloned(), lowest: lowest.cloned(), net_change, timeline }
+}
@@ -1,7 +1,11 @@
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SteamProfile {
     pub account_id: i32,
     pub personaname: String,
@@ -26,6 +30,187 @@ pub struct MMRHistory {
     pub division_tier: i32,
 }
 
+impl MMRHistory {
+    pub fn badge(&self) -> Option<Badge> {
+        Badge::from_division(self.division, self.division_tier)
+    }
+}
+
+/// Everything one player lookup returns: profile, latest MMR and hero stats.
+#[derive(Debug, Clone, Serialize)]
+pub struct CombinedPayload {
+    pub steamid64: String,
+    pub account_id: u32,
+    pub profile: SteamProfile,
+    pub latest_mmr: Option<MMRHistory>,
+    pub hero_stats: Vec<HeroStats>,
+}
+
+pub fn latest_mmr_for(all: &[MMRHistory], account_id: u32) -> Option<MMRHistory> {
+    all.iter()
+        .filter(|m| m.account_id as u32 == account_id)
+        .cloned()
+        .max_by_key(|m| (m.start_time, m.match_id))
+}
+
+// ============ Ranks / Badges ============
+
+/// Deadlock rank (the API's `division`), lowest to highest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Rank {
+    Obscurus,
+    Initiate,
+    Seeker,
+    Alchemist,
+    Arcanist,
+    Ritualist,
+    Emissary,
+    Archon,
+    Oracle,
+    Phantom,
+    Ascendant,
+    Eternus,
+}
+
+impl Rank {
+    pub const ALL: [Rank; 12] = [
+        Rank::Obscurus,
+        Rank::Initiate,
+        Rank::Seeker,
+        Rank::Alchemist,
+        Rank::Arcanist,
+        Rank::Ritualist,
+        Rank::Emissary,
+        Rank::Archon,
+        Rank::Oracle,
+        Rank::Phantom,
+        Rank::Ascendant,
+        Rank::Eternus,
+    ];
+
+    pub fn from_division(division: i32) -> Option<Rank> {
+        usize::try_from(division).ok().and_then(|i| Rank::ALL.get(i).copied())
+    }
+
+    pub fn division(self) -> i32 {
+        self as i32
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rank::Obscurus => "Obscurus",
+            Rank::Initiate => "Initiate",
+            Rank::Seeker => "Seeker",
+            Rank::Alchemist => "Alchemist",
+            Rank::Arcanist => "Arcanist",
+            Rank::Ritualist => "Ritualist",
+            Rank::Emissary => "Emissary",
+            Rank::Archon => "Archon",
+            Rank::Oracle => "Oracle",
+            Rank::Phantom => "Phantom",
+            Rank::Ascendant => "Ascendant",
+            Rank::Eternus => "Eternus",
+        }
+    }
+}
+
+/// A rank plus its tier (1–6), e.g. "Archon 4". Encoded by the API as `division * 10 + tier`
+/// (`rank` in MMR history, `average_badge` on matches). Orders lowest to highest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Badge {
+    pub rank: Rank,
+    /// 1–6; 0 for Obscurus, which has no tiers
+    pub tier: u8,
+}
+
+impl Badge {
+    pub fn from_division(division: i32, tier: i32) -> Option<Badge> {
+        let rank = Rank::from_division(division)?;
+        match (rank, tier) {
+            (Rank::Obscurus, _) => Some(Badge { rank, tier: 0 }),
+            (_, 1..=6) => Some(Badge { rank, tier: tier as u8 }),
+            _ => None,
+        }
+    }
+
+    /// Decode a combined badge number such as `average_badge` (e.g. `74` = Archon 4).
+    pub fn from_badge(badge: i32) -> Option<Badge> {
+        if badge < 0 {
+            return None;
+        }
+        Badge::from_division(badge / 10, badge % 10)
+    }
+
+    pub fn badge(self) -> i32 {
+        self.rank.division() * 10 + self.tier as i32
+    }
+
+    pub fn name(self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::str::FromStr for Rank {
+    type Err = String;
+
+    /// Accepts the rank name in any case (`archon`) or its division number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(n) = s.trim().parse::<i32>() {
+            return Rank::from_division(n).ok_or_else(|| format!("unknown rank '{}'", s));
+        }
+        Rank::ALL
+            .into_iter()
+            .find(|r| r.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown rank '{}'", s))
+    }
+}
+
+impl fmt::Display for Badge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.tier == 0 {
+            f.write_str(self.rank.name())
+        } else {
+            write!(f, "{} {}", self.rank.name(), self.tier)
+        }
+    }
+}
+
+impl Serialize for Badge {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut st = s.serialize_struct("Badge", 4)?;
+        st.serialize_field("badge", &self.badge())?;
+        st.serialize_field("name", &self.name())?;
+        st.serialize_field("rank", self.rank.name())?;
+        st.serialize_field("tier", &self.tier)?;
+        st.end()
+    }
+}
+
+/// Add decoded badges to a JSON document: `badge` next to every `division`/`division_tier`
+/// pair and `average_badge_info` next to every `average_badge`.
+pub fn annotate_badges(v: &mut Value) {
+    match v {
+        Value::Object(map) => {
+            let div = map.get("division").and_then(Value::as_i64);
+            let tier = map.get("division_tier").and_then(Value::as_i64);
+            if let (Some(d), Some(t)) = (div, tier) {
+                let b = Badge::from_division(d as i32, t as i32);
+                map.insert("badge".into(), serde_json::to_value(b).unwrap_or(Value::Null));
+            }
+            if let Some(avg) = map.get("average_badge").and_then(Value::as_i64) {
+                let b = Badge::from_badge(avg as i32);
+                map.insert("average_badge_info".into(), serde_json::to_value(b).unwrap_or(Value::Null));
+            }
+            for child in map.values_mut() {
+                annotate_badges(child);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(annotate_badges),
+        _ => {}
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct HeroStats {
     pub account_id: i32,
@@ -51,6 +236,215 @@ pub struct HeroStats {
     // many more fields exist; we only map the ones we display
 }
 
+// ============ Match enums ============
+
+/// Lobby team. The API sends `0`/`1` in history entries and `"team0"`/`"team1"`
+/// strings in metadata; both decode here and unknown values are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Team {
+    /// The Hidden King (amber)
+    #[default]
+    Team0,
+    /// The Archmother (sapphire)
+    Team1,
+    Unknown(i32),
+}
+
+impl Team {
+    pub fn id(self) -> i32 {
+        match self {
+            Team::Team0 => 0,
+            Team::Team1 => 1,
+            Team::Unknown(n) => n,
+        }
+    }
+
+    pub fn is_known(self) -> bool {
+        !matches!(self, Team::Unknown(_))
+    }
+
+}
+
+impl From<i32> for Team {
+    fn from(n: i32) -> Self {
+        match n {
+            0 => Team::Team0,
+            1 => Team::Team1,
+            n => Team::Unknown(n),
+        }
+    }
+}
+
+impl std::str::FromStr for Team {
+    type Err = String;
+
+    /// Accepts `team1`, `1` and the API's `k_ECitadelLobbyTeam_Team1`, in any case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        let digits = lower.strip_prefix("k_ecitadellobbyteam_").unwrap_or(&lower);
+        let digits = digits.strip_prefix("team").unwrap_or(digits);
+        digits.parse::<i32>().map(Team::from).map_err(|_| format!("unknown team '{}'", s))
+    }
+}
+
+impl fmt::Display for Team {
+    /// The `team0` / `team1` form stored in `match_players.team` and `matches.winner_team`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "team{}", self.id())
+    }
+}
+
+impl Serialize for Team {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Team {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum IntOrStr {
+            I(i64),
+            S(String),
+        }
+        Ok(match IntOrStr::deserialize(de)? {
+            IntOrStr::I(i) => Team::from(i as i32),
+            IntOrStr::S(s) => s.parse().map_err(serde::de::Error::custom)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum GameMode {
+    #[default]
+    Invalid,
+    Normal,
+    OneVsOneTest,
+    Sandbox,
+    StreetBrawl,
+    Unknown(i32),
+}
+
+impl GameMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Invalid => "invalid",
+            GameMode::Normal => "normal",
+            GameMode::OneVsOneTest => "1v1 test",
+            GameMode::Sandbox => "sandbox",
+            GameMode::StreetBrawl => "street brawl",
+            GameMode::Unknown(_) => "unknown",
+        }
+    }
+}
+
+impl From<i32> for GameMode {
+    fn from(n: i32) -> Self {
+        match n {
+            0 => GameMode::Invalid,
+            1 => GameMode::Normal,
+            2 => GameMode::OneVsOneTest,
+            3 => GameMode::Sandbox,
+            4 => GameMode::StreetBrawl,
+            n => GameMode::Unknown(n),
+        }
+    }
+}
+
+impl From<GameMode> for i32 {
+    fn from(m: GameMode) -> i32 {
+        match m {
+            GameMode::Invalid => 0,
+            GameMode::Normal => 1,
+            GameMode::OneVsOneTest => 2,
+            GameMode::Sandbox => 3,
+            GameMode::StreetBrawl => 4,
+            GameMode::Unknown(n) => n,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum MatchMode {
+    #[default]
+    Invalid,
+    Unranked,
+    PrivateLobby,
+    CoopBot,
+    Ranked,
+    ServerTest,
+    Tutorial,
+    HeroLabs,
+    Unknown(i32),
+}
+
+impl MatchMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            MatchMode::Invalid => "invalid",
+            MatchMode::Unranked => "unranked",
+            MatchMode::PrivateLobby => "private",
+            MatchMode::CoopBot => "coop bots",
+            MatchMode::Ranked => "ranked",
+            MatchMode::ServerTest => "server test",
+            MatchMode::Tutorial => "tutorial",
+            MatchMode::HeroLabs => "hero labs",
+            MatchMode::Unknown(_) => "unknown",
+        }
+    }
+}
+
+impl From<i32> for MatchMode {
+    fn from(n: i32) -> Self {
+        match n {
+            0 => MatchMode::Invalid,
+            1 => MatchMode::Unranked,
+            2 => MatchMode::PrivateLobby,
+            3 => MatchMode::CoopBot,
+            4 => MatchMode::Ranked,
+            5 => MatchMode::ServerTest,
+            6 => MatchMode::Tutorial,
+            7 => MatchMode::HeroLabs,
+            n => MatchMode::Unknown(n),
+        }
+    }
+}
+
+impl std::str::FromStr for MatchMode {
+    type Err = String;
+
+    /// Accepts the display name (`ranked`, `hero labs`, `hero-labs`, ...) or the raw number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(n) = s.trim().parse::<i32>() {
+            return Ok(MatchMode::from(n));
+        }
+        let wanted = s.trim().to_ascii_lowercase().replace(['-', '_'], " ");
+        (0..=7)
+            .map(MatchMode::from)
+            .find(|m| m.name() == wanted || (wanted == "private lobby" && *m == MatchMode::PrivateLobby))
+            .ok_or_else(|| format!("unknown match mode '{}'", s))
+    }
+}
+
+impl From<MatchMode> for i32 {
+    fn from(m: MatchMode) -> i32 {
+        match m {
+            MatchMode::Invalid => 0,
+            MatchMode::Unranked => 1,
+            MatchMode::PrivateLobby => 2,
+            MatchMode::CoopBot => 3,
+            MatchMode::Ranked => 4,
+            MatchMode::ServerTest => 5,
+            MatchMode::Tutorial => 6,
+            MatchMode::HeroLabs => 7,
+            MatchMode::Unknown(n) => n,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PlayerMatchHistoryEntry {
     pub account_id: i32,
@@ -58,9 +452,9 @@ pub struct PlayerMatchHistoryEntry {
     pub hero_id: i32,
     pub hero_level: i32,
     pub start_time: i32,
-    pub game_mode: i32,
-    pub match_mode: i32,
-    pub player_team: i32,
+    pub game_mode: GameMode,
+    pub match_mode: MatchMode,
+    pub player_team: Team,
     pub player_kills: i32,
     pub player_deaths: i32,
     pub player_assists: i32,
@@ -68,13 +462,449 @@ pub struct PlayerMatchHistoryEntry {
     pub net_worth: i32,
     pub last_hits: i32,
     pub match_duration_s: i32,
-    pub match_result: i32,
+    /// The winning team
+    pub match_result: Team,
     pub objectives_mask_team0: i32,
     pub objectives_mask_team1: i32,
 }
 
+impl PlayerMatchHistoryEntry {
+    pub fn objectives(&self) -> MatchObjectives {
+        MatchObjectives {
+            team0: ObjectivesMask(self.objectives_mask_team0),
+            team1: ObjectivesMask(self.objectives_mask_team1),
+        }
+    }
+
+    /// `None` when either team is an unknown value.
+    pub fn is_victory(&self) -> Option<bool> {
+        (self.player_team.is_known() && self.match_result.is_known()).then(|| self.player_team == self.match_result)
+    }
+}
+
+// ============ Heroes ============
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Hero {
+    pub id: i32,
+    pub name: String,
+    #[serde(default)]
+    pub class_name: Option<String>,
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+}
+
+/// One stored match from a single player's point of view (`match_players` joined with `matches`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PlayerMatch {
+    pub match_id: i64,
+    /// Unix seconds
+    pub start_time: Option<i64>,
+    pub duration_s: Option<i32>,
+    pub hero_id: Option<i32>,
+    pub team: Option<Team>,
+    pub party_id: Option<i64>,
+    pub is_victory: Option<bool>,
+    pub match_mode: Option<MatchMode>,
+    pub kills: Option<i32>,
+    pub deaths: Option<i32>,
+    pub assists: Option<i32>,
+    pub networth: Option<i64>,
+    pub damage: Option<i64>,
+    pub last_hits: Option<i32>,
+    pub accuracy: Option<f64>,
+    pub average_badge: Option<i32>,
+    pub region: Option<String>,
+    pub patch_version: Option<String>,
+    /// Filled in from `match_player_scores` by `score::Scores::annotate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<PerformanceScore>,
+}
+
+/// How one player did in one match, relative to the lobby and to others on the same hero.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceScore {
+    /// 50 is an average game in its lobby; every 10 points is one standard deviation
+    pub score: f64,
+    /// Percent of stored games on the same hero that scored lower (ties count half)
+    pub percentile: f64,
+    /// Stored scored games on the hero
+    pub hero_matches: usize,
+}
+
+impl From<&PlayerMatchHistoryEntry> for PlayerMatch {
+    fn from(e: &PlayerMatchHistoryEntry) -> Self {
+        PlayerMatch {
+            match_id: e.match_id,
+            start_time: Some(e.start_time as i64),
+            duration_s: Some(e.match_duration_s),
+            hero_id: Some(e.hero_id),
+            team: Some(e.player_team),
+            party_id: None,
+            is_victory: e.is_victory(),
+            match_mode: Some(e.match_mode),
+            kills: Some(e.player_kills),
+            deaths: Some(e.player_deaths),
+            assists: Some(e.player_assists),
+            networth: Some(e.net_worth as i64),
+            damage: None,
+            last_hits: Some(e.last_hits),
+            accuracy: None,
+            average_badge: None,
+            region: None,
+            patch_version: None,
+            score: None,
+        }
+    }
+}
+
+impl PlayerMatch {
+    /// (kills + assists) / deaths, counting a deathless game as one death.
+    pub fn kda(&self) -> Option<f64> {
+        let (k, d, a) = (self.kills?, self.deaths?, self.assists?);
+        Some((k + a) as f64 / d.max(1) as f64)
+    }
+}
+
+/// Column `matches list` orders by; rows without a value for it always go last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SortKey {
+    #[default]
+    Date,
+    Duration,
+    Kills,
+    Deaths,
+    Assists,
+    Kda,
+    Networth,
+    Damage,
+    LastHits,
+    Accuracy,
+}
+
+/// One player's stored matches narrowed and ordered by the database. Empty fields don't filter.
+#[derive(Debug, Clone, Default)]
+pub struct MatchQuery {
+    pub account_id: u32,
+    pub hero_id: Option<i32>,
+    /// Unix seconds, inclusive
+    pub since: Option<i64>,
+    /// Unix seconds, exclusive
+    pub until: Option<i64>,
+    pub mode: Option<MatchMode>,
+    /// `Some(true)` keeps wins only, `Some(false)` losses only
+    pub victory: Option<bool>,
+    pub sort: SortKey,
+    pub ascending: bool,
+}
+
+// ============ Aggregates ============
+
+/// Which stored matches count towards the meta and hero synergy. Empty fields don't filter.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetaFilter {
+    /// Inclusive bounds on `matches.average_badge` (e.g. `70`-`76` for Archon)
+    pub min_badge: Option<i32>,
+    pub max_badge: Option<i32>,
+    /// Case-insensitive
+    pub region: Option<String>,
+    pub patch: Option<String>,
+}
+
+impl MetaFilter {
+    /// Narrow the badge range to one rank's tiers.
+    pub fn with_rank(mut self, rank: Option<Rank>) -> Self {
+        if let Some(r) = rank {
+            self.min_badge = Some(r.division() * 10);
+            self.max_badge = Some(r.division() * 10 + 6);
+        }
+        self
+    }
+}
+
+/// One hero's summed results over the stored matches that pass a [`MetaFilter`].
+#[derive(Debug, Clone, Default)]
+pub struct HeroTotals {
+    pub hero_id: i32,
+    pub picks: usize,
+    /// Picks with a known result
+    pub decided: usize,
+    pub wins: usize,
+    pub kills: i64,
+    pub deaths: i64,
+    pub assists: i64,
+    /// Over the picks that recorded it
+    pub avg_damage: Option<f64>,
+    pub avg_networth: Option<f64>,
+}
+
+/// Per-hero totals of the filtered matches, summed by the database.
+#[derive(Debug, Clone, Default)]
+pub struct MetaTotals {
+    /// Distinct matches with at least one stored participant
+    pub matches: usize,
+    pub heroes: Vec<HeroTotals>,
+}
+
+/// Average per-match K/D/A of one player over a set of shared matches.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KdaAverages {
+    pub kills: f64,
+    pub deaths: f64,
+    pub assists: f64,
+    /// `(kills + assists) / max(deaths, 1)` over the summed totals
+    pub kda: f64,
+}
+
+impl KdaAverages {
+    pub fn of<'a>(rows: impl Iterator<Item = &'a PlayerMatch>) -> Self {
+        let (mut n, mut k, mut d, mut a) = (0usize, 0i64, 0i64, 0i64);
+        for m in rows {
+            n += 1;
+            k += m.kills.unwrap_or_default() as i64;
+            d += m.deaths.unwrap_or_default() as i64;
+            a += m.assists.unwrap_or_default() as i64;
+        }
+        if n == 0 {
+            return Self::default();
+        }
+        let per = |v: i64| v as f64 / n as f64;
+        Self { kills: per(k), deaths: per(d), assists: per(a), kda: (k + a) as f64 / d.max(1) as f64 }
+    }
+}
+
+/// One side of a [`crate::duo::Pairing`]; a struct so JSON output gains the hero's name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PairedHero {
+    pub hero_id: i32,
+}
+
+/// The player's results and averages over a group of matches.
+#[derive(Debug, Clone, Serialize)]
+pub struct Performance {
+    pub matches: usize,
+    pub wins: usize,
+    /// Percent, over matches with a known result
+    pub win_rate: Option<f64>,
+    pub kda: KdaAverages,
+    pub avg_networth: Option<f64>,
+    pub avg_damage: Option<f64>,
+}
+
+impl Performance {
+    pub fn of(rows: &[&PlayerMatch]) -> Self {
+        let decided: Vec<bool> = rows.iter().filter_map(|m| m.is_victory).collect();
+        let wins = decided.iter().filter(|w| **w).count();
+        let avg = |f: fn(&PlayerMatch) -> Option<i64>| {
+            let vals: Vec<i64> = rows.iter().filter_map(|m| f(m)).collect();
+            (!vals.is_empty()).then(|| vals.iter().sum::<i64>() as f64 / vals.len() as f64)
+        };
+        Self {
+            matches: rows.len(),
+            wins,
+            win_rate: (!decided.is_empty()).then(|| wins as f64 / decided.len() as f64 * 100.0),
+            kda: KdaAverages::of(rows.iter().copied()),
+            avg_networth: avg(|m| m.networth),
+            avg_damage: avg(|m| m.damage),
+        }
+    }
+}
+
+/// A hero's results over the filtered rows that have a hero, a known team and a result.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeroRecord {
+    pub hero_id: i32,
+    pub matches: usize,
+    pub wins: usize,
+}
+
+/// How often `hero_id` shared a match with `other_hero_id`, and how often it won.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PairRecord {
+    pub hero_id: i32,
+    pub other_hero_id: i32,
+    pub same_team: bool,
+    pub matches: usize,
+    pub wins: usize,
+}
+
+/// Hero and hero-pair records of the filtered matches, counted by the database.
+#[derive(Debug, Clone, Default)]
+pub struct SynergyTotals {
+    /// Distinct matches with at least one usable row
+    pub matches: usize,
+    pub heroes: Vec<HeroRecord>,
+    /// Both orders of every pair
+    pub pairs: Vec<PairRecord>,
+}
+
+// ============ Stored rows ============
+
+/// Another participant of one of the player's stored matches, next to the player's own row.
+#[derive(Debug, Clone, Default)]
+pub struct CoPlayer {
+    pub match_id: i64,
+    pub start_time: Option<i64>,
+    pub account_id: u32,
+    /// `None` for stubs that were never looked up
+    pub personaname: Option<String>,
+    pub team: Option<Team>,
+    pub party_id: Option<i64>,
+    pub my_team: Option<Team>,
+    pub my_party_id: Option<i64>,
+    /// The player's result, not the co-player's
+    pub is_victory: Option<bool>,
+}
+
+impl CoPlayer {
+    pub fn same_team(&self) -> Option<bool> {
+        match (self.my_team, self.team) {
+            (Some(a), Some(b)) if a.is_known() && b.is_known() => Some(a == b),
+            _ => None,
+        }
+    }
+
+    /// Party ID 0 means solo queue.
+    pub fn same_party(&self) -> bool {
+        matches!((self.my_party_id, self.party_id), (Some(a), Some(b)) if a != 0 && a == b)
+    }
+}
+
+/// A persisted performance score, with its percentile among the hero's stored games.
+#[derive(Debug, Clone, Copy)]
+pub struct StoredScore {
+    pub match_id: i64,
+    pub account_id: u32,
+    pub score: PerformanceScore,
+}
+
+/// A row of the dashboard's recent-lookups list.
+#[derive(Debug, Clone)]
+pub struct RecentLookup {
+    pub account_id: u32,
+    pub personaname: String,
+    pub looked_up_at: DateTime<Utc>,
+}
+
+// ============ Objectives ============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectiveKind {
+    /// Tier 1 lane guardian
+    Guardian,
+    /// Tier 2 lane walker
+    Walker,
+    BaseGuardian,
+    Shrine,
+    Patron,
+    /// The Patron's final, weakened phase
+    Core,
+}
+
+impl ObjectiveKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ObjectiveKind::Guardian => "guardian",
+            ObjectiveKind::Walker => "walker",
+            ObjectiveKind::BaseGuardian => "base_guardian",
+            ObjectiveKind::Shrine => "shrine",
+            ObjectiveKind::Patron => "patron",
+            ObjectiveKind::Core => "core",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ObjectiveKind::Guardian => "Guardian",
+            ObjectiveKind::Walker => "Walker",
+            ObjectiveKind::BaseGuardian => "Base Guardian",
+            ObjectiveKind::Shrine => "Shrine",
+            ObjectiveKind::Patron => "Patron",
+            ObjectiveKind::Core => "Core",
+        }
+    }
+}
+
+/// One bit of an `objectives_mask_team*` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Objective {
+    pub kind: ObjectiveKind,
+    /// Lane 1–4 for lane objectives; shrines are numbered 1–2; `None` for the Patron
+    pub lane: Option<u8>,
+    pub bit: u8,
+}
+
+impl Objective {
+    pub fn label(self) -> String {
+        match (self.kind, self.lane) {
+            (ObjectiveKind::Shrine, Some(n)) => format!("Shrine {}", n),
+            (k, Some(n)) => format!("{} (lane {})", k.label(), n),
+            (k, None) => k.label().to_string(),
+        }
+    }
+}
+
+/// Bit layout of the objective masks, following the game's `ECitadelObjective` order.
+pub const OBJECTIVES: [Objective; 16] = [
+    Objective { kind: ObjectiveKind::Core, lane: None, bit: 0 },
+    Objective { kind: ObjectiveKind::Guardian, lane: Some(1), bit: 1 },
+    Objective { kind: ObjectiveKind::Guardian, lane: Some(2), bit: 2 },
+    Objective { kind: ObjectiveKind::Guardian, lane: Some(3), bit: 3 },
+    Objective { kind: ObjectiveKind::Guardian, lane: Some(4), bit: 4 },
+    Objective { kind: ObjectiveKind::Walker, lane: Some(1), bit: 5 },
+    Objective { kind: ObjectiveKind::Walker, lane: Some(2), bit: 6 },
+    Objective { kind: ObjectiveKind::Walker, lane: Some(3), bit: 7 },
+    Objective { kind: ObjectiveKind::Walker, lane: Some(4), bit: 8 },
+    Objective { kind: ObjectiveKind::Patron, lane: None, bit: 9 },
+    Objective { kind: ObjectiveKind::Shrine, lane: Some(1), bit: 10 },
+    Objective { kind: ObjectiveKind::Shrine, lane: Some(2), bit: 11 },
+    Objective { kind: ObjectiveKind::BaseGuardian, lane: Some(1), bit: 12 },
+    Objective { kind: ObjectiveKind::BaseGuardian, lane: Some(2), bit: 13 },
+    Objective { kind: ObjectiveKind::BaseGuardian, lane: Some(3), bit: 14 },
+    Objective { kind: ObjectiveKind::BaseGuardian, lane: Some(4), bit: 15 },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ObjectiveStatus {
+    #[serde(flatten)]
+    pub objective: Objective,
+    pub destroyed: bool,
+}
+
+/// A team's objective mask; a set bit means that objective was destroyed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ObjectivesMask(pub i32);
+
+impl ObjectivesMask {
+    pub fn destroyed(self, o: Objective) -> bool {
+        self.0 & (1 << o.bit) != 0
+    }
+
+    pub fn statuses(self) -> Vec<ObjectiveStatus> {
+        OBJECTIVES.iter().map(|&o| ObjectiveStatus { objective: o, destroyed: self.destroyed(o) }).collect()
+    }
+
+    pub fn destroyed_total(self) -> usize {
+        OBJECTIVES.iter().filter(|o| self.destroyed(**o)).count()
+    }
+}
+
+/// Decoded objective state for both teams of one match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MatchObjectives {
+    pub team0: ObjectivesMask,
+    pub team1: ObjectivesMask,
+}
+
 // ============ Matches Metadata (bulk) ============
 
+/// Two teams of six.
+pub const PLAYERS_PER_MATCH: usize = 12;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchMeta {
     pub match_id: i64,
@@ -84,7 +914,7 @@ pub struct MatchMeta {
     #[serde(default)]
     pub duration_s: Option<i32>,
     #[serde(default)]
-    pub winner_team: Option<String>,
+    pub winner_team: Option<Team>,
     #[serde(default)]
     pub average_badge: Option<i32>,
     #[serde(default)]
@@ -99,13 +929,28 @@ pub struct MatchMeta {
     pub players: Option<Vec<PlayerInMatch>>, 
 }
 
+impl MatchMeta {
+    /// Objective masks from `info` or, for matches built from player history, any
+    /// participant's `extra`.
+    pub fn objectives(&self) -> Option<MatchObjectives> {
+        let from = |v: &Value| {
+            let t0 = v.get("objectives_mask_team0")?.as_i64()?;
+            let t1 = v.get("objectives_mask_team1")?.as_i64()?;
+            Some(MatchObjectives { team0: ObjectivesMask(t0 as i32), team1: ObjectivesMask(t1 as i32) })
+        };
+        self.info.as_ref().and_then(from).or_else(|| {
+            self.players.as_ref()?.iter().find_map(|p| p.extra.as_ref().and_then(from))
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PlayerInMatch {
     pub account_id: i32,
     #[serde(default)]
     pub hero_id: Option<i32>,
     #[serde(default)]
-    pub team: Option<String>,
+    pub team: Option<Team>,
     #[serde(default)]
     pub party_id: Option<i64>,
     #[serde(default)]
@@ -136,6 +981,58 @@ pub struct PlayerInMatch {
     pub extra: Option<Value>,
 }
 
+/// Group per-player history entries by match into `MatchMeta` rows ready for ingestion.
+pub fn history_to_metas(entries: Vec<PlayerMatchHistoryEntry>) -> Vec<MatchMeta> {
+    let mut grouped: BTreeMap<i64, MatchMeta> = BTreeMap::new();
+    for e in entries {
+        let meta = grouped.entry(e.match_id).or_insert_with(|| MatchMeta {
+            match_id: e.match_id,
+            start_time: None,
+            duration_s: None,
+            winner_team: None,
+            average_badge: None,
+            region: None,
+            patch_version: None,
+            info: None,
+            players: Some(Vec::new()),
+        });
+        if meta.start_time.is_none() { meta.start_time = Some(e.start_time as i64); }
+        if meta.duration_s.is_none() { meta.duration_s = Some(e.match_duration_s); }
+        if meta.winner_team.is_none() && e.match_result.is_known() { meta.winner_team = Some(e.match_result); }
+        let objectives = e.objectives();
+        let extra = serde_json::json!({
+            "denies": e.denies,
+            "game_mode": e.game_mode,
+            "match_mode": e.match_mode,
+            "match_result": e.match_result,
+            "objectives_mask_team0": objectives.team0,
+            "objectives_mask_team1": objectives.team1,
+            "hero_level": e.hero_level
+        });
+        let pim = PlayerInMatch {
+            account_id: e.account_id,
+            hero_id: Some(e.hero_id),
+            team: Some(e.player_team),
+            party_id: None,
+            lane: None,
+            is_victory: e.is_victory(),
+            kills: Some(e.player_kills),
+            deaths: Some(e.player_deaths),
+            assists: Some(e.player_assists),
+            networth: Some(e.net_worth as i64),
+            damage: None,
+            damage_taken: None,
+            obj_damage: None,
+            last_hits: Some(e.last_hits),
+            accuracy: None,
+            crit_shot_rate: None,
+            extra: Some(extra),
+        };
+        meta.players.get_or_insert_with(Vec::new).push(pim);
+    }
+    grouped.into_values().collect()
+}
+
 // Accept either a string or an integer and convert to Some(String)
 fn opt_string_from_string_or_int<'de, D>(de: D) -> Result<Option<String>, D::Error>
 where
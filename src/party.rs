@@ -0,0 +1,132 @@
+use crate::models::{CoPlayer, Performance, PLAYERS_PER_MATCH, PlayerMatch};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// How many players queued together, the player included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartySize {
+    Solo,
+    Duo,
+    Trio,
+    Four,
+    Five,
+    FullStack,
+}
+
+impl PartySize {
+    pub fn from_count(n: usize) -> Self {
+        match n {
+            0 | 1 => PartySize::Solo,
+            2 => PartySize::Duo,
+            3 => PartySize::Trio,
+            4 => PartySize::Four,
+            5 => PartySize::Five,
+            _ => PartySize::FullStack,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PartySize::Solo => "Solo",
+            PartySize::Duo => "Duo",
+            PartySize::Trio => "Trio",
+            PartySize::Four => "4-stack",
+            PartySize::Five => "5-stack",
+            PartySize::FullStack => "Full stack",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeStats {
+    pub size: PartySize,
+    #[serde(flatten)]
+    pub performance: Performance,
+}
+
+/// The player's results in matches queued with one particular account.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberStats {
+    pub account_id: u32,
+    pub personaname: Option<String>,
+    #[serde(flatten)]
+    pub performance: Performance,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PartyReport {
+    pub account_id: u32,
+    pub matches: usize,
+    /// Matches whose party size can't be told: no party data, or a partial scoreboard
+    pub unclassified: usize,
+    /// Smallest party first
+    pub by_size: Vec<SizeStats>,
+    /// Most frequent first
+    pub members: Vec<MemberStats>,
+}
+
+/// Party size of each of `own` from the player's `party_id` and the stored co-players.
+///
+/// `party_id` 0 is solo queue. Otherwise the party is counted from the scoreboard, which must
+/// be complete or members could be missing.
+pub fn classify(own: &[PlayerMatch], co: &[CoPlayer]) -> HashMap<i64, PartySize> {
+    let mut roster: HashMap<i64, (usize, usize)> = HashMap::new();
+    for c in co {
+        let e = roster.entry(c.match_id).or_default();
+        e.0 += 1;
+        e.1 += usize::from(c.same_party());
+    }
+    own.iter()
+        .filter_map(|m| {
+            let size = match m.party_id? {
+                0 => PartySize::Solo,
+                _ => {
+                    let (others, members) = roster.get(&m.match_id).copied().unwrap_or_default();
+                    if others + 1 < PLAYERS_PER_MATCH {
+                        return None;
+                    }
+                    PartySize::from_count(members + 1)
+                }
+            };
+            Some((m.match_id, size))
+        })
+        .collect()
+}
+
+/// Win rate and performance per party size and per party member (seen at least `min_matches`
+/// times, at most `limit` members).
+pub fn analyze(account_id: u32, own: &[PlayerMatch], co: &[CoPlayer], min_matches: usize, limit: usize) -> PartyReport {
+    let sizes = classify(own, co);
+
+    let mut grouped: HashMap<PartySize, Vec<&PlayerMatch>> = HashMap::new();
+    for m in own {
+        if let Some(size) = sizes.get(&m.match_id) {
+            grouped.entry(*size).or_default().push(m);
+        }
+    }
+    let mut by_size: Vec<SizeStats> =
+        grouped.into_iter().map(|(size, rows)| SizeStats { size, performance: Performance::of(&rows) }).collect();
+    by_size.sort_by_key(|s| s.size);
+
+    let by_match: HashMap<i64, &PlayerMatch> = own.iter().map(|m| (m.match_id, m)).collect();
+    let mut with: HashMap<u32, (Option<String>, Vec<&PlayerMatch>)> = HashMap::new();
+    for c in co.iter().filter(|c| c.same_party() && c.account_id != account_id) {
+        if let Some(m) = by_match.get(&c.match_id) {
+            let e = with.entry(c.account_id).or_default();
+            if e.0.is_none() {
+                e.0 = c.personaname.clone().filter(|n| !n.is_empty());
+            }
+            e.1.push(m);
+        }
+    }
+    let mut members: Vec<MemberStats> = with
+        .into_iter()
+        .filter(|(_, (_, rows))| rows.len() >= min_matches.max(1))
+        .map(|(account_id, (personaname, rows))| MemberStats { account_id, personaname, performance: Performance::of(&rows) })
+        .collect();
+    members.sort_by(|a, b| b.performance.matches.cmp(&a.performance.matches).then(a.account_id.cmp(&b.account_id)));
+    members.truncate(limit);
+
+    PartyReport { account_id, matches: own.len(), unclassified: own.len() - sizes.len(), by_size, members }
+}
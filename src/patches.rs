@@ -0,0 +1,143 @@
+use crate::models::PlayerMatch;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Changes of one hero since the immediately preceding patch; not computed when it wasn't played there.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HeroDelta {
+    /// Percentage points
+    pub win_rate: Option<f64>,
+    /// Percentage points
+    pub pick_rate: f64,
+    pub kda: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HeroPatchStats {
+    pub hero_id: i32,
+    pub matches: usize,
+    pub wins: usize,
+    /// Percent, over matches with a known result
+    pub win_rate: Option<f64>,
+    /// Percent of the patch's matches in which the hero was picked
+    pub pick_rate: f64,
+    /// `(kills + assists) / max(deaths, 1)` over the summed totals
+    pub kda: f64,
+    /// `None` on the first patch, or when the hero wasn't played on the previous one
+    pub delta: Option<HeroDelta>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PatchStats {
+    pub patch: String,
+    /// Unix seconds of the earliest stored match on this patch
+    pub first_seen: Option<i64>,
+    /// Distinct matches
+    pub matches: usize,
+    /// Participant rows: equal to `matches` for one player
+    pub picks: usize,
+    /// Most picked first
+    pub heroes: Vec<HeroPatchStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PatchReport {
+    /// `None` when the report covers every stored player
+    pub account_id: Option<u32>,
+    /// Oldest first
+    pub patches: Vec<PatchStats>,
+    /// Rows whose match has no patch version
+    pub unknown_patch: usize,
+}
+
+#[derive(Default)]
+struct Tally {
+    matches: usize,
+    wins: usize,
+    decided: usize,
+    kills: i64,
+    deaths: i64,
+    assists: i64,
+}
+
+/// Group rows by `patch_version`, oldest patch first (by its earliest match), with per-hero
+/// win rate, pick rate and KDA and the change since the previous patch.
+pub fn analyze(account_id: Option<u32>, rows: &[PlayerMatch]) -> PatchReport {
+    let mut order: Vec<String> = Vec::new();
+    let mut first_seen: HashMap<&str, Option<i64>> = HashMap::new();
+    let mut match_ids: HashMap<&str, HashSet<i64>> = HashMap::new();
+    let mut picks: HashMap<&str, usize> = HashMap::new();
+    let mut tallies: HashMap<(&str, i32), Tally> = HashMap::new();
+    let mut unknown_patch = 0;
+
+    for r in rows {
+        let Some(patch) = r.patch_version.as_deref().filter(|p| !p.is_empty()) else {
+            unknown_patch += 1;
+            continue;
+        };
+        if !first_seen.contains_key(patch) {
+            order.push(patch.to_string());
+        }
+        let seen = first_seen.entry(patch).or_insert(r.start_time);
+        *seen = match (*seen, r.start_time) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        match_ids.entry(patch).or_default().insert(r.match_id);
+        *picks.entry(patch).or_default() += 1;
+
+        let Some(hero_id) = r.hero_id else { continue };
+        let t = tallies.entry((patch, hero_id)).or_default();
+        t.matches += 1;
+        if let Some(won) = r.is_victory {
+            t.decided += 1;
+            t.wins += usize::from(won);
+        }
+        t.kills += r.kills.unwrap_or_default() as i64;
+        t.deaths += r.deaths.unwrap_or_default() as i64;
+        t.assists += r.assists.unwrap_or_default() as i64;
+    }
+    // undated patches sort last, in the order they were met
+    order.sort_by_key(|p| first_seen[p.as_str()].unwrap_or(i64::MAX));
+
+    let mut patches: Vec<PatchStats> = Vec::with_capacity(order.len());
+    for patch in &order {
+        let matches = match_ids[patch.as_str()].len();
+        let mut heroes: Vec<HeroPatchStats> = tallies
+            .iter()
+            .filter(|((p, _), _)| p == patch)
+            .map(|(&(_, hero_id), t)| HeroPatchStats {
+                hero_id,
+                matches: t.matches,
+                wins: t.wins,
+                win_rate: (t.decided > 0).then(|| t.wins as f64 / t.decided as f64 * 100.0),
+                pick_rate: t.matches as f64 / matches as f64 * 100.0,
+                kda: (t.kills + t.assists) as f64 / t.deaths.max(1) as f64,
+                delta: None,
+            })
+            .collect();
+        heroes.sort_by(|a, b| b.matches.cmp(&a.matches).then(a.hero_id.cmp(&b.hero_id)));
+
+        if let Some(prev) = patches.last() {
+            for h in heroes.iter_mut() {
+                if let Some(p) = prev.heroes.iter().find(|p| p.hero_id == h.hero_id) {
+                    h.delta = Some(HeroDelta {
+                        win_rate: h.win_rate.zip(p.win_rate).map(|(a, b)| a - b),
+                        pick_rate: h.pick_rate - p.pick_rate,
+                        kda: h.kda - p.kda,
+                    });
+                }
+            }
+        }
+
+        patches.push(PatchStats {
+            patch: patch.clone(),
+            first_seen: first_seen[patch.as_str()],
+            matches,
+            picks: picks[patch.as_str()],
+            heroes,
+        });
+    }
+
+    PatchReport { account_id, patches, unknown_patch }
+}
@@ -0,0 +1,114 @@
+use reqwest::header::HeaderMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+/// Proactive token-bucket limiter for outgoing API requests.
+///
+/// Clones share the same bucket, so every task using a cloned `DeadlockClient`
+/// draws from one budget. The bucket also tightens itself from the server's
+/// `X-RateLimit-Remaining` / `X-RateLimit-Reset` headers and from `Retry-After`.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    inner: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+    blocked_until: Option<Instant>,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    fn block_for(&mut self, wait: Duration) {
+        let until = Instant::now() + wait;
+        if self.blocked_until.is_none_or(|b| b < until) {
+            self.blocked_until = Some(until);
+        }
+    }
+}
+
+impl RateLimiter {
+    /// `requests_per_sec` steady rate with up to `burst` requests allowed back to back.
+    pub fn new(requests_per_sec: f64, burst: u32) -> Self {
+        let capacity = burst.max(1) as f64;
+        let bucket = Bucket {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: requests_per_sec.max(0.01),
+            last_refill: Instant::now(),
+            blocked_until: None,
+        };
+        Self { inner: Arc::new(Mutex::new(bucket)) }
+    }
+
+    /// Wait until a request may be sent, then consume one token.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut b = self.inner.lock().unwrap();
+                let now = Instant::now();
+                b.refill(now);
+                match b.blocked_until {
+                    Some(until) if until > now => until - now,
+                    _ => {
+                        b.blocked_until = None;
+                        if b.tokens >= 1.0 {
+                            b.tokens -= 1.0;
+                            return;
+                        }
+                        Duration::from_secs_f64((1.0 - b.tokens) / b.refill_per_sec)
+                    }
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Hold back every request for `wait`, e.g. after a 429 with `Retry-After`.
+    pub fn pause_for(&self, wait: Duration) {
+        self.inner.lock().unwrap().block_for(wait);
+    }
+
+    /// Learn from `X-RateLimit-Remaining` / `X-RateLimit-Reset` on any response.
+    pub fn observe_headers(&self, headers: &HeaderMap) {
+        let num = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).and_then(|s| s.trim().parse::<f64>().ok());
+        let remaining = num("x-ratelimit-remaining");
+        let reset = num("x-ratelimit-reset").map(reset_to_duration);
+
+        let mut b = self.inner.lock().unwrap();
+        if let Some(rem) = remaining {
+            b.refill(Instant::now());
+            b.tokens = b.tokens.min(rem.max(0.0));
+            if rem < 1.0 {
+                b.block_for(reset.unwrap_or(Duration::from_secs(1)));
+            }
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(4.0, 4)
+    }
+}
+
+// servers send either seconds-until-reset or an absolute unix timestamp
+fn reset_to_duration(v: f64) -> Duration {
+    const EPOCH_THRESHOLD: f64 = 1_000_000_000.0;
+    if v >= EPOCH_THRESHOLD {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0);
+        Duration::from_secs_f64((v - now).max(0.0))
+    } else {
+        Duration::from_secs_f64(v.max(0.0))
+    }
+}
@@ -0,0 +1,117 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Query parameters that must never be written to fixtures.
+const SECRET_PARAMS: &[&str] = &["key", "api_key"];
+
+/// One recorded HTTP GET and its response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    /// Request URL with secrets redacted
+    pub url: String,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+impl Exchange {
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn header_map(&self) -> HeaderMap {
+        let mut out = HeaderMap::new();
+        for (k, v) in &self.headers {
+            if let (Ok(k), Ok(v)) = (HeaderName::try_from(k.as_str()), HeaderValue::from_str(v)) {
+                out.insert(k, v);
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+/// Writes every exchange to a fixture directory (`--record`) or serves them back
+/// from it without touching the network (`--replay`).
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    dir: PathBuf,
+    mode: Mode,
+}
+
+impl Recorder {
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), mode: Mode::Record }
+    }
+
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), mode: Mode::Replay }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.mode == Mode::Replay
+    }
+
+    /// Build an exchange from a live response's parts; secrets are redacted from the URL.
+    pub fn exchange(url: &Url, status: StatusCode, headers: &HeaderMap, body: String) -> Exchange {
+        let headers = headers
+            .iter()
+            .filter_map(|(k, v)| v.to_str().ok().map(|v| (k.as_str().to_string(), v.to_string())))
+            .collect();
+        Exchange { url: redact(url).to_string(), status: status.as_u16(), headers, body }
+    }
+
+    /// Persist an exchange. A later exchange for the same URL replaces the earlier
+    /// one, so a 429 followed by a successful retry replays as the success.
+    pub async fn save(&self, exchange: &Exchange) -> anyhow::Result<()> {
+        if self.mode != Mode::Record {
+            return Ok(());
+        }
+        let url = Url::parse(&exchange.url)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let raw = serde_json::to_vec_pretty(exchange)?;
+        tokio::fs::write(self.path_for(&url), raw).await?;
+        Ok(())
+    }
+
+    pub async fn load(&self, url: &Url) -> Option<Exchange> {
+        let url = redact(url);
+        let raw = tokio::fs::read(self.path_for(&url)).await.ok()?;
+        let ex: Exchange = serde_json::from_slice(&raw).ok()?;
+        (ex.url == url.as_str()).then_some(ex)
+    }
+
+    // readable prefix for humans browsing fixtures, hash for uniqueness
+    fn path_for(&self, redacted: &Url) -> PathBuf {
+        let slug: String = format!("{}{}", redacted.host_str().unwrap_or(""), redacted.path())
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let hash = crate::cache::fnv1a64(redacted.as_str().as_bytes());
+        self.dir.join(format!("{}-{:016x}.json", slug.trim_matches('_'), hash))
+    }
+}
+
+pub fn redact(url: &Url) -> Url {
+    let mut out = url.clone();
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if SECRET_PARAMS.contains(&k.as_ref()) { "REDACTED".to_string() } else { v.into_owned() };
+                (k.into_owned(), v)
+            })
+            .collect();
+        out.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    out
+}
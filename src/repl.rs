@@ -0,0 +1,402 @@
+use crate::compare::Comparison;
+use crate::deadlock::DeadlockApi;
+use crate::heroes::HeroCatalog;
+use crate::history;
+use crate::models::CombinedPayload;
+use crate::steam;
+use crate::tui::{self, Env, PlayerView};
+use crate::ui::{self, ChartStyle};
+use anyhow::{Context as _, Result};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub const COMMANDS: [&str; 9] = ["lookup", "heroes", "matches", "mmr", "compare", "history", "help", "quit", "exit"];
+const DEFAULT_MATCHES: usize = 10;
+
+const HELP: &str = "\
+Commands:
+  lookup <player>             profile and totals (fetched fresh); a bare <player> works too
+  heroes [player]             detailed hero stats
+  matches [player] [-n N]     latest N matches (default 10)
+  mmr [player]                rank timeline
+  compare <player> [player…]  side by side; with one player, compares against the current one
+  history                     players looked up this session
+  help · quit
+
+<player> is a SteamID64, vanity name, profile URL, [U:1:x], account ID, or a name seen before
+(Tab completes). Quote names that contain spaces. Without [player] the last lookup is used.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplCommand {
+    Lookup(String),
+    Heroes(Option<String>),
+    Matches { player: Option<String>, limit: usize },
+    Mmr(Option<String>),
+    Compare(Vec<String>),
+    History,
+    Help,
+    Quit,
+}
+
+/// Split a line into words; single or double quotes group words with spaces.
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut cur: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => cur.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                cur.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(cur.take()),
+            (None, c) => cur.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("unterminated quote".into());
+    }
+    words.extend(cur);
+    Ok(words)
+}
+
+/// Parse one input line; `None` for a blank line. Anything that isn't a command is a lookup.
+pub fn parse(line: &str) -> Result<Option<ReplCommand>, String> {
+    let words = split_args(line)?;
+    let Some((first, rest)) = words.split_first() else { return Ok(None) };
+    let one = |what: &str| -> Result<Option<String>, String> {
+        match rest {
+            [] => Ok(None),
+            [p] => Ok(Some(p.clone())),
+            _ => Err(format!("`{}` takes at most one player", what)),
+        }
+    };
+
+    let cmd = match first.to_lowercase().as_str() {
+        "lookup" | "l" => match rest {
+            [p] => ReplCommand::Lookup(p.clone()),
+            _ => return Err("usage: lookup <player>".into()),
+        },
+        "heroes" => ReplCommand::Heroes(one("heroes")?),
+        "mmr" => ReplCommand::Mmr(one("mmr")?),
+        "matches" => {
+            let mut player = None;
+            let mut limit = DEFAULT_MATCHES;
+            let mut it = rest.iter();
+            while let Some(w) = it.next() {
+                if w == "-n" {
+                    limit = it
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .filter(|&n| n > 0)
+                        .ok_or("`-n` needs a positive number")?;
+                } else if player.is_none() {
+                    player = Some(w.clone());
+                } else {
+                    return Err("usage: matches [player] [-n N]".into());
+                }
+            }
+            ReplCommand::Matches { player, limit }
+        }
+        "compare" | "vs" => {
+            if rest.is_empty() {
+                return Err("usage: compare <player> [player…]".into());
+            }
+            ReplCommand::Compare(rest.to_vec())
+        }
+        "history" => ReplCommand::History,
+        "help" | "?" => ReplCommand::Help,
+        "quit" | "exit" | "q" => ReplCommand::Quit,
+        _ if rest.is_empty() => ReplCommand::Lookup(first.clone()),
+        _ => return Err(format!("unknown command `{}` (try `help`)", first)),
+    };
+    Ok(Some(cmd))
+}
+
+/// Tab completion over command names and previously seen player names and IDs.
+#[derive(Debug, Default)]
+pub struct ReplHelper {
+    names: Vec<String>,
+}
+
+impl ReplHelper {
+    pub fn new(names: impl IntoIterator<Item = String>) -> Self {
+        let mut helper = Self::default();
+        for n in names {
+            helper.add_name(n);
+        }
+        helper
+    }
+
+    pub fn add_name(&mut self, name: String) {
+        if !name.trim().is_empty() && !self.names.contains(&name) {
+            self.names.push(name);
+        }
+    }
+
+    /// Start of the word under the cursor and its candidates; names with spaces come back quoted.
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let before = &line[..pos];
+        let mut start = 0;
+        let mut quote: Option<char> = None;
+        let mut words_before = 0;
+        for (i, c) in before.char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => {
+                    quote = Some(c);
+                }
+                (None, c) if c.is_whitespace() => {
+                    if i > start {
+                        words_before += 1;
+                    }
+                    start = i + c.len_utf8();
+                }
+                _ => {}
+            }
+        }
+        let prefix = before[start..].trim_start_matches(['"', '\'']).to_lowercase();
+
+        // a bare player is a lookup, so the first word offers names after the commands
+        let mut found: Vec<String> = Vec::new();
+        if words_before == 0 {
+            found.extend(COMMANDS.iter().filter(|c| c.starts_with(&prefix)).map(|c| c.to_string()));
+        }
+        found.extend(
+            self.names
+                .iter()
+                .filter(|n| n.to_lowercase().starts_with(&prefix))
+                .map(|n| if n.contains(char::is_whitespace) { format!("\"{}\"", n) } else { n.clone() }),
+        );
+        (start, found)
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, found) = self.candidates(line, pos);
+        Ok((start, found.into_iter().map(|s| Pair { display: s.clone(), replacement: s }).collect()))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Players seen by this session: what was loaded, in which order, and which names map to whom.
+#[derive(Default)]
+pub struct Session {
+    /// Account IDs in lookup order, without repeats
+    pub history: Vec<u32>,
+    pub current: Option<u32>,
+    views: HashMap<u32, PlayerView>,
+    /// Lower-cased persona name -> account ID, from the database and this session's lookups
+    names: HashMap<String, u32>,
+}
+
+impl Session {
+    pub fn new(known: impl IntoIterator<Item = (u32, String)>) -> Self {
+        let mut s = Self::default();
+        for (id, name) in known {
+            s.names.entry(name.to_lowercase()).or_insert(id);
+        }
+        s
+    }
+
+    /// A known persona name becomes its account ID; anything else is left for Steam resolution.
+    pub fn resolve_name(&self, query: &str) -> String {
+        match self.names.get(&query.to_lowercase()) {
+            Some(id) => id.to_string(),
+            None => query.to_string(),
+        }
+    }
+
+    pub fn remember(&mut self, view: PlayerView) -> &PlayerView {
+        let id = view.payload.account_id;
+        self.names.insert(view.payload.profile.personaname.to_lowercase(), id);
+        self.history.retain(|&h| h != id);
+        self.history.push(id);
+        self.current = Some(id);
+        self.views.entry(id).insert_entry(view).into_mut()
+    }
+
+    pub fn view(&self, account_id: u32) -> Option<&PlayerView> {
+        self.views.get(&account_id)
+    }
+
+    /// Loaded players, oldest lookup first.
+    pub fn players(&self) -> Vec<&CombinedPayload> {
+        self.history.iter().filter_map(|id| self.views.get(id)).map(|v| &v.payload).collect()
+    }
+}
+
+/// How command results are printed.
+pub struct Output<'a> {
+    pub catalog: &'a HeroCatalog,
+    pub style: ChartStyle,
+    pub json: bool,
+}
+
+async fn fetch<A: DeadlockApi>(env: &Env<'_, A>, session: &mut Session, query: &str) -> Result<u32> {
+    let view = tui::load_view(env, &session.resolve_name(query)).await?;
+    Ok(session.remember(view).payload.account_id)
+}
+
+/// The player a command is about: the named one (reusing this session's copy) or the current one.
+async fn target<A: DeadlockApi>(env: &Env<'_, A>, session: &mut Session, query: Option<&str>) -> Result<u32> {
+    let Some(query) = query else {
+        return session.current.context("No player selected yet; run `lookup <player>` first");
+    };
+    let id = steam::resolve_account_id(&session.resolve_name(query), env.http, env.recorder).await?;
+    if session.view(id).is_some() {
+        session.current = Some(id);
+        return Ok(id);
+    }
+    fetch(env, session, &id.to_string()).await
+}
+
+/// Run one command. Returns `false` once the user asked to quit.
+pub async fn execute<A: DeadlockApi>(env: &Env<'_, A>, session: &mut Session, cmd: ReplCommand, out: &Output<'_>) -> Result<bool> {
+    match cmd {
+        ReplCommand::Quit => return Ok(false),
+        ReplCommand::Help => println!("{}", HELP),
+        ReplCommand::Lookup(q) => {
+            let id = fetch(env, session, &q).await?;
+            let p = &session.view(id).expect("just loaded").payload;
+            if out.json {
+                ui::print_json(p, out.catalog)?;
+            } else {
+                ui::print_profile_table(&p.profile, &p.steamid64, p.account_id, p.latest_mmr.as_ref());
+                ui::print_stats_table(&p.hero_stats);
+            }
+        }
+        ReplCommand::Heroes(q) => {
+            let id = target(env, session, q.as_deref()).await?;
+            let p = &session.view(id).expect("loaded").payload;
+            if out.json {
+                ui::print_json(&p.hero_stats, out.catalog)?;
+            } else {
+                ui::print_detailed_hero_stats(&p.hero_stats, out.catalog, out.style);
+            }
+        }
+        ReplCommand::Matches { player, limit } => {
+            let id = target(env, session, player.as_deref()).await?;
+            let v = session.view(id).expect("loaded");
+            let page = history::paginate(v.matches.clone(), 1, limit);
+            if out.json {
+                ui::print_json(&page, out.catalog)?;
+            } else if page.total == 0 {
+                println!("No matches known for {}.", v.payload.profile.personaname);
+            } else {
+                ui::print_match_list(&page, out.catalog, out.style);
+            }
+        }
+        ReplCommand::Mmr(q) => {
+            let id = target(env, session, q.as_deref()).await?;
+            let v = session.view(id).expect("loaded");
+            match &v.mmr {
+                Some(report) if out.json => ui::print_json(report, out.catalog)?,
+                Some(report) => ui::print_mmr_report(report, 20, out.style),
+                None => println!("No MMR history for {}.", v.payload.profile.personaname),
+            }
+        }
+        ReplCommand::Compare(queries) => {
+            let mut ids: Vec<u32> = Vec::new();
+            if queries.len() == 1 {
+                ids.extend(session.current);
+            }
+            for q in &queries {
+                let id = target(env, session, Some(q)).await?;
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            if ids.len() < 2 {
+                anyhow::bail!("Need two different players to compare");
+            }
+            let players: Vec<&CombinedPayload> = ids.iter().filter_map(|id| session.view(*id)).map(|v| &v.payload).collect();
+            let comparison = Comparison::new(&players);
+            if out.json {
+                ui::print_json(&comparison, out.catalog)?;
+            } else {
+                ui::print_comparison(&comparison, out.catalog, out.style);
+            }
+        }
+        ReplCommand::History => {
+            let players = session.players();
+            if out.json {
+                ui::print_json(&players, out.catalog)?;
+            } else if players.is_empty() {
+                println!("Nobody looked up yet.");
+            } else {
+                ui::print_session_history(&players, session.current);
+            }
+        }
+    }
+    Ok(true)
+}
+
+fn history_file() -> PathBuf {
+    crate::cache::ResponseCache::default_dir().join("repl_history")
+}
+
+/// Read commands until `quit` or EOF. `known` seeds completion and name lookups.
+pub async fn run<A: DeadlockApi>(env: &Env<'_, A>, known: Vec<(u32, String)>, out: &Output<'_>) -> Result<()> {
+    let mut rl: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+    rl.set_helper(Some(ReplHelper::new(known.iter().flat_map(|(id, name)| [name.clone(), id.to_string()]))));
+    let path = history_file();
+    let _ = rl.load_history(&path);
+    let mut session = Session::new(known);
+
+    println!("Deadlock stats shell. Type `help` for commands, `quit` to leave.");
+    loop {
+        let line = match rl.readline("deadlock> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let cmd = match parse(&line) {
+            Ok(Some(cmd)) => cmd,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("{}", e);
+                continue;
+            }
+        };
+        let _ = rl.add_history_entry(line.as_str());
+        match execute(env, &mut session, cmd, out).await {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => eprintln!("Error: {:#}", e),
+        }
+        if let (Some(helper), Some(v)) = (rl.helper_mut(), session.current.and_then(|id| session.view(id))) {
+            helper.add_name(v.payload.profile.personaname.clone());
+            helper.add_name(v.payload.account_id.to_string());
+        }
+    }
+
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = rl.save_history(&path);
+    Ok(())
+}
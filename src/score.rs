@@ -0,0 +1,26 @@
+use crate::models::{PerformanceScore, PlayerMatch, StoredScore};
+use std::collections::HashMap;
+
+/// Performance scores of stored rows by match and account. The scores themselves are computed
+/// per lobby when a match is ingested (see `score_matches` in the migrations).
+#[derive(Debug, Clone, Default)]
+pub struct Scores {
+    by_row: HashMap<(i64, u32), PerformanceScore>,
+}
+
+impl Scores {
+    pub fn new(rows: impl IntoIterator<Item = StoredScore>) -> Self {
+        Self { by_row: rows.into_iter().map(|r| ((r.match_id, r.account_id), r.score)).collect() }
+    }
+
+    pub fn get(&self, match_id: i64, account_id: u32) -> Option<PerformanceScore> {
+        self.by_row.get(&(match_id, account_id)).copied()
+    }
+
+    /// Set `score` on one account's rows.
+    pub fn annotate(&self, account_id: u32, rows: &mut [PlayerMatch]) {
+        for r in rows {
+            r.score = self.get(r.match_id, account_id);
+        }
+    }
+}
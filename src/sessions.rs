@@ -0,0 +1,258 @@
+use crate::models::{Performance, PlayerMatch};
+use chrono::{DateTime, Datelike, FixedOffset, Timelike};
+use serde::Serialize;
+
+/// Positions in a session at or beyond this one share the last bucket.
+pub const POSITION_CAP: usize = 6;
+/// Consecutive in-session losses at or beyond this share the last tilt bucket.
+pub const TILT_CAP: usize = 3;
+/// Width of the time-of-day windows, in hours.
+pub const HOURS_PER_WINDOW: u32 = 4;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Matches played with no gap longer than the report's `gap_minutes` between them.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaySession {
+    /// Unix seconds of the first match's start
+    pub start: i64,
+    /// Unix seconds of the last match's end (its start when the duration is unknown)
+    pub end: i64,
+    #[serde(flatten)]
+    pub performance: Performance,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Streak {
+    pub won: bool,
+    pub length: usize,
+    /// Unix seconds of the first and last match
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Streaks {
+    pub longest_win: Option<Streak>,
+    pub longest_loss: Option<Streak>,
+    /// Ending with the latest decided match
+    pub current: Option<Streak>,
+}
+
+/// The player's next-match results after `after_losses` straight losses in the same session
+/// (`TILT_CAP` or more for the last bucket).
+#[derive(Debug, Clone, Serialize)]
+pub struct TiltStats {
+    pub after_losses: usize,
+    #[serde(flatten)]
+    pub performance: Performance,
+}
+
+/// Results of the `position`-th match of a session (`POSITION_CAP` or later for the last bucket).
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionStats {
+    pub position: usize,
+    #[serde(flatten)]
+    pub performance: Performance,
+}
+
+/// First half vs second half of every long session.
+#[derive(Debug, Clone, Serialize)]
+pub struct Decay {
+    pub sessions: usize,
+    pub first_half: Performance,
+    pub second_half: Performance,
+}
+
+/// A time-of-day or day-of-week bucket, in the report's UTC offset.
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowStats {
+    /// `"08-12"` or `"Mon"`
+    pub label: String,
+    #[serde(flatten)]
+    pub performance: Performance,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionsReport {
+    pub account_id: u32,
+    /// Matches with a start time; undated ones are left out
+    pub matches: usize,
+    pub gap_minutes: i64,
+    pub utc_offset_minutes: i32,
+    /// Newest first
+    pub sessions: Vec<PlaySession>,
+    pub streaks: Streaks,
+    /// Fewest prior losses first
+    pub tilt: Vec<TiltStats>,
+    pub by_position: Vec<PositionStats>,
+    /// `None` when no session has at least `long_session` matches
+    pub decay: Option<Decay>,
+    /// Earliest window first; empty windows are left out
+    pub by_time: Vec<WindowStats>,
+    /// Monday first; empty days are left out
+    pub by_weekday: Vec<WindowStats>,
+    /// Labels of the highest and lowest win rate windows with at least `min_matches` matches;
+    /// no worst unless two windows qualify
+    pub best_time: Option<String>,
+    pub worst_time: Option<String>,
+    pub best_weekday: Option<String>,
+    pub worst_weekday: Option<String>,
+}
+
+/// Split chronologically ordered matches wherever the idle time after a match's end exceeds
+/// `gap_minutes`.
+pub fn split_sessions<'a>(ordered: &[&'a PlayerMatch], gap_minutes: i64) -> Vec<Vec<&'a PlayerMatch>> {
+    let mut sessions: Vec<Vec<&PlayerMatch>> = Vec::new();
+    let mut last_end = i64::MIN;
+    for &m in ordered {
+        let start = m.start_time.unwrap_or_default();
+        if sessions.is_empty() || start - last_end > gap_minutes * 60 {
+            sessions.push(Vec::new());
+        }
+        sessions.last_mut().expect("pushed above").push(m);
+        last_end = last_end.max(start + i64::from(m.duration_s.unwrap_or_default()));
+    }
+    sessions
+}
+
+fn streaks(ordered: &[&PlayerMatch]) -> Streaks {
+    let mut out = Streaks::default();
+    let mut run: Option<Streak> = None;
+    for m in ordered {
+        let (Some(won), Some(t)) = (m.is_victory, m.start_time) else { continue };
+        match run.as_mut() {
+            Some(s) if s.won == won => {
+                s.length += 1;
+                s.end = t;
+            }
+            _ => run = Some(Streak { won, length: 1, start: t, end: t }),
+        }
+        let s = run.as_ref().expect("set above");
+        let best = if won { &mut out.longest_win } else { &mut out.longest_loss };
+        if best.as_ref().is_none_or(|b| s.length > b.length) {
+            *best = Some(s.clone());
+        }
+    }
+    out.current = run;
+    out
+}
+
+fn best_and_worst(windows: &[WindowStats], min_matches: usize) -> (Option<String>, Option<String>) {
+    let mut eligible: Vec<(&str, f64)> = windows
+        .iter()
+        .filter(|w| w.performance.matches >= min_matches.max(1))
+        .filter_map(|w| Some((w.label.as_str(), w.performance.win_rate?)))
+        .collect();
+    eligible.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let worst = eligible.last().filter(|_| eligible.len() > 1).map(|w| w.0.to_string());
+    (eligible.first().map(|w| w.0.to_string()), worst)
+}
+
+/// Sessions, streaks, in-session tilt and decay, and time-of-day / day-of-week windows.
+///
+/// A session is long with at least `long_session` matches; best/worst windows need
+/// `min_matches`. Hours and days are taken at `offset`.
+pub fn analyze(
+    account_id: u32,
+    rows: &[PlayerMatch],
+    gap_minutes: i64,
+    long_session: usize,
+    min_matches: usize,
+    offset: FixedOffset,
+) -> SessionsReport {
+    let mut ordered: Vec<&PlayerMatch> = rows.iter().filter(|m| m.start_time.is_some()).collect();
+    ordered.sort_by_key(|m| (m.start_time, m.match_id));
+    let split = split_sessions(&ordered, gap_minutes);
+
+    let mut tilt: Vec<Vec<&PlayerMatch>> = vec![Vec::new(); TILT_CAP + 1];
+    let mut positions: Vec<Vec<&PlayerMatch>> = vec![Vec::new(); POSITION_CAP];
+    let (mut first_half, mut second_half, mut long) = (Vec::new(), Vec::new(), 0);
+    for session in &split {
+        let mut losses = 0;
+        for (i, &m) in session.iter().enumerate() {
+            positions[i.min(POSITION_CAP - 1)].push(m);
+            if i > 0 {
+                tilt[losses.min(TILT_CAP)].push(m);
+            }
+            match m.is_victory {
+                Some(false) => losses += 1,
+                Some(true) => losses = 0,
+                None => {}
+            }
+        }
+        if session.len() >= long_session.max(2) {
+            long += 1;
+            let (a, b) = session.split_at(session.len() / 2);
+            first_half.extend_from_slice(a);
+            second_half.extend_from_slice(b);
+        }
+    }
+
+    let mut time_windows: Vec<Vec<&PlayerMatch>> = vec![Vec::new(); (24 / HOURS_PER_WINDOW) as usize];
+    let mut weekdays: Vec<Vec<&PlayerMatch>> = vec![Vec::new(); 7];
+    for &m in &ordered {
+        if let Some(t) = m.start_time.and_then(|t| DateTime::from_timestamp(t, 0)) {
+            let local = t.with_timezone(&offset);
+            time_windows[(local.hour() / HOURS_PER_WINDOW) as usize].push(m);
+            weekdays[local.weekday().num_days_from_monday() as usize].push(m);
+        }
+    }
+    let windows = |groups: Vec<Vec<&PlayerMatch>>, label: &dyn Fn(usize) -> String| -> Vec<WindowStats> {
+        groups
+            .into_iter()
+            .enumerate()
+            .filter(|(_, g)| !g.is_empty())
+            .map(|(i, g)| WindowStats { label: label(i), performance: Performance::of(&g) })
+            .collect()
+    };
+    let by_time = windows(time_windows, &|i| {
+        let from = i as u32 * HOURS_PER_WINDOW;
+        format!("{:02}-{:02}", from, from + HOURS_PER_WINDOW)
+    });
+    let by_weekday = windows(weekdays, &|i| WEEKDAYS[i].to_string());
+    let (best_time, worst_time) = best_and_worst(&by_time, min_matches);
+    let (best_weekday, worst_weekday) = best_and_worst(&by_weekday, min_matches);
+
+    let mut sessions: Vec<PlaySession> = split
+        .iter()
+        .map(|s| PlaySession {
+            start: s[0].start_time.unwrap_or_default(),
+            end: s.iter().map(|m| m.start_time.unwrap_or_default() + i64::from(m.duration_s.unwrap_or_default())).max().unwrap_or_default(),
+            performance: Performance::of(s),
+        })
+        .collect();
+    sessions.reverse();
+
+    SessionsReport {
+        account_id,
+        matches: ordered.len(),
+        gap_minutes,
+        utc_offset_minutes: offset.local_minus_utc() / 60,
+        sessions,
+        streaks: streaks(&ordered),
+        tilt: tilt
+            .into_iter()
+            .enumerate()
+            .filter(|(_, g)| !g.is_empty())
+            .map(|(after_losses, g)| TiltStats { after_losses, performance: Performance::of(&g) })
+            .collect(),
+        by_position: positions
+            .into_iter()
+            .enumerate()
+            .filter(|(_, g)| !g.is_empty())
+            .map(|(i, g)| PositionStats { position: i + 1, performance: Performance::of(&g) })
+            .collect(),
+        decay: (long > 0).then(|| Decay {
+            sessions: long,
+            first_half: Performance::of(&first_half),
+            second_half: Performance::of(&second_half),
+        }),
+        by_time,
+        by_weekday,
+        best_time,
+        worst_time,
+        best_weekday,
+        worst_weekday,
+    }
+}
@@ -1,3 +1,4 @@
+use crate::recorder::Recorder;
 use anyhow::Result;
 use regex::Regex;
 use reqwest::Client;
@@ -20,6 +21,12 @@ pub enum SteamError {
 const STEAMID64_MIN: u64 = 76561197960265728; // steamID64 offset
 
 pub async fn to_steamid64_with_client(input: &str, http: &Client) -> Result<String, SteamError> {
+    to_steamid64_recorded(input, http, None).await
+}
+
+/// Like `to_steamid64_with_client`, but vanity lookups go through `recorder`
+/// (`--record` / `--replay`) when one is given.
+pub async fn to_steamid64_recorded(input: &str, http: &Client, recorder: Option<&Recorder>) -> Result<String, SteamError> {
     let input = input.trim();
 
     if is_steamid64(input) {
@@ -45,8 +52,8 @@ pub async fn to_steamid64_with_client(input: &str, http: &Client) -> Result<Stri
                 return Ok(id.to_string());
             }
             (Some("id"), Some(name)) => {
-                let key = std::env::var("STEAM_WEB_API_KEY").map_err(|_| SteamError::MissingSteamWebApiKey)?;
-                let sid = resolve_vanity(name, &key, http).await?;
+                let key = steam_key(recorder)?;
+                let sid = resolve_vanity(name, &key, http, recorder).await?;
                 validate_steamid64(&sid)?;
                 return Ok(sid);
             }
@@ -58,12 +65,22 @@ pub async fn to_steamid64_with_client(input: &str, http: &Client) -> Result<Stri
     if input.contains('/') || input.contains(':') || input.starts_with("http") {
         return Err(SteamError::InvalidCommunityUrl);
     }
-    let key = std::env::var("STEAM_WEB_API_KEY").map_err(|_| SteamError::MissingSteamWebApiKey)?;
-    let sid = resolve_vanity(input, &key, http).await?;
+    let key = steam_key(recorder)?;
+    let sid = resolve_vanity(input, &key, http, recorder).await?;
     validate_steamid64(&sid)?;
     Ok(sid)
 }
 
+/// Account ID from any identifier a user may type: SteamID3, Steam2, a raw account ID,
+/// a SteamID64, a community URL or a vanity name.
+pub async fn resolve_account_id(input: &str, http: &Client, recorder: Option<&Recorder>) -> Result<u32, SteamError> {
+    if let Ok(acc) = parse_steamid3_or_account_id(input) {
+        return Ok(acc);
+    }
+    let sid = to_steamid64_recorded(input, http, recorder).await?;
+    steamid64_to_account_id(&sid)
+}
+
 #[allow(dead_code)]
 pub async fn to_steamid64(input: &str) -> Result<String, SteamError> {
     let http = reqwest::Client::builder()
@@ -74,6 +91,15 @@ pub async fn to_steamid64(input: &str) -> Result<String, SteamError> {
     to_steamid64_with_client(input, &http).await
 }
 
+// replayed fixtures have the key redacted, so none is needed to serve them
+fn steam_key(recorder: Option<&Recorder>) -> Result<String, SteamError> {
+    match std::env::var("STEAM_WEB_API_KEY") {
+        Ok(k) => Ok(k),
+        Err(_) if recorder.is_some_and(Recorder::is_replay) => Ok(String::new()),
+        Err(_) => Err(SteamError::MissingSteamWebApiKey),
+    }
+}
+
 fn host_is_steamcommunity(url: &Url) -> bool {
     match url.host_str() {
         Some(h) => h.eq_ignore_ascii_case("steamcommunity.com") || h.eq_ignore_ascii_case("www.steamcommunity.com"),
@@ -157,7 +183,7 @@ struct VanityResponse {
     steamid: Option<String>,
 }
 
-async fn resolve_vanity(vanity: impl AsRef<str>, key: &str, http: &Client) -> Result<String, SteamError> {
+async fn resolve_vanity(vanity: impl AsRef<str>, key: &str, http: &Client, recorder: Option<&Recorder>) -> Result<String, SteamError> {
     let vanity = vanity.as_ref();
     let base = std::env::var("STEAM_WEB_API_BASE").unwrap_or_else(|_| "https://api.steampowered.com".to_string());
     let endpoint = format!("{}/ISteamUser/ResolveVanityURL/v1/", base.trim_end_matches('/'));
@@ -166,11 +192,29 @@ async fn resolve_vanity(vanity: impl AsRef<str>, key: &str, http: &Client) -> Re
         &[("key", key), ("vanityurl", vanity)],
     ).map_err(|e| SteamError::Other(e.into()))?;
 
-    let resp = http.get(url).send().await.map_err(|e| SteamError::Other(e.into()))?;
-    if !resp.status().is_success() {
-        return Err(SteamError::Other(anyhow::anyhow!("Steam vanity resolve failed: {}", resp.status())));
+    let ex = match recorder {
+        Some(r) if r.is_replay() => r
+            .load(&url)
+            .await
+            .ok_or_else(|| SteamError::Other(anyhow::anyhow!("No recorded response (--replay) for vanity {}", vanity)))?,
+        _ => {
+            let resp = http.get(url.clone()).send().await.map_err(|e| SteamError::Other(e.into()))?;
+            let status = resp.status();
+            let headers = resp.headers().clone();
+            let body = resp.text().await.map_err(|e| SteamError::Other(e.into()))?;
+            let ex = Recorder::exchange(&url, status, &headers, body);
+            if let Some(r) = recorder
+                && let Err(e) = r.save(&ex).await
+            {
+                eprintln!("Warning: failed to record {}: {}", ex.url, e);
+            }
+            ex
+        }
+    };
+    if !ex.status().is_success() {
+        return Err(SteamError::Other(anyhow::anyhow!("Steam vanity resolve failed: {}", ex.status())));
     }
-    let wrap: VanityResponseWrap = resp.json().await.map_err(|e| SteamError::Other(e.into()))?;
+    let wrap: VanityResponseWrap = serde_json::from_str(&ex.body).map_err(|e| SteamError::Other(e.into()))?;
     match wrap.response.success {
         1 => Ok(wrap.response.steamid.unwrap()),
         _ => Err(SteamError::Other(anyhow::anyhow!(wrap.response.message.unwrap_or_else(|| "Vanity not found".to_string())))),
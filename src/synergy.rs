@@ -0,0 +1,156 @@
+use crate::models::{HeroRecord, PairRecord, PairedHero, SynergyTotals};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Which matrix to export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum MatrixKind {
+    /// Row hero's win rate with the column hero on the same team
+    Synergy,
+    /// Row hero's win rate against the column hero
+    Counter,
+}
+
+/// A hero's own record, the baseline pair win rates are compared against.
+#[derive(Debug, Clone, Serialize)]
+pub struct Baseline {
+    pub hero_id: i32,
+    pub matches: usize,
+    pub wins: usize,
+    /// Percent
+    pub win_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PairStats {
+    pub matches: usize,
+    /// The row hero's
+    pub wins: usize,
+    /// Percent
+    pub win_rate: f64,
+    /// Percent. Synergy: mean of both heroes' win rates; counter: mean of the row hero's
+    /// win rate and the column hero's loss rate.
+    pub expected: Option<f64>,
+    /// `win_rate - expected`, percentage points
+    pub delta: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Pair {
+    pub hero: PairedHero,
+    pub other: PairedHero,
+    #[serde(flatten)]
+    pub stats: PairStats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SynergyReport {
+    /// Distinct matches with at least one usable row
+    pub matches: usize,
+    /// By hero ID
+    pub heroes: Vec<Baseline>,
+    /// Both orders of every same-team pair, by hero then other
+    pub synergy: Vec<Pair>,
+    /// Every ordered opposing pair, by hero then other
+    pub counters: Vec<Pair>,
+}
+
+/// One hero's best and worst teammates and opponents.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeroSynergy {
+    pub baseline: Option<Baseline>,
+    pub min_matches: usize,
+    /// Highest delta first
+    pub with: Vec<Pair>,
+    /// Highest delta first: the heroes this one beats most
+    pub against: Vec<Pair>,
+}
+
+/// Square matrix for export; `cells[i][j]` is row hero `i` with/against column hero `j`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Matrix {
+    pub kind: MatrixKind,
+    pub min_matches: usize,
+    pub heroes: Vec<Baseline>,
+    /// `None` on the diagonal and for pairs seen fewer than `min_matches` times
+    pub cells: Vec<Vec<Option<PairStats>>>,
+}
+
+fn rate(matches: usize, wins: usize) -> Option<f64> {
+    (matches > 0).then(|| wins as f64 / matches as f64 * 100.0)
+}
+
+/// Pairwise hero win rates from team composition, compared against each hero's own win rate.
+pub fn analyze(totals: &SynergyTotals) -> SynergyReport {
+    let solo: BTreeMap<i32, HeroRecord> = totals.heroes.iter().map(|h| (h.hero_id, *h)).collect();
+    let solo_rate = |hero: i32| solo.get(&hero).and_then(|h| rate(h.matches, h.wins));
+
+    let mut pairs: Vec<&PairRecord> = totals.pairs.iter().collect();
+    pairs.sort_by_key(|p| (p.hero_id, p.other_hero_id));
+    let (mut synergy, mut counters) = (Vec::new(), Vec::new());
+    for p in pairs {
+        let kind = if p.same_team { MatrixKind::Synergy } else { MatrixKind::Counter };
+        let win_rate = rate(p.matches, p.wins).unwrap_or_default();
+        let expected = solo_rate(p.hero_id).zip(solo_rate(p.other_hero_id)).map(|(a, b)| match kind {
+            MatrixKind::Synergy => (a + b) / 2.0,
+            MatrixKind::Counter => (a + 100.0 - b) / 2.0,
+        });
+        let pair = Pair {
+            hero: PairedHero { hero_id: p.hero_id },
+            other: PairedHero { hero_id: p.other_hero_id },
+            stats: PairStats { matches: p.matches, wins: p.wins, win_rate, expected, delta: expected.map(|e| win_rate - e) },
+        };
+        match kind {
+            MatrixKind::Synergy => synergy.push(pair),
+            MatrixKind::Counter => counters.push(pair),
+        }
+    }
+
+    SynergyReport {
+        matches: totals.matches,
+        heroes: solo
+            .values()
+            .map(|h| Baseline { hero_id: h.hero_id, matches: h.matches, wins: h.wins, win_rate: rate(h.matches, h.wins) })
+            .collect(),
+        synergy,
+        counters,
+    }
+}
+
+impl SynergyReport {
+    /// `hero_id`'s pairs seen at least `min_matches` times, at most `limit` per list.
+    pub fn for_hero(&self, hero_id: i32, min_matches: usize, limit: usize) -> HeroSynergy {
+        let pick = |pairs: &[Pair]| {
+            let mut list: Vec<Pair> =
+                pairs.iter().filter(|p| p.hero.hero_id == hero_id && p.stats.matches >= min_matches.max(1)).cloned().collect();
+            list.sort_by(|a, b| {
+                b.stats.delta.unwrap_or(f64::MIN).total_cmp(&a.stats.delta.unwrap_or(f64::MIN)).then(a.other.hero_id.cmp(&b.other.hero_id))
+            });
+            list.truncate(limit);
+            list
+        };
+        HeroSynergy {
+            baseline: self.heroes.iter().find(|h| h.hero_id == hero_id).cloned(),
+            min_matches,
+            with: pick(&self.synergy),
+            against: pick(&self.counters),
+        }
+    }
+
+    pub fn matrix(&self, kind: MatrixKind, min_matches: usize) -> Matrix {
+        let pairs = match kind {
+            MatrixKind::Synergy => &self.synergy,
+            MatrixKind::Counter => &self.counters,
+        };
+        let by_pair: HashMap<(i32, i32), PairStats> =
+            pairs.iter().filter(|p| p.stats.matches >= min_matches.max(1)).map(|p| ((p.hero.hero_id, p.other.hero_id), p.stats)).collect();
+        let cells = self
+            .heroes
+            .iter()
+            .map(|row| self.heroes.iter().map(|col| by_pair.get(&(row.hero_id, col.hero_id)).copied()).collect())
+            .collect();
+        Matrix { kind, min_matches, heroes: self.heroes.clone(), cells }
+    }
+}
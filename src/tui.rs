@@ -0,0 +1,547 @@
+use crate::deadlock::{self, DeadlockApi};
+use crate::heroes::HeroCatalog;
+use crate::mmr::{self, MmrReport};
+use crate::models::{Badge, CombinedPayload, PlayerMatch, RecentLookup};
+use crate::recorder::Recorder;
+use crate::steam;
+use crate::ui;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Row, Sparkline, Table, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use reqwest::Client;
+use std::time::{Duration, Instant};
+
+/// How often the open player is refetched while the dashboard sits idle.
+const REFRESH_EVERY: Duration = Duration::from_secs(120);
+#[cfg(feature = "db")]
+const RECENT_LIMIT: i64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Profile,
+    Heroes,
+    Matches,
+    Mmr,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 4] = [Tab::Profile, Tab::Heroes, Tab::Matches, Tab::Mmr];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Profile => "Profile",
+            Tab::Heroes => "Heroes",
+            Tab::Matches => "Matches",
+            Tab::Mmr => "MMR",
+        }
+    }
+
+    fn index(self) -> usize {
+        Tab::ALL.iter().position(|t| *t == self).unwrap_or_default()
+    }
+
+    fn step(self, by: isize) -> Tab {
+        let n = Tab::ALL.len() as isize;
+        Tab::ALL[(self.index() as isize + by).rem_euclid(n) as usize]
+    }
+}
+
+/// Which pane receives navigation keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Search,
+    Recent,
+    Content,
+}
+
+/// What the event loop should do after a key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    None,
+    Quit,
+    /// Look up whatever identifier the user entered or selected
+    Load(String),
+    Refresh,
+}
+
+/// Everything the dashboard shows for one player.
+#[derive(Debug, Clone)]
+pub struct PlayerView {
+    pub payload: CombinedPayload,
+    /// Newest first
+    pub matches: Vec<PlayerMatch>,
+    pub mmr: Option<MmrReport>,
+    pub loaded_at: DateTime<Utc>,
+}
+
+/// Dashboard state; rendering and key handling are pure so they can run against a test backend.
+pub struct Dashboard {
+    pub input: String,
+    pub focus: Focus,
+    pub tab: Tab,
+    pub recent: Vec<RecentLookup>,
+    pub player: Option<PlayerView>,
+    /// First visible row of the active tab's table
+    pub scroll: usize,
+    pub status: String,
+    recent_state: ListState,
+    catalog: HeroCatalog,
+    color: bool,
+}
+
+impl Dashboard {
+    pub fn new(catalog: HeroCatalog, color: bool) -> Self {
+        Self {
+            input: String::new(),
+            focus: Focus::Search,
+            tab: Tab::Profile,
+            recent: Vec::new(),
+            player: None,
+            scroll: 0,
+            status: "Type a SteamID64, vanity name, profile URL or [U:1:x] and press Enter".into(),
+            recent_state: ListState::default(),
+            catalog,
+            color,
+        }
+    }
+
+    pub fn set_recent(&mut self, recent: Vec<RecentLookup>) {
+        let selected = self.recent_state.selected().map(|i| i.min(recent.len().saturating_sub(1)));
+        self.recent_state.select(if recent.is_empty() { None } else { selected.or(Some(0)) });
+        self.recent = recent;
+    }
+
+    pub fn set_player(&mut self, view: PlayerView) {
+        let same = self.player.as_ref().is_some_and(|p| p.payload.account_id == view.payload.account_id);
+        if !same {
+            self.scroll = 0;
+            self.focus = Focus::Content;
+        }
+        self.status = format!("Loaded {} ({})", view.payload.profile.personaname, view.payload.account_id);
+        self.player = Some(view);
+    }
+
+    pub fn selected_recent(&self) -> Option<&RecentLookup> {
+        self.recent_state.selected().and_then(|i| self.recent.get(i))
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.kind != KeyEventKind::Press {
+            return Action::None;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+
+        if self.focus == Focus::Search {
+            match key.code {
+                KeyCode::Char(c) => self.input.push(c),
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Enter if !self.input.trim().is_empty() => return Action::Load(self.input.trim().to_string()),
+                KeyCode::Esc | KeyCode::Tab => self.focus = Focus::Recent,
+                KeyCode::BackTab => self.focus = Focus::Content,
+                _ => {}
+            }
+            return Action::None;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('/') => self.focus = Focus::Search,
+            KeyCode::Char('r') if self.player.is_some() => return Action::Refresh,
+            KeyCode::Tab => self.focus = if self.focus == Focus::Recent { Focus::Content } else { Focus::Search },
+            KeyCode::BackTab => self.focus = if self.focus == Focus::Content { Focus::Recent } else { Focus::Search },
+            KeyCode::Right => self.switch_tab(self.tab.step(1)),
+            KeyCode::Left => self.switch_tab(self.tab.step(-1)),
+            KeyCode::Char(c @ '1'..='4') => self.switch_tab(Tab::ALL[c as usize - '1' as usize]),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::Enter if self.focus == Focus::Recent => {
+                if let Some(r) = self.selected_recent() {
+                    return Action::Load(r.account_id.to_string());
+                }
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn switch_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        self.scroll = 0;
+    }
+
+    fn move_selection(&mut self, by: isize) {
+        if self.focus == Focus::Recent {
+            if self.recent.is_empty() {
+                return;
+            }
+            let cur = self.recent_state.selected().unwrap_or(0) as isize;
+            self.recent_state.select(Some((cur + by).clamp(0, self.recent.len() as isize - 1) as usize));
+        } else {
+            self.scroll = self.scroll.saturating_add_signed(by).min(self.row_count().saturating_sub(1));
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        let Some(p) = &self.player else { return 0 };
+        match self.tab {
+            Tab::Profile => 0,
+            Tab::Heroes => p.payload.hero_stats.len(),
+            Tab::Matches => p.matches.len(),
+            Tab::Mmr => p.mmr.as_ref().map(|m| m.timeline.len()).unwrap_or_default(),
+        }
+    }
+
+    fn accent(&self, color: Color) -> Style {
+        if self.color { Style::default().fg(color) } else { Style::default().add_modifier(Modifier::BOLD) }
+    }
+
+    fn pane(&self, title: &str, focus: Focus) -> Block<'static> {
+        let block = Block::default().borders(Borders::ALL).title(title.to_string());
+        if self.focus == focus { block.border_style(self.accent(Color::Yellow)) } else { block }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let [search, tabs, body, status] =
+            Layout::vertical([Constraint::Length(3), Constraint::Length(3), Constraint::Min(5), Constraint::Length(1)])
+                .areas(frame.area());
+        let [sidebar, content] = Layout::horizontal([Constraint::Length(30), Constraint::Min(20)]).areas(body);
+
+        let search_block = self.pane("Search (SteamID64 / vanity / URL / [U:1:x])", Focus::Search);
+        frame.render_widget(Paragraph::new(self.input.as_str()).block(search_block), search);
+        if self.focus == Focus::Search {
+            frame.set_cursor_position((search.x + 1 + self.input.chars().count() as u16, search.y + 1));
+        }
+
+        let titles: Vec<String> = Tab::ALL.iter().enumerate().map(|(i, t)| format!("{} {}", i + 1, t.title())).collect();
+        let tab_bar = Tabs::new(titles)
+            .select(self.tab.index())
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(self.accent(Color::Cyan).add_modifier(Modifier::REVERSED));
+        frame.render_widget(tab_bar, tabs);
+
+        let items: Vec<ListItem> = self
+            .recent
+            .iter()
+            .map(|r| {
+                let name = if r.personaname.is_empty() { r.account_id.to_string() } else { r.personaname.clone() };
+                ListItem::new(format!("{} · {}", name, r.looked_up_at.format("%m-%d %H:%M")))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(self.pane("Recent lookups", Focus::Recent))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, sidebar, &mut self.recent_state);
+
+        match &self.player {
+            None => {
+                let help = Paragraph::new(vec![
+                    Line::from("No player loaded."),
+                    Line::from(""),
+                    Line::from("/ search · Tab switch pane · Enter load · ←/→ or 1-4 tabs"),
+                    Line::from("↑/↓ scroll · r refresh · q quit"),
+                ])
+                .block(self.pane(self.tab.title(), Focus::Content));
+                frame.render_widget(help, content);
+            }
+            Some(p) => match self.tab {
+                Tab::Profile => self.draw_profile(frame, content, p),
+                Tab::Heroes => self.draw_heroes(frame, content, p),
+                Tab::Matches => self.draw_matches(frame, content, p),
+                Tab::Mmr => self.draw_mmr(frame, content, p),
+            },
+        }
+
+        let hints = "/ search · Tab pane · ←→ tabs · r refresh · q quit";
+        frame.render_widget(Paragraph::new(format!("{}  |  {}", self.status, hints)), status);
+    }
+
+    fn draw_profile(&self, frame: &mut Frame, area: Rect, p: &PlayerView) {
+        let c = &p.payload;
+        let rank = c
+            .latest_mmr
+            .as_ref()
+            .map(|m| match m.badge() {
+                Some(b) => format!("{} (score {:.1})", b, m.player_score),
+                None => format!("rank {} (score {:.1})", m.rank, m.player_score),
+            })
+            .unwrap_or_else(|| "-".into());
+        let matches: i64 = c.hero_stats.iter().filter_map(|h| h.matches_played).sum();
+        let wins: i64 = c.hero_stats.iter().filter_map(|h| h.wins).sum();
+        let winrate = if matches > 0 { wins as f64 / matches as f64 * 100.0 } else { 0.0 };
+        let age = (Utc::now() - p.loaded_at).num_seconds().max(0);
+        let lines = vec![
+            Line::from(format!("Name:       {}", c.profile.personaname)),
+            Line::from(format!("SteamID64:  {}", c.steamid64)),
+            Line::from(format!("Account ID: {}", c.account_id)),
+            Line::from(format!("Country:    {}", c.profile.countrycode.as_deref().unwrap_or("-"))),
+            Line::from(format!("Rank:       {}", rank)),
+            Line::from(format!("Matches:    {} ({} wins, {:.1}%)", matches, wins, winrate)),
+            Line::from(format!("Updated:    {}s ago", age)),
+        ];
+        frame.render_widget(Paragraph::new(lines).block(self.pane("Profile", Focus::Content)), area);
+    }
+
+    fn draw_heroes(&self, frame: &mut Frame, area: Rect, p: &PlayerView) {
+        let rows = p.payload.hero_stats.iter().skip(self.scroll).map(|h| {
+            let played = h.matches_played.unwrap_or_default();
+            let wins = h.wins.unwrap_or_default();
+            let wr = if played > 0 { format!("{:.1}%", wins as f64 / played as f64 * 100.0) } else { "-".into() };
+            Row::new(vec![
+                self.catalog.name(h.hero_id),
+                self.catalog.role(h.hero_id).unwrap_or("-").to_string(),
+                played.to_string(),
+                wins.to_string(),
+                wr,
+                fmt_opt(h.kills_per_min),
+                fmt_opt(h.deaths_per_min),
+                fmt_opt(h.assists_per_min),
+            ])
+        });
+        let widths = [
+            Constraint::Min(14),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(6),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(vec!["Hero", "Role", "Matches", "Wins", "WR", "KPM", "DPM", "APM"]).style(self.accent(Color::Cyan)))
+            .block(self.pane("Heroes", Focus::Content));
+        frame.render_widget(table, area);
+    }
+
+    fn draw_matches(&self, frame: &mut Frame, area: Rect, p: &PlayerView) {
+        let [table_area, trend] = Layout::vertical([Constraint::Min(3), Constraint::Length(4)]).areas(area);
+        let rows = p.matches.iter().skip(self.scroll).map(|m| {
+            let result = match m.is_victory {
+                Some(true) => "win",
+                Some(false) => "loss",
+                None => "-",
+            };
+            let style = match m.is_victory {
+                Some(true) => self.accent(Color::Green),
+                Some(false) if self.color => Style::default().fg(Color::Red),
+                _ => Style::default(),
+            };
+            Row::new(vec![
+                m.start_time.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0)).map(|t| t.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_else(|| "-".into()),
+                m.hero_id.map(|h| self.catalog.name(h)).unwrap_or_else(|| "-".into()),
+                m.match_mode.map(|md| md.name().to_string()).unwrap_or_else(|| "-".into()),
+                result.to_string(),
+                format!("{}/{}/{}", m.kills.unwrap_or_default(), m.deaths.unwrap_or_default(), m.assists.unwrap_or_default()),
+                m.networth.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+            ])
+            .style(style)
+        });
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Min(12),
+            Constraint::Length(10),
+            Constraint::Length(5),
+            Constraint::Length(9),
+            Constraint::Length(9),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(vec!["Date", "Hero", "Mode", "Result", "K/D/A", "Networth"]).style(self.accent(Color::Cyan)))
+            .block(self.pane(&format!("Matches ({})", p.matches.len()), Focus::Content));
+        frame.render_widget(table, table_area);
+
+        let width = trend.width.saturating_sub(10) as usize;
+        let oldest_first: Vec<&PlayerMatch> = p.matches.iter().rev().collect();
+        let kda: Vec<f64> = oldest_first.iter().filter_map(|m| m.kda()).collect();
+        let results: Vec<Option<bool>> = oldest_first.iter().rev().take(width).rev().map(|m| m.is_victory).collect();
+        let plain = ui::ChartStyle { width, color: false };
+        let lines = vec![
+            Line::from(format!("KDA      {}", ui::sparkline_fit(&kda, width))),
+            Line::from(format!("Results  {}", ui::result_strip(&results, plain))),
+        ];
+        frame.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Trend")), trend);
+    }
+
+    fn draw_mmr(&self, frame: &mut Frame, area: Rect, p: &PlayerView) {
+        let Some(report) = &p.mmr else {
+            frame.render_widget(Paragraph::new("No MMR history.").block(self.pane("MMR", Focus::Content)), area);
+            return;
+        };
+        let [summary, chart, timeline] =
+            Layout::vertical([Constraint::Length(5), Constraint::Length(6), Constraint::Min(3)]).areas(area);
+
+        let badge = |pt: Option<&mmr::MmrPoint>| {
+            pt.map(|pt| pt.badge.map(|b| b.to_string()).unwrap_or_else(|| format!("rank {}", pt.rank))).unwrap_or_else(|| "-".into())
+        };
+        let lines = vec![
+            Line::from(format!("Latest: {}   Net change: {:+.1}", badge(report.latest()), report.net_change)),
+            Line::from(format!("Peak:   {}   Lowest: {}", badge(report.peak.as_ref()), badge(report.lowest.as_ref()))),
+            Line::from(format!("Snapshots: {}   Rolling window: {}", report.timeline.len(), report.window)),
+        ];
+        frame.render_widget(Paragraph::new(lines).block(self.pane("MMR", Focus::Content)), summary);
+
+        let width = chart.width.saturating_sub(2) as usize;
+        let ranks: Vec<u64> = report.timeline.iter().rev().take(width).rev().map(|pt| pt.rank.max(0) as u64).collect();
+        let spark = Sparkline::default()
+            .block(Block::default().borders(Borders::ALL).title("Rank over time"))
+            .data(&ranks)
+            .style(self.accent(Color::Magenta));
+        frame.render_widget(spark, chart);
+
+        let rows = report.timeline.iter().rev().skip(self.scroll).map(|pt| {
+            Row::new(vec![
+                DateTime::<Utc>::from_timestamp(pt.start_time as i64, 0).map(|t| t.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "-".into()),
+                pt.badge.map(Badge::name).unwrap_or_else(|| "-".into()),
+                format!("{:.1}", pt.player_score),
+                pt.delta.map(|d| format!("{:+.1}", d)).unwrap_or_else(|| "-".into()),
+                format!("{:.1}", pt.rolling_avg),
+            ])
+        });
+        let widths = [Constraint::Length(11), Constraint::Min(12), Constraint::Length(7), Constraint::Length(7), Constraint::Length(7)];
+        let table = Table::new(rows, widths)
+            .header(Row::new(vec!["Date", "Badge", "Score", "Change", "Avg"]).style(self.accent(Color::Cyan)))
+            .block(Block::default().borders(Borders::ALL).title("Timeline"));
+        frame.render_widget(table, timeline);
+    }
+}
+
+fn fmt_opt(v: Option<f64>) -> String {
+    v.map(|x| format!("{:.2}", x)).unwrap_or_else(|| "-".into())
+}
+
+/// Where the dashboard gets its data from.
+pub struct Env<'a, A> {
+    pub api: &'a A,
+    pub http: &'a Client,
+    pub recorder: Option<&'a Recorder>,
+    /// Read the database only; never call the Deadlock API
+    pub offline: bool,
+    #[cfg(feature = "db")]
+    pub pool: Option<&'a sqlx::PgPool>,
+}
+
+/// Resolve `query` and gather profile, heroes, matches and MMR for the dashboard. Online loads
+/// are persisted like a normal lookup when a database is available.
+pub async fn load_view<A: DeadlockApi>(env: &Env<'_, A>, query: &str) -> Result<PlayerView> {
+    let account_id = steam::resolve_account_id(query, env.http, env.recorder).await?;
+    let steamid64 = steam::account_id_to_steamid64(account_id);
+
+    let mut history = Vec::new();
+    let mut fetched_mmr = Vec::new();
+    let payload = if env.offline {
+        #[cfg(feature = "db")]
+        let stored = match env.pool {
+            Some(pool) => {
+                crate::db::mark_lookup(pool, account_id).await?;
+                crate::db::load_player(pool, account_id).await?.map(|s| s.payload)
+            }
+            None => None,
+        };
+        #[cfg(not(feature = "db"))]
+        let stored: Option<CombinedPayload> = None;
+        stored.ok_or_else(|| anyhow::anyhow!("No stored data for account {} (run an online lookup first)", account_id))?
+    } else {
+        let payload = deadlock::fetch_player(env.api, &steamid64, account_id).await?;
+        history = env.api.get_player_match_history(account_id, false, true).await.unwrap_or_default();
+        fetched_mmr = env.api.get_mmr_history(account_id).await.unwrap_or_default();
+        #[cfg(feature = "db")]
+        if let Some(pool) = env.pool {
+            crate::db::ingest_player(pool, &payload).await?;
+            if !history.is_empty() {
+                crate::db::ingest_matches_batch(pool, &crate::models::history_to_metas(history.clone())).await?;
+            }
+            if !fetched_mmr.is_empty() {
+                crate::db::store_mmr_history(pool, account_id, &fetched_mmr).await?;
+            }
+        }
+        payload
+    };
+
+    let mut matches: Vec<PlayerMatch> = history.iter().map(PlayerMatch::from).collect();
+    let mut series = vec![fetched_mmr];
+    #[cfg(feature = "db")]
+    if let Some(pool) = env.pool {
+        matches = crate::db::load_player_matches(pool, account_id).await?;
+        let ids: Vec<i64> = matches.iter().map(|m| m.match_id).collect();
+        crate::score::Scores::new(crate::db::load_scores(pool, &ids, Some(account_id)).await?).annotate(account_id, &mut matches);
+        series.insert(0, crate::db::load_mmr_history(pool, account_id).await?);
+    }
+    matches.sort_by_key(|m| std::cmp::Reverse(m.start_time));
+    series.push(payload.latest_mmr.iter().cloned().collect());
+    let series = mmr::merge(series);
+    let mmr = (!series.is_empty()).then(|| mmr::analyze(account_id, &series, 5));
+
+    Ok(PlayerView { payload, matches, mmr, loaded_at: Utc::now() })
+}
+
+async fn refresh_recent<A: DeadlockApi>(_env: &Env<'_, A>, dash: &mut Dashboard) {
+    #[cfg(feature = "db")]
+    if let Some(pool) = _env.pool {
+        match crate::db::recent_lookups(pool, RECENT_LIMIT).await {
+            Ok(recent) => dash.set_recent(recent),
+            Err(e) => dash.status = format!("Failed to load recent lookups: {}", e),
+        }
+    }
+    #[cfg(not(feature = "db"))]
+    let _ = dash;
+}
+
+/// Run the full-screen dashboard until the user quits.
+pub async fn run<A: DeadlockApi>(env: &Env<'_, A>, catalog: HeroCatalog, color: bool) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let res = event_loop(&mut terminal, env, Dashboard::new(catalog, color)).await;
+    ratatui::restore();
+    res
+}
+
+async fn event_loop<A: DeadlockApi>(terminal: &mut DefaultTerminal, env: &Env<'_, A>, mut dash: Dashboard) -> Result<()> {
+    refresh_recent(env, &mut dash).await;
+    let mut last_load = Instant::now();
+    let mut current: Option<String> = None;
+
+    loop {
+        terminal.draw(|f| dash.draw(f))?;
+
+        let mut action = Action::None;
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                action = dash.handle_key(key);
+            }
+        } else if !env.offline && current.is_some() && last_load.elapsed() >= REFRESH_EVERY {
+            action = Action::Refresh;
+        }
+
+        let query = match action {
+            Action::None => continue,
+            Action::Quit => return Ok(()),
+            Action::Load(q) => q,
+            Action::Refresh => match &current {
+                Some(q) => q.clone(),
+                None => continue,
+            },
+        };
+
+        dash.status = format!("Loading {}…", query);
+        terminal.draw(|f| dash.draw(f))?;
+        match load_view(env, &query).await {
+            Ok(view) => {
+                current = Some(view.payload.account_id.to_string());
+                dash.input.clear();
+                dash.set_player(view);
+                refresh_recent(env, &mut dash).await;
+            }
+            Err(e) => dash.status = format!("Error: {:#}", e),
+        }
+        last_load = Instant::now();
+    }
+}
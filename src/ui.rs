@@ -1,21 +1,101 @@
-use crate::models::{HeroStats, MMRHistory, SteamProfile};
-use comfy_table::{presets::UTF8_FULL, Table};
+use crate::batch::BatchRecord;
+use crate::compare::{Comparison, Metric, PlayerSummary};
+use crate::duo::{DuoReport, Side};
+use crate::heroes::HeroCatalog;
+use crate::history::Page;
+use crate::mates::{Associate, MatesReport};
+use crate::meta::{Interval, MetaReport};
+use crate::mmr::MmrReport;
+use crate::models::{
+    annotate_badges, Badge, CombinedPayload, HeroStats, KdaAverages, MMRHistory, MatchMeta, MatchObjectives, ObjectiveStatus, Performance, PerformanceScore, PlayerInMatch, PlayerMatch,
+    SteamProfile, Team, OBJECTIVES,
+};
+use crate::party::PartyReport;
+use crate::patches::PatchReport;
+use crate::score::Scores;
+use crate::sessions::{SessionsReport, Streak, POSITION_CAP, TILT_CAP};
+use crate::synergy::{HeroSynergy, Matrix};
+use chrono::{DateTime, FixedOffset, Utc};
+use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, Table};
 use serde::Serialize;
 
+/// JSON shape for `--offline` lookups: the usual payload plus where it came from.
 #[derive(Debug, Clone, Serialize)]
-pub struct CombinedPayload {
-    pub steamid64: String,
-    pub account_id: u32,
-    pub profile: SteamProfile,
-    pub latest_mmr: Option<MMRHistory>,
-    pub hero_stats: Vec<HeroStats>,
+pub struct OfflinePayload<'a> {
+    #[serde(flatten)]
+    pub payload: &'a CombinedPayload,
+    pub offline: bool,
+    pub profile_updated_at: Option<DateTime<Utc>>,
+    pub staleness: Staleness,
 }
 
-pub fn latest_mmr_for(all: &[MMRHistory], account_id: u32) -> Option<MMRHistory> {
-    all.iter()
-        .filter(|m| m.account_id as u32 == account_id)
-        .cloned()
-        .max_by_key(|m| (m.start_time, m.match_id))
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Staleness {
+    Fresh,
+    Stale,
+    VeryStale,
+    Unknown,
+}
+
+impl Staleness {
+    /// Fresh under a day old, stale under a week, very stale beyond that.
+    pub fn from_updated_at(updated_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Self {
+        match updated_at.map(|t| now - t) {
+            None => Staleness::Unknown,
+            Some(age) if age < chrono::Duration::days(1) => Staleness::Fresh,
+            Some(age) if age < chrono::Duration::days(7) => Staleness::Stale,
+            Some(_) => Staleness::VeryStale,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Staleness::Fresh => "fresh",
+            Staleness::Stale => "stale",
+            Staleness::VeryStale => "very stale",
+            Staleness::Unknown => "unknown age",
+        }
+    }
+}
+
+/// Any payload as a JSON value, with hero names/roles and decoded badges added.
+fn annotated_json(value: &impl Serialize, catalog: &HeroCatalog) -> anyhow::Result<serde_json::Value> {
+    let mut v = serde_json::to_value(value)?;
+    catalog.annotate(&mut v);
+    annotate_badges(&mut v);
+    Ok(v)
+}
+
+/// Pretty-print any payload as JSON, with hero names/roles and decoded badges added.
+pub fn print_json(value: &impl Serialize, catalog: &HeroCatalog) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(&annotated_json(value, catalog)?)?);
+    Ok(())
+}
+
+/// Like [`print_json`] but on a single line, for NDJSON streams.
+pub fn print_json_line(value: &impl Serialize, catalog: &HeroCatalog) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string(&annotated_json(value, catalog)?)?);
+    Ok(())
+}
+
+pub fn print_offline_banner(updated_at: Option<DateTime<Utc>>) {
+    let now = Utc::now();
+    let staleness = Staleness::from_updated_at(updated_at, now);
+    match updated_at {
+        Some(t) => {
+            let age = now - t;
+            let ago = if age.num_days() > 0 {
+                format!("{}d ago", age.num_days())
+            } else if age.num_hours() > 0 {
+                format!("{}h ago", age.num_hours())
+            } else {
+                format!("{}m ago", age.num_minutes().max(0))
+            };
+            println!("\n[offline] Showing stored data; profile updated {} ({}) — {}", t.format("%Y-%m-%d %H:%M UTC"), ago, staleness.label());
+        }
+        None => println!("\n[offline] Showing stored data; profile update time unknown"),
+    }
 }
 
 pub fn print_profile_table(profile: &SteamProfile, steamid64: &str, account_id: u32, mmr: Option<&MMRHistory>) {
@@ -29,7 +109,11 @@ pub fn print_profile_table(profile: &SteamProfile, steamid64: &str, account_id:
     if let Some(c) = &profile.countrycode { table.add_row(vec!["Country", c]); }
     table.add_row(vec!["Profile URL", &profile.profileurl]);
     if let Some(m) = mmr {
-        table.add_row(vec!["Rank", &format!("{} (div {}-{})", m.rank, m.division, m.division_tier)]);
+        let rank = match m.badge() {
+            Some(b) => format!("{} (badge {})", b, b.badge()),
+            None => format!("{} (div {}-{})", m.rank, m.division, m.division_tier),
+        };
+        table.add_row(vec!["Rank", &rank]);
     }
 
     println!("\n== Profile ==\n{}
@@ -52,14 +136,15 @@ pub fn print_stats_table(hero_stats: &[HeroStats]) {
 ", table);
 }
 
-pub fn print_detailed_hero_stats(hero_stats: &[HeroStats]) {
+pub fn print_detailed_hero_stats(hero_stats: &[HeroStats], heroes: &HeroCatalog, style: ChartStyle) {
     let mut table = Table::new();
     table.load_preset(UTF8_FULL);
-    table.set_header(vec!["Hero ID", "Matches", "Wins", "KPM", "DPM", "APM"]);
+    table.set_header(vec!["Hero", "Role", "Matches", "Wins", "KPM", "DPM", "APM"]);
 
     for h in hero_stats {
         table.add_row(vec![
-            h.hero_id.to_string(),
+            heroes.name(h.hero_id),
+            heroes.role(h.hero_id).unwrap_or("-").to_string(),
             h.matches_played.unwrap_or_default().to_string(),
             h.wins.unwrap_or_default().to_string(),
             fmt_opt_f(h.kills_per_min),
@@ -69,7 +154,892 @@ pub fn print_detailed_hero_stats(hero_stats: &[HeroStats]) {
     }
     println!("\n== Detailed Hero Stats ==\n{}
 ", table);
+
+    let bars: Vec<Bar> = hero_stats
+        .iter()
+        .filter_map(|h| {
+            let played = h.matches_played.filter(|&n| n > 0)?;
+            let rate = h.wins.unwrap_or_default() as f64 / played as f64 * 100.0;
+            Some(Bar {
+                label: heroes.name(h.hero_id),
+                value: rate,
+                text: format!("{:.1}% ({})", rate, played),
+                tone: if rate >= 50.0 { Tone::Good } else { Tone::Bad },
+            })
+        })
+        .collect();
+    if !bars.is_empty() {
+        println!("== Win Rate by Hero ==\n{}", bar_chart(&bars, Some(100.0), style));
+    }
+}
+
+fn rank_label(m: Option<&MMRHistory>) -> String {
+    match m {
+        Some(m) => m.badge().map(|b| b.to_string()).unwrap_or_else(|| format!("rank {}", m.rank)),
+        None => "-".into(),
+    }
+}
+
+fn totals(hero_stats: &[HeroStats]) -> (i64, i64) {
+    let matches = hero_stats.iter().filter_map(|h| h.matches_played).sum();
+    let wins = hero_stats.iter().filter_map(|h| h.wins).sum();
+    (matches, wins)
+}
+
+fn metric_text(m: Metric, p: &PlayerSummary) -> String {
+    match (m, m.value(p)) {
+        (_, None) => "-".into(),
+        (Metric::Rank, Some(r)) => p.badge.map(|b| b.to_string()).unwrap_or_else(|| format!("rank {}", r)),
+        (Metric::Matches, Some(v)) => format!("{}", v),
+        (Metric::WinRate, Some(v)) => format!("{:.2}%", v),
+        (Metric::DamagePerMin, Some(v)) => format!("{:.0}", v),
+        (Metric::Accuracy, Some(v)) => format!("{:.1}%", v * 100.0),
+        (_, Some(v)) => format!("{:.2}", v),
+    }
+}
+
+/// One column per player, one row per metric; the best value in each row is highlighted
+/// (green when colour is on, `*` otherwise).
+pub fn print_comparison(c: &Comparison, heroes: &HeroCatalog, style: ChartStyle) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    if style.color {
+        table.enforce_styling();
+    }
+    let mut header = vec!["Player".to_string()];
+    header.extend(c.players.iter().map(|p| p.personaname.clone()));
+    table.set_header(header);
+
+    let mut ids = vec![Cell::new("Account ID")];
+    ids.extend(c.players.iter().map(|p| Cell::new(p.account_id)));
+    table.add_row(ids);
+    for m in Metric::ALL {
+        let mut row = vec![Cell::new(m.label())];
+        for p in &c.players {
+            let text = metric_text(m, p);
+            row.push(match (c.is_best(m, p.account_id), style.color) {
+                (false, _) => Cell::new(text),
+                (true, true) => Cell::new(text).fg(Color::Green).add_attribute(Attribute::Bold),
+                (true, false) => Cell::new(format!("{} *", text)),
+            });
+        }
+        table.add_row(row);
+    }
+    let mut top = vec![Cell::new("Most Played")];
+    top.extend(c.players.iter().map(|p| {
+        let lines: Vec<String> = p
+            .top_heroes
+            .iter()
+            .map(|h| format!("{} ({}, {})", heroes.name(h.hero_id), h.matches_played, h.win_rate.map(|w| format!("{:.0}%", w)).unwrap_or_else(|| "-".into())))
+            .collect();
+        Cell::new(if lines.is_empty() { "-".to_string() } else { lines.join("\n") })
+    }));
+    table.add_row(top);
+
+    println!("\n== Comparison ==\n{}", table);
+    if !style.color {
+        println!("* best in row");
+    }
+    println!();
+}
+
+/// Players looked up in a `shell` session, oldest first; `current` is marked.
+pub fn print_session_history(players: &[&CombinedPayload], current: Option<u32>) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["#", "Name", "Account ID", "Rank", "Matches"]);
+    for (i, p) in players.iter().enumerate() {
+        let marker = if Some(p.account_id) == current { "*" } else { "" };
+        table.add_row(vec![
+            format!("{}{}", i + 1, marker),
+            p.profile.personaname.clone(),
+            p.account_id.to_string(),
+            rank_label(p.latest_mmr.as_ref()),
+            totals(&p.hero_stats).0.to_string(),
+        ]);
+    }
+    println!("\n== Session ==\n{}\n", table);
+}
+
+/// One row per input line of `batch`; failed lines show their error instead of stats.
+pub fn print_batch_table(records: &[BatchRecord]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Line", "Input", "Account ID", "Name", "Rank", "Matches", "Win Rate", "Status"]);
+    for r in records {
+        let account = r.account_id.map(|a| a.to_string()).unwrap_or_else(|| "-".into());
+        let mut row = vec![r.line.to_string(), r.input.clone(), account];
+        match &r.player {
+            Some(p) => {
+                let (matches, wins) = totals(&p.hero_stats);
+                let winrate = if matches > 0 { format!("{:.2}%", wins as f64 / matches as f64 * 100.0) } else { "-".into() };
+                row.extend([p.profile.personaname.clone(), rank_label(p.latest_mmr.as_ref()), matches.to_string(), winrate]);
+            }
+            None => row.extend(["-".to_string(), "-".into(), "-".into(), "-".into()]),
+        }
+        row.push(r.error.clone().unwrap_or_else(|| "ok".into()));
+        table.add_row(row);
+    }
+    println!("\n== Batch ==\n{}\n", table);
+}
+
+fn kda_text(k: &KdaAverages) -> String {
+    format!("{:.1}/{:.1}/{:.1} ({:.2})", k.kills, k.deaths, k.assists, k.kda)
+}
+
+/// `vs`: together/against summary, top hero pairings per side and the latest shared matches.
+pub fn print_duo_report(r: &DuoReport, name_a: &str, name_b: &str, heroes: &HeroCatalog, pairings: usize, limit: usize) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["", "Same Team", "Opposing Teams"]);
+    let pct = |s: &Side| s.win_rate.map(|w| format!("{:.1}%", w)).unwrap_or_else(|| "-".into());
+    table.add_row(vec!["Matches".to_string(), r.together.matches.to_string(), r.against.matches.to_string()]);
+    table.add_row(vec![format!("{} wins", name_a), r.together.wins.to_string(), r.against.wins.to_string()]);
+    table.add_row(vec![format!("{} win rate", name_a), pct(&r.together), pct(&r.against)]);
+    table.add_row(vec![format!("{} K/D/A", name_a), kda_text(&r.together.a), kda_text(&r.against.a)]);
+    table.add_row(vec![format!("{} K/D/A", name_b), kda_text(&r.together.b), kda_text(&r.against.b)]);
+    println!("\n== {} vs {} ==\n{}", name_a, name_b, table);
+    if r.unknown_team > 0 {
+        println!("{} shared matches without team data are not counted.", r.unknown_team);
+    }
+
+    for (title, side) in [("Same Team", &r.together), ("Opposing Teams", &r.against)] {
+        if side.pairings.is_empty() {
+            continue;
+        }
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec![name_a.to_string(), name_b.to_string(), "Matches".into(), format!("{} Win Rate", name_a)]);
+        for p in side.pairings.iter().take(pairings) {
+            table.add_row(vec![
+                heroes.name(p.a.hero_id),
+                heroes.name(p.b.hero_id),
+                p.matches.to_string(),
+                format!("{:.1}%", p.wins as f64 / p.matches as f64 * 100.0),
+            ]);
+        }
+        println!("\n== Hero Pairings: {} ==\n{}", title, table);
+    }
+
+    if !r.shared.is_empty() {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Date".to_string(), "Match".into(), "Side".into(), format!("{} Hero", name_a), "K/D/A".into(), format!("{} Hero", name_b), "K/D/A".into(), format!("{} Result", name_a)]);
+        for m in r.shared.iter().take(limit) {
+            let hero = |p: &PlayerMatch| p.hero_id.map(|h| heroes.name(h)).unwrap_or_else(|| "-".into());
+            let kda = |p: &PlayerMatch| format!("{}/{}/{}", p.kills.unwrap_or_default(), p.deaths.unwrap_or_default(), p.assists.unwrap_or_default());
+            let side = match (m.teams_known(), m.same_team) {
+                (false, _) => "-",
+                (true, true) => "together",
+                (true, false) => "against",
+            };
+            table.add_row(vec![
+                m.start_time.map(fmt_date).unwrap_or_else(|| "-".into()),
+                m.match_id.to_string(),
+                side.to_string(),
+                hero(&m.a),
+                kda(&m.a),
+                hero(&m.b),
+                kda(&m.b),
+                fmt_result(m.a.is_victory).to_string(),
+            ]);
+        }
+        println!("\n== Latest Shared Matches ==\n{}", table);
+    }
+    println!();
+}
+
+/// `mates`: most frequent teammates and opponents with the player's win rate in each case.
+pub fn print_mates_report(r: &MatesReport) {
+    let name = |a: &Associate| a.personaname.clone().unwrap_or_else(|| "(unknown)".into());
+    let pct = |v: Option<f64>| v.map(|w| format!("{:.1}%", w)).unwrap_or_else(|| "-".into());
+    let seen = |a: &Associate| a.last_seen.map(fmt_date).unwrap_or_else(|| "-".into());
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    let with = if r.party_only { "In Party" } else { "Same Team" };
+    table.set_header(vec!["Name", "Account ID", with, "Party", "Win Rate Together", "Last Seen"]);
+    for a in &r.mates {
+        table.add_row(vec![name(a), a.account_id.to_string(), a.with.to_string(), a.party.to_string(), pct(a.win_rate_with), seen(a)]);
+    }
+    println!("\n== Teammates ({} matches) ==\n{}", r.matches, table);
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Name", "Account ID", "Against", "Win Rate Against", "Last Seen"]);
+    for a in &r.opponents {
+        table.add_row(vec![name(a), a.account_id.to_string(), a.against.to_string(), pct(a.win_rate_against), seen(a)]);
+    }
+    println!("\n== Opponents ==\n{}\n", table);
+}
+
+fn performance_row(label: String, p: &Performance) -> Vec<String> {
+    let avg = |v: Option<f64>| v.map(|x| format!("{:.0}", x)).unwrap_or_else(|| "-".into());
+    vec![
+        label,
+        p.matches.to_string(),
+        p.wins.to_string(),
+        p.win_rate.map(|w| format!("{:.1}%", w)).unwrap_or_else(|| "-".into()),
+        kda_text(&p.kda),
+        avg(p.avg_networth),
+        avg(p.avg_damage),
+    ]
+}
+
+/// `party`: results by party size with a win-rate chart, then per party member.
+pub fn print_party_report(r: &PartyReport, style: ChartStyle) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Party", "Matches", "Wins", "Win Rate", "K/D/A", "Networth", "Damage"]);
+    for s in &r.by_size {
+        table.add_row(performance_row(s.size.label().to_string(), &s.performance));
+    }
+    println!("\n== By Party Size ({} matches) ==\n{}", r.matches, table);
+    if r.unclassified > 0 {
+        println!("{} matches without party data or a full scoreboard are not counted.", r.unclassified);
+    }
+
+    let bars: Vec<Bar> = r
+        .by_size
+        .iter()
+        .filter_map(|s| {
+            let rate = s.performance.win_rate?;
+            Some(Bar {
+                label: s.size.label().to_string(),
+                value: rate,
+                text: format!("{:.1}% ({})", rate, s.performance.matches),
+                tone: if rate >= 50.0 { Tone::Good } else { Tone::Bad },
+            })
+        })
+        .collect();
+    if !bars.is_empty() {
+        println!("\n== Win Rate by Party Size ==\n{}", bar_chart(&bars, Some(100.0), style));
+    }
+
+    if !r.members.is_empty() {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Member", "Matches", "Wins", "Win Rate", "K/D/A", "Networth", "Damage"]);
+        for m in &r.members {
+            let name = m.personaname.clone().unwrap_or_else(|| m.account_id.to_string());
+            table.add_row(performance_row(name, &m.performance));
+        }
+        println!("\n== Party Members ==\n{}", table);
+    }
+    println!();
+}
+
+/// `sessions`: recent sessions, streaks, tilt and decay, then time-of-day and weekday windows.
+pub fn print_sessions_report(r: &SessionsReport, style: ChartStyle) {
+    let offset = FixedOffset::east_opt(r.utc_offset_minutes * 60).unwrap_or(FixedOffset::east_opt(0).expect("zero offset"));
+    let local = |t: i64, fmt: &str| {
+        DateTime::<Utc>::from_timestamp(t, 0).map(|t| t.with_timezone(&offset).format(fmt).to_string()).unwrap_or_else(|| "-".into())
+    };
+    let header = |first: &'static str| vec![first, "Matches", "Wins", "Win Rate", "K/D/A", "Networth", "Damage"];
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Session", "Length", "Matches", "Wins", "Win Rate", "K/D/A", "Networth", "Damage"]);
+    for s in &r.sessions {
+        let mut row = performance_row(format!("{}-{}", local(s.start, "%Y-%m-%d %H:%M"), local(s.end, "%H:%M")), &s.performance);
+        row.insert(1, fmt_duration(((s.end - s.start) as i32).max(0)));
+        table.add_row(row);
+    }
+    let hours = r.utc_offset_minutes as f64 / 60.0;
+    println!("\n== Sessions ({} matches, gaps over {} min, UTC{:+}) ==\n{}", r.matches, r.gap_minutes, hours, table);
+
+    let streak = |s: &Option<Streak>| {
+        s.as_ref()
+            .map(|s| {
+                let what = match (s.won, s.length) {
+                    (true, 1) => "win",
+                    (true, _) => "wins",
+                    (false, 1) => "loss",
+                    (false, _) => "losses",
+                };
+                format!("{} {} ({} to {})", s.length, what, local(s.start, "%Y-%m-%d"), local(s.end, "%Y-%m-%d"))
+            })
+            .unwrap_or_else(|| "-".into())
+    };
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Streak", ""]);
+    table.add_row(vec!["Longest win streak".to_string(), streak(&r.streaks.longest_win)]);
+    table.add_row(vec!["Longest loss streak".to_string(), streak(&r.streaks.longest_loss)]);
+    table.add_row(vec!["Current".to_string(), streak(&r.streaks.current)]);
+    println!("\n== Streaks ==\n{}", table);
+
+    if !r.tilt.is_empty() {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(header("After Losses"));
+        for t in &r.tilt {
+            let label = if t.after_losses >= TILT_CAP { format!("{}+", TILT_CAP) } else { t.after_losses.to_string() };
+            table.add_row(performance_row(label, &t.performance));
+        }
+        println!("\n== Tilt: Next Match After Consecutive In-Session Losses ==\n{}", table);
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(header("Match #"));
+    for p in &r.by_position {
+        let label = if p.position >= POSITION_CAP { format!("{}+", POSITION_CAP) } else { p.position.to_string() };
+        table.add_row(performance_row(label, &p.performance));
+    }
+    println!("\n== By Position in Session ==\n{}", table);
+    if let Some(d) = &r.decay {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(header("Half"));
+        table.add_row(performance_row("First".into(), &d.first_half));
+        table.add_row(performance_row("Second".into(), &d.second_half));
+        println!("\n== Decay Across {} Long Sessions ==\n{}", d.sessions, table);
+    }
+
+    for (title, windows, best, worst) in [
+        ("Time of Day", &r.by_time, &r.best_time, &r.worst_time),
+        ("Day of Week", &r.by_weekday, &r.best_weekday, &r.worst_weekday),
+    ] {
+        let bars: Vec<Bar> = windows
+            .iter()
+            .filter_map(|w| {
+                let rate = w.performance.win_rate?;
+                Some(Bar {
+                    label: w.label.clone(),
+                    value: rate,
+                    text: format!("{:.1}% ({})", rate, w.performance.matches),
+                    tone: if rate >= 50.0 { Tone::Good } else { Tone::Bad },
+                })
+            })
+            .collect();
+        if bars.is_empty() {
+            continue;
+        }
+        println!("\n== Win Rate by {} ==\n{}", title, bar_chart(&bars, Some(100.0), style));
+        if let Some(b) = best {
+            println!("Best: {}{}", b, worst.as_ref().map(|w| format!(" · Worst: {}", w)).unwrap_or_default());
+        }
+    }
+    println!();
+}
+
+/// `patches`: per-hero table for each patch, newest first, with changes since the previous patch.
+pub fn print_patch_report(r: &PatchReport, heroes: &HeroCatalog) {
+    let signed = |v: Option<f64>, digits: usize| v.map(|d| format!(" ({:+.*})", digits, d)).unwrap_or_default();
+    for p in r.patches.iter().rev() {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Hero", "Matches", "Win Rate", "Pick Rate", "KDA"]);
+        for h in &p.heroes {
+            let d = h.delta.as_ref();
+            table.add_row(vec![
+                heroes.name(h.hero_id),
+                h.matches.to_string(),
+                format!("{}{}", h.win_rate.map(|w| format!("{:.1}%", w)).unwrap_or_else(|| "-".into()), signed(d.and_then(|d| d.win_rate), 1)),
+                format!("{:.1}%{}", h.pick_rate, signed(d.map(|d| d.pick_rate), 1)),
+                format!("{:.2}{}", h.kda, signed(d.map(|d| d.kda), 2)),
+            ]);
+        }
+        let since = p.first_seen.map(|t| format!(", since {}", fmt_date(t))).unwrap_or_default();
+        println!("\n== Patch {} ({} matches{}) ==\n{}", p.patch, p.matches, since, table);
+    }
+    if r.unknown_patch > 0 {
+        println!("{} rows without a patch version are not counted.", r.unknown_patch);
+    }
+    println!();
+}
+
+/// `meta`: per-hero pick rate, win rate with its confidence interval, KDA, damage and networth.
+pub fn print_meta_report(r: &MetaReport, heroes: &HeroCatalog) {
+    let ci = |i: Option<Interval>| i.map(|i| format!("{:.1}-{:.1}%", i.low, i.high)).unwrap_or_else(|| "-".into());
+    let avg = |v: Option<f64>| v.map(|v| format!("{:.0}", v)).unwrap_or_else(|| "-".into());
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Hero", "Role", "Picks", "Pick Rate", "Win Rate", "Win 95% CI", "K/D/A", "KDA", "Damage", "Networth", "Sample"]);
+    for h in &r.heroes {
+        table.add_row(vec![
+            heroes.name(h.hero_id),
+            heroes.role(h.hero_id).unwrap_or("-").to_string(),
+            h.picks.to_string(),
+            format!("{:.1}%", h.pick_rate),
+            h.win_rate.map(|w| format!("{:.1}%", w)).unwrap_or_else(|| "-".into()),
+            ci(h.win_rate_ci),
+            format!("{:.1}/{:.1}/{:.1}", h.avg_kills, h.avg_deaths, h.avg_assists),
+            format!("{:.2}", h.kda),
+            avg(h.avg_damage),
+            avg(h.avg_networth),
+            if h.low_sample { "low".to_string() } else { "ok".to_string() },
+        ]);
+    }
+    let f = &r.filter;
+    let mut scope: Vec<String> = Vec::new();
+    match (f.min_badge, f.max_badge) {
+        (None, None) => {}
+        (lo, hi) => {
+            let badge = |b: Option<i32>| match b {
+                Some(b) => Badge::from_badge(b).map(|x| x.to_string()).unwrap_or_else(|| b.to_string()),
+                None => "any".into(),
+            };
+            scope.push(format!("badge {} to {}", badge(lo), badge(hi)));
+        }
+    }
+    scope.extend(f.region.iter().map(|r| format!("region {}", r)));
+    scope.extend(f.patch.iter().map(|p| format!("patch {}", p)));
+    let scope = if scope.is_empty() { String::new() } else { format!(", {}", scope.join(", ")) };
+    println!("\n== Hero Meta ({} matches, {} picks{}) ==\n{}", r.matches, r.picks, scope, table);
+    let low = r.heroes.iter().filter(|h| h.low_sample).count();
+    if low > 0 {
+        println!("{} heroes have fewer than {} picks; their rates are unreliable.", low, r.min_sample);
+    }
+    println!();
+}
+
+/// `heroes synergy`: the hero's win rate with and against each other hero, next to the expected rate.
+pub fn print_hero_synergy(hero_id: i32, h: &HeroSynergy, heroes: &HeroCatalog) {
+    let name = heroes.name(hero_id);
+    if let Some(b) = &h.baseline {
+        let wr = b.win_rate.map(|w| format!("{:.1}%", w)).unwrap_or_else(|| "-".into());
+        println!("\n{}: {} matches, {} win rate", name, b.matches, wr);
+    }
+    let pct = |v: Option<f64>| v.map(|v| format!("{:.1}%", v)).unwrap_or_else(|| "-".into());
+    let pts = |v: Option<f64>| v.map(|v| format!("{:+.1}", v)).unwrap_or_else(|| "-".into());
+    for (title, pairs) in [(format!("{} With", name), &h.with), (format!("{} Against", name), &h.against)] {
+        if pairs.is_empty() {
+            println!("\nNo hero seen {} or more times for {}.", h.min_matches, title.to_lowercase());
+            continue;
+        }
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Hero", "Matches", "Win Rate", "Expected", "Delta"]);
+        for p in pairs {
+            let s = &p.stats;
+            table.add_row(vec![
+                heroes.name(p.other.hero_id),
+                s.matches.to_string(),
+                format!("{:.1}%", s.win_rate),
+                pct(s.expected),
+                pts(s.delta),
+            ]);
+        }
+        println!("\n== {} ==\n{}", title, table);
+    }
+    println!();
+}
+
+/// JSON shape of `matches objectives`: per-objective flags for both teams.
+#[derive(Debug, Serialize)]
+pub struct ObjectivesPayload {
+    pub match_id: i64,
+    pub team0: Vec<ObjectiveStatus>,
+    pub team1: Vec<ObjectiveStatus>,
+}
+
+impl ObjectivesPayload {
+    pub fn new(match_id: i64, o: &MatchObjectives) -> Self {
+        Self { match_id, team0: o.team0.statuses(), team1: o.team1.statuses() }
+    }
+}
+
+pub fn print_match_objectives(match_id: i64, o: &MatchObjectives) {
+    let mark = |destroyed: bool| if destroyed { "destroyed" } else { "standing" };
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Objective", "Team 0", "Team 1"]);
+    for obj in OBJECTIVES {
+        table.add_row(vec![obj.label(), mark(o.team0.destroyed(obj)).to_string(), mark(o.team1.destroyed(obj)).to_string()]);
+    }
+    let total = OBJECTIVES.len();
+    table.add_row(vec![
+        "Lost".to_string(),
+        format!("{}/{}", o.team0.destroyed_total(), total),
+        format!("{}/{}", o.team1.destroyed_total(), total),
+    ]);
+    println!("\n== Match {} Objectives ==\n{}\n", match_id, table);
+}
+
+/// JSON shape of `matches show`: the match plus decoded objectives and performance scores when known.
+#[derive(Debug, Serialize)]
+pub struct MatchDetailPayload<'a> {
+    #[serde(flatten)]
+    pub meta: &'a MatchMeta,
+    pub objectives: Option<ObjectivesPayload>,
+    pub scores: Vec<PlayerScore>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlayerScore {
+    pub account_id: i32,
+    pub hero_id: Option<i32>,
+    #[serde(flatten)]
+    pub score: PerformanceScore,
+}
+
+impl PlayerScore {
+    /// Scores of the match's players that have one.
+    pub fn of(meta: &MatchMeta, scores: &Scores) -> Vec<Self> {
+        meta.players
+            .iter()
+            .flatten()
+            .filter_map(|p| Some(Self { account_id: p.account_id, hero_id: p.hero_id, score: scores.get(meta.match_id, p.account_id as u32)? }))
+            .collect()
+    }
+}
+
+fn score_text(s: Option<PerformanceScore>) -> String {
+    s.map(|s| format!("{:.0} (p{:.0})", s.score, s.percentile)).unwrap_or_else(|| "-".into())
+}
+
+pub fn print_match_summary(meta: &MatchMeta) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Field", "Value"]);
+    table.add_row(vec!["Match ID".to_string(), meta.match_id.to_string()]);
+    table.add_row(vec!["Winner".to_string(), meta.winner_team.map(|t| t.to_string()).unwrap_or_else(|| "-".into())]);
+    table.add_row(vec!["Duration".to_string(), meta.duration_s.map(fmt_duration).unwrap_or_else(|| "-".into())]);
+    let started = meta.start_time.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0));
+    table.add_row(vec!["Started".to_string(), started.map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()).unwrap_or_else(|| "-".into())]);
+    table.add_row(vec!["Region".to_string(), meta.region.clone().unwrap_or_else(|| "-".into())]);
+    table.add_row(vec!["Patch".to_string(), meta.patch_version.clone().unwrap_or_else(|| "-".into())]);
+    let badge = meta.average_badge.and_then(Badge::from_badge).map(|b| b.to_string());
+    table.add_row(vec!["Average Badge".to_string(), badge.unwrap_or_else(|| "-".into())]);
+    println!("\n== Match {} ==\n{}\n", meta.match_id, table);
+}
+
+/// One table per team, richest player first.
+pub fn print_scoreboard(meta: &MatchMeta, heroes: &HeroCatalog, scores: &Scores) {
+    let players = meta.players.as_deref().unwrap_or_default();
+    let mut teams: Vec<Option<Team>> = players.iter().map(|p| p.team).collect();
+    teams.sort_by_key(|t| t.map(Team::id).unwrap_or(i32::MAX));
+    teams.dedup();
+
+    for team in teams {
+        let mut rows: Vec<&PlayerInMatch> = players.iter().filter(|p| p.team == team).collect();
+        rows.sort_by_key(|p| std::cmp::Reverse(p.networth.unwrap_or_default()));
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Account", "Hero", "K/D/A", "Networth", "Damage", "Last Hits", "Accuracy", "Score"]);
+        for p in rows {
+            let kda = format!(
+                "{}/{}/{}",
+                p.kills.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+                p.deaths.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+                p.assists.map(|v| v.to_string()).unwrap_or_else(|| "-".into())
+            );
+            table.add_row(vec![
+                p.account_id.to_string(),
+                p.hero_id.map(|h| heroes.name(h)).unwrap_or_else(|| "-".into()),
+                kda,
+                p.networth.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+                p.damage.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+                p.last_hits.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+                p.accuracy.map(|a| format!("{:.1}%", a * 100.0)).unwrap_or_else(|| "-".into()),
+                score_text(scores.get(meta.match_id, p.account_id as u32)),
+            ]);
+        }
+        let title = match team {
+            Some(t) if meta.winner_team == Some(t) => format!("{} (winner)", t),
+            Some(t) => t.to_string(),
+            None => "unknown team".to_string(),
+        };
+        println!("== {} ==\n{}\n", title, table);
+    }
+}
+
+pub fn print_match_list(page: &Page<PlayerMatch>, heroes: &HeroCatalog, style: ChartStyle) {
+    // without any stored lobby scores the column would be all dashes
+    let scored = page.items.iter().any(|m| m.score.is_some());
+    let mut header = vec!["Date", "Match", "Hero", "Mode", "Result", "K/D/A", "Networth", "Damage", "Last Hits", "Accuracy", "Duration", "Score"];
+    if !scored {
+        header.pop();
+    }
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(header);
+    for m in &page.items {
+        let mut row = match_row(m, heroes);
+        if !scored {
+            row.pop();
+        }
+        table.add_row(row);
+    }
+    println!("\n== Matches ==\n{}", table);
+    println!("Page {}/{} ({} matches)", page.page, page.pages, page.total);
+
+    let mut chrono_order: Vec<&PlayerMatch> = page.items.iter().collect();
+    chrono_order.sort_by_key(|m| m.start_time);
+    let kda: Vec<f64> = chrono_order.iter().filter_map(|m| m.kda()).collect();
+    let results: Vec<Option<bool>> = chrono_order.iter().map(|m| m.is_victory).collect();
+    println!("KDA     {}", sparkline_fit(&kda, style.width.saturating_sub(8)));
+    println!("Results {}\n", result_strip(&results, style));
+}
+
+/// All rows as CSV with the same columns as the table, plus raw IDs.
+pub fn write_match_csv(rows: &[PlayerMatch], heroes: &HeroCatalog, out: &mut impl std::io::Write) -> std::io::Result<()> {
+    writeln!(out, "date,match_id,hero_id,hero,mode,result,kills,deaths,assists,networth,damage,last_hits,accuracy,duration_s,score,percentile")?;
+    for m in rows {
+        let opt = |v: Option<String>| v.unwrap_or_default();
+        let fields = [
+            opt(m.start_time.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0)).map(|t| t.to_rfc3339())),
+            m.match_id.to_string(),
+            opt(m.hero_id.map(|h| h.to_string())),
+            opt(m.hero_id.map(|h| heroes.name(h))),
+            opt(m.match_mode.map(|md| md.name().to_string())),
+            fmt_result(m.is_victory).to_string(),
+            opt(m.kills.map(|v| v.to_string())),
+            opt(m.deaths.map(|v| v.to_string())),
+            opt(m.assists.map(|v| v.to_string())),
+            opt(m.networth.map(|v| v.to_string())),
+            opt(m.damage.map(|v| v.to_string())),
+            opt(m.last_hits.map(|v| v.to_string())),
+            opt(m.accuracy.map(|v| format!("{:.4}", v))),
+            opt(m.duration_s.map(|v| v.to_string())),
+            opt(m.score.map(|s| format!("{:.1}", s.score))),
+            opt(m.score.map(|s| format!("{:.1}", s.percentile))),
+        ];
+        writeln!(out, "{}", fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","))?;
+    }
+    Ok(())
+}
+
+fn match_row(m: &PlayerMatch, heroes: &HeroCatalog) -> Vec<String> {
+    let dash = || "-".to_string();
+    vec![
+        m.start_time.and_then(|t| DateTime::<Utc>::from_timestamp(t, 0)).map(|t| t.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_else(dash),
+        m.match_id.to_string(),
+        m.hero_id.map(|h| heroes.name(h)).unwrap_or_else(dash),
+        m.match_mode.map(|md| md.name().to_string()).unwrap_or_else(dash),
+        fmt_result(m.is_victory).to_string(),
+        format!(
+            "{}/{}/{}",
+            m.kills.map(|v| v.to_string()).unwrap_or_else(dash),
+            m.deaths.map(|v| v.to_string()).unwrap_or_else(dash),
+            m.assists.map(|v| v.to_string()).unwrap_or_else(dash)
+        ),
+        m.networth.map(|v| v.to_string()).unwrap_or_else(dash),
+        m.damage.map(|v| v.to_string()).unwrap_or_else(dash),
+        m.last_hits.map(|v| v.to_string()).unwrap_or_else(dash),
+        m.accuracy.map(|a| format!("{:.1}%", a * 100.0)).unwrap_or_else(dash),
+        m.duration_s.map(fmt_duration).unwrap_or_else(dash),
+        score_text(m.score),
+    ]
+}
+
+pub fn print_mmr_report(report: &MmrReport, limit: usize, style: ChartStyle) {
+    let badge_at = |p: Option<&crate::mmr::MmrPoint>| {
+        p.map(|p| {
+            let badge = p.badge.map(|b| b.to_string()).unwrap_or_else(|| format!("rank {}", p.rank));
+            format!("{} ({:.1}) on {}", badge, p.player_score, fmt_date(p.start_time as i64))
+        })
+        .unwrap_or_else(|| "-".into())
+    };
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Stat", "Value"]);
+    table.add_row(vec!["Snapshots".to_string(), report.timeline.len().to_string()]);
+    table.add_row(vec!["First".to_string(), badge_at(report.first())]);
+    table.add_row(vec!["Latest".to_string(), badge_at(report.latest())]);
+    table.add_row(vec!["Net Change".to_string(), format!("{:+.1}", report.net_change)]);
+    table.add_row(vec!["Peak".to_string(), badge_at(report.peak.as_ref())]);
+    table.add_row(vec!["Lowest".to_string(), badge_at(report.lowest.as_ref())]);
+    println!("\n== MMR ==\n{}", table);
+
+    let ranks: Vec<f64> = report.timeline.iter().map(|p| p.rank as f64).collect();
+    println!("Rank trend: {}\n", sparkline_fit(&ranks, style.width.saturating_sub(12)));
+    let scores: Vec<f64> = report.timeline.iter().map(|p| p.player_score).collect();
+    println!("== Score Distribution ==\n{}", histogram(&scores, 8.min(scores.len()), style));
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Date".to_string(), "Match".to_string(), "Badge".to_string(), "Score".to_string(), "Change".to_string(), format!("Avg ({})", report.window)]);
+    let skip = report.timeline.len().saturating_sub(limit);
+    for p in report.timeline.iter().skip(skip) {
+        table.add_row(vec![
+            fmt_date(p.start_time as i64),
+            p.match_id.to_string(),
+            p.badge.map(|b| b.to_string()).unwrap_or_else(|| "-".into()),
+            format!("{:.1}", p.player_score),
+            p.delta.map(|d| format!("{:+.1}", d)).unwrap_or_else(|| "-".into()),
+            format!("{:.1}", p.rolling_avg),
+        ]);
+    }
+    println!("== Timeline (last {}) ==\n{}\n", report.timeline.len() - skip, table);
+}
+
+fn fmt_date(secs: i64) -> String {
+    DateTime::<Utc>::from_timestamp(secs, 0).map(|t| t.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "-".into())
+}
+
+fn fmt_result(victory: Option<bool>) -> &'static str {
+    match victory {
+        Some(true) => "win",
+        Some(false) => "loss",
+        None => "-",
+    }
+}
+
+/// The matrix as CSV: a header of hero names, then one row per hero with its delta against
+/// each column hero in percentage points (blank below the sample threshold).
+pub fn write_matrix_csv(m: &Matrix, heroes: &HeroCatalog, out: &mut impl std::io::Write) -> std::io::Result<()> {
+    let names: Vec<String> = m.heroes.iter().map(|h| csv_field(&heroes.name(h.hero_id))).collect();
+    writeln!(out, "hero,{}", names.join(","))?;
+    for (name, row) in names.iter().zip(&m.cells) {
+        let cells: Vec<String> = row.iter().map(|c| c.and_then(|c| c.delta).map(|d| format!("{:.1}", d)).unwrap_or_default()).collect();
+        writeln!(out, "{},{}", name, cells.join(","))?;
+    }
+    Ok(())
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn fmt_duration(secs: i32) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
 }
 
 fn fmt_opt_f(v: Option<f64>) -> String { v.map(|x| format!("{:.2}", x)).unwrap_or_else(|| "-".into()) }
 
+// ============ Charts ============
+
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Width and colour settings shared by every chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChartStyle {
+    /// Total columns a chart line may use
+    pub width: usize,
+    pub color: bool,
+}
+
+impl ChartStyle {
+    /// Terminal width (falling back to `COLUMNS`, then 80); colour unless `--no-color`,
+    /// `NO_COLOR` is set or stdout is not a terminal.
+    pub fn detect(no_color: bool) -> Self {
+        let probe = Table::new();
+        let width = probe
+            .width()
+            .map(usize::from)
+            .or_else(|| std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()))
+            .unwrap_or(80);
+        let color = !no_color && std::env::var_os("NO_COLOR").is_none() && probe.is_tty();
+        Self { width, color }
+    }
+
+    fn paint(&self, text: &str, tone: Tone) -> String {
+        let code = match tone {
+            Tone::Neutral => return text.to_string(),
+            Tone::Good => 32,
+            Tone::Bad => 31,
+        };
+        if self.color { format!("\x1b[{}m{}\x1b[0m", code, text) } else { text.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    Neutral,
+    Good,
+    Bad,
+}
+
+/// One row of a horizontal bar chart.
+#[derive(Debug, Clone)]
+pub struct Bar {
+    pub label: String,
+    pub value: f64,
+    /// Shown after the bar, e.g. `"54.2%"`
+    pub text: String,
+    pub tone: Tone,
+}
+
+/// Unicode block sparkline, one character per value, scaled between the min and max.
+pub fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if max > min {
+                BLOCKS[(((v - min) / (max - min)) * (BLOCKS.len() - 1) as f64).round() as usize]
+            } else {
+                BLOCKS[BLOCKS.len() / 2]
+            }
+        })
+        .collect()
+}
+
+/// Sparkline of at most `width` characters; longer series are averaged into equal buckets.
+pub fn sparkline_fit(values: &[f64], width: usize) -> String {
+    let width = width.max(1);
+    if values.len() <= width {
+        return sparkline(values);
+    }
+    let buckets: Vec<f64> = (0..width)
+        .map(|i| {
+            let chunk = &values[i * values.len() / width..(i + 1) * values.len() / width];
+            chunk.iter().sum::<f64>() / chunk.len().max(1) as f64
+        })
+        .collect();
+    sparkline(&buckets)
+}
+
+/// Horizontal bars scaled to the largest value (or `max`), fitted to the style's width.
+pub fn bar_chart(bars: &[Bar], max: Option<f64>, style: ChartStyle) -> String {
+    let label_w = bars.iter().map(|b| b.label.chars().count()).max().unwrap_or(0);
+    let text_w = bars.iter().map(|b| b.text.chars().count()).max().unwrap_or(0);
+    let bar_w = style.width.saturating_sub(label_w + text_w + 2).max(10);
+    let max = max.unwrap_or_else(|| bars.iter().map(|b| b.value).fold(0.0, f64::max));
+
+    let mut out = String::new();
+    for b in bars {
+        let filled = if max > 0.0 { ((b.value / max).clamp(0.0, 1.0) * bar_w as f64).round() as usize } else { 0 };
+        let bar = format!("{}{}", "█".repeat(filled), " ".repeat(bar_w - filled));
+        out.push_str(&format!("{:<label_w$} {} {:>text_w$}\n", b.label, style.paint(&bar, b.tone), b.text));
+    }
+    out
+}
+
+/// Distribution of `values` across `bins` equal-width buckets, one bar per bucket.
+pub fn histogram(values: &[f64], bins: usize, style: ChartStyle) -> String {
+    if values.is_empty() {
+        return String::new();
+    }
+    let bins = bins.max(1);
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let step = if max > min { (max - min) / bins as f64 } else { 1.0 };
+    let mut counts = vec![0usize; bins];
+    for v in values {
+        let i = (((v - min) / step) as usize).min(bins - 1);
+        counts[i] += 1;
+    }
+    let bars: Vec<Bar> = counts
+        .iter()
+        .enumerate()
+        .map(|(i, &n)| Bar {
+            label: format!("{:.1}–{:.1}", min + step * i as f64, min + step * (i + 1) as f64),
+            value: n as f64,
+            text: n.to_string(),
+            tone: Tone::Neutral,
+        })
+        .collect();
+    bar_chart(&bars, None, style)
+}
+
+/// `W`/`L` strip, oldest first, coloured by result.
+pub fn result_strip(results: &[Option<bool>], style: ChartStyle) -> String {
+    results
+        .iter()
+        .map(|r| match r {
+            Some(true) => style.paint("W", Tone::Good),
+            Some(false) => style.paint("L", Tone::Bad),
+            None => "·".to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,67 @@
+use deadlock_cli::batch::{self, BatchInput};
+use deadlock_cli::fixture::FixtureApi;
+use deadlock_cli::models;
+
+fn profile(account_id: i32, name: &str) -> models::SteamProfile {
+    models::SteamProfile {
+        account_id,
+        personaname: name.into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reads_identifiers_skipping_blanks_and_comments() {
+    let raw = "# team roster\n76561197960435530\n\n  [U:1:22202]  # captain\nSTEAM_0:1:11101\n";
+    let inputs = batch::read_inputs(raw.as_bytes()).unwrap();
+    assert_eq!(
+        inputs,
+        vec![
+            BatchInput { line: 2, input: "76561197960435530".into() },
+            BatchInput { line: 4, input: "[U:1:22202]".into() },
+            BatchInput { line: 5, input: "STEAM_0:1:11101".into() },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn resolves_offline_formats_in_input_order() {
+    let inputs = batch::read_inputs("76561197960287930\n[U:1:22202]\nSTEAM_0:1:11101\n388674065\n".as_bytes()).unwrap();
+    let resolved = batch::resolve_all(&inputs, &reqwest::Client::new(), None, 2).await;
+    assert_eq!(resolved, vec![Ok(22202), Ok(22202), Ok(22203), Ok(388674065)]);
+}
+
+#[tokio::test]
+async fn fetches_chunks_and_reports_each_line() {
+    let api = FixtureApi::new()
+        .with_profile(profile(1, "one"))
+        .with_profile(profile(2, "two"))
+        .with_profile(profile(3, "three"))
+        .with_hero_stats(models::HeroStats { account_id: 2, hero_id: 6, matches_played: Some(4), wins: Some(1), ..Default::default() })
+        .with_mmr(models::MMRHistory {
+            account_id: 3,
+            match_id: 9,
+            start_time: 1_700_000_000,
+            player_score: 74.0,
+            rank: 74,
+            division: 7,
+            division_tier: 4,
+        });
+    let inputs: Vec<BatchInput> =
+        ["1", "2", "nobody", "3", "4", "2"].iter().enumerate().map(|(i, s)| BatchInput { line: i + 1, input: s.to_string() }).collect();
+    let resolved = vec![Ok(1), Ok(2), Err("could not resolve".to_string()), Ok(3), Ok(4), Ok(2)];
+
+    let records = batch::fetch_all(&api, &inputs, resolved, 2).await;
+    assert_eq!(records.len(), 6);
+    assert_eq!(records.iter().filter(|r| r.is_ok()).count(), 4);
+    assert_eq!(records[1].player.as_ref().unwrap().hero_stats.len(), 1);
+    assert_eq!(records[3].player.as_ref().unwrap().latest_mmr.as_ref().unwrap().rank, 74);
+    assert_eq!(records[2].error.as_deref(), Some("could not resolve"));
+    assert_eq!(records[4].account_id, Some(4));
+    assert!(records[4].error.as_deref().unwrap().contains("not found"));
+    assert_eq!(records[5].player.as_ref().unwrap().profile.personaname, "two");
+
+    let line = serde_json::to_value(&records[2]).unwrap();
+    assert_eq!(line["line"], 3);
+    assert!(line.get("player").is_none());
+}
@@ -0,0 +1,102 @@
+use deadlock_cli::cache::{CacheEntry, CacheMode, ResponseCache};
+use deadlock_cli::deadlock::{DeadlockApi, DeadlockClient, DeadlockError};
+use httpmock::prelude::*;
+use std::path::{Path, PathBuf};
+
+fn temp_cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("deadlock-cli-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Age every stored entry past any TTL.
+fn backdate_entries(dir: &Path) {
+    for file in std::fs::read_dir(dir).unwrap() {
+        let path = file.unwrap().path();
+        let mut entry: CacheEntry = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        entry.stored_at = 0;
+        std::fs::write(&path, serde_json::to_vec(&entry).unwrap()).unwrap();
+    }
+}
+
+fn mmr_body() -> serde_json::Value {
+    serde_json::json!([{
+        "account_id": 1, "match_id": 10, "start_time": 1_700_000_000,
+        "player_score": 5.0, "rank": 41, "division": 4, "division_tier": 1
+    }])
+}
+
+#[tokio::test]
+async fn fresh_entry_is_served_from_disk() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/v1/players/mmr").query_param("account_ids", "1");
+        then.status(200).json_body_obj(&mmr_body());
+    });
+
+    let dir = temp_cache_dir("fresh");
+    let dl = DeadlockClient::new(server.base_url(), None, reqwest::Client::new())
+        .with_cache(ResponseCache::new(&dir, CacheMode::Normal));
+
+    assert_eq!(dl.get_mmr(&[1]).await.unwrap()[0].match_id, 10);
+    assert_eq!(dl.get_mmr(&[1]).await.unwrap()[0].match_id, 10);
+    m.assert_hits(1);
+}
+
+#[tokio::test]
+async fn stale_entry_is_revalidated_with_etag() {
+    let server = MockServer::start();
+    let mut first = server.mock(|when, then| {
+        when.method(GET).path("/v1/players/mmr");
+        then.status(200).header("ETag", "\"v1\"").json_body_obj(&mmr_body());
+    });
+
+    let dir = temp_cache_dir("revalidate");
+    let dl = DeadlockClient::new(server.base_url(), None, reqwest::Client::new())
+        .with_cache(ResponseCache::new(&dir, CacheMode::Normal));
+    dl.get_mmr(&[1]).await.unwrap();
+    first.assert_hits(1);
+    first.delete();
+    backdate_entries(&dir);
+
+    let not_modified = server.mock(|when, then| {
+        when.method(GET).path("/v1/players/mmr").header("If-None-Match", "\"v1\"");
+        then.status(304);
+    });
+    let out = dl.get_mmr(&[1]).await.unwrap();
+    not_modified.assert_hits(1);
+    assert_eq!(out[0].rank, 41);
+}
+
+#[tokio::test]
+async fn cache_only_misses_without_network() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/v1/players/mmr");
+        then.status(200).json_body_obj(&mmr_body());
+    });
+
+    let dir = temp_cache_dir("only");
+    let dl = DeadlockClient::new(server.base_url(), None, reqwest::Client::new())
+        .with_cache(ResponseCache::new(&dir, CacheMode::Only));
+    let err = dl.get_mmr(&[1]).await.err().unwrap();
+    assert!(matches!(err, DeadlockError::CacheMiss(_)));
+    m.assert_hits(0);
+}
+
+#[tokio::test]
+async fn no_cache_always_hits_network() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/v1/players/mmr");
+        then.status(200).json_body_obj(&mmr_body());
+    });
+
+    let dir = temp_cache_dir("bypass");
+    let dl = DeadlockClient::new(server.base_url(), None, reqwest::Client::new())
+        .with_cache(ResponseCache::new(&dir, CacheMode::Bypass));
+    dl.get_mmr(&[1]).await.unwrap();
+    dl.get_mmr(&[1]).await.unwrap();
+    m.assert_hits(2);
+    assert!(!dir.exists());
+}
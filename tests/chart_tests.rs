@@ -0,0 +1,39 @@
+use deadlock_cli::ui::{self, Bar, ChartStyle, Tone};
+
+const PLAIN: ChartStyle = ChartStyle { width: 30, color: false };
+
+fn bar(label: &str, value: f64, tone: Tone) -> Bar {
+    Bar { label: label.into(), value, text: format!("{}%", value), tone }
+}
+
+#[test]
+fn bar_chart_fits_width_and_scales() {
+    let out = ui::bar_chart(&[bar("Haze", 50.0, Tone::Good), bar("Abrams", 100.0, Tone::Bad)], Some(100.0), PLAIN);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|l| l.chars().count() == 30));
+    // label 6 + text 4 + 2 separators leaves 18 columns of bar
+    assert_eq!(lines[1].matches('█').count(), 18);
+    assert_eq!(lines[0].matches('█').count(), 9);
+    assert!(!out.contains('\x1b'));
+
+    let colored = ui::bar_chart(&[bar("Haze", 50.0, Tone::Good)], None, ChartStyle { color: true, ..PLAIN });
+    assert!(colored.contains("\x1b[32m"));
+}
+
+#[test]
+fn histogram_counts_into_bins() {
+    let out = ui::histogram(&[1.0, 1.5, 2.0, 9.0, 10.0], 3, PLAIN);
+    let counts: Vec<&str> = out.lines().map(|l| l.rsplit(' ').next().unwrap()).collect();
+    assert_eq!(counts, vec!["3", "0", "2"]);
+    assert!(ui::histogram(&[], 3, PLAIN).is_empty());
+}
+
+#[test]
+fn sparkline_fit_downsamples_long_series() {
+    let values: Vec<f64> = (0..100).map(f64::from).collect();
+    let line = ui::sparkline_fit(&values, 10);
+    assert_eq!(line.chars().count(), 10);
+    assert!(line.starts_with('▁') && line.ends_with('█'));
+    assert_eq!(ui::result_strip(&[Some(true), Some(false), None], PLAIN), "WL·");
+}
@@ -0,0 +1,67 @@
+use deadlock_cli::compare::{self, Comparison, Metric, PlayerSummary};
+use deadlock_cli::models::{self, CombinedPayload};
+
+fn player(account_id: u32, rank: i32, heroes: Vec<models::HeroStats>) -> CombinedPayload {
+    CombinedPayload {
+        steamid64: deadlock_cli::steam::account_id_to_steamid64(account_id),
+        account_id,
+        profile: models::SteamProfile {
+            account_id: account_id as i32,
+            personaname: format!("p{}", account_id),
+            ..Default::default()
+        },
+        latest_mmr: Some(models::MMRHistory {
+            account_id: account_id as i32,
+            match_id: 1,
+            start_time: 1_700_000_000,
+            player_score: rank as f64,
+            rank,
+            division: rank / 10,
+            division_tier: rank % 10,
+        }),
+        hero_stats: heroes,
+    }
+}
+
+fn hero(hero_id: i32, matches: i64, wins: i64, minutes: i64, kpm: f64, dpm: f64) -> models::HeroStats {
+    models::HeroStats {
+        hero_id,
+        matches_played: Some(matches),
+        wins: Some(wins),
+        time_played: Some(minutes * 60),
+        kills_per_min: Some(kpm),
+        deaths_per_min: Some(dpm),
+        accuracy: Some(0.5),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn summary_weights_rates_by_time_played() {
+    let p = player(1, 74, vec![hero(6, 10, 6, 300, 0.4, 0.2), hero(7, 30, 12, 100, 0.8, 0.1), hero(8, 0, 0, 0, 9.0, 9.0)]);
+    let s = PlayerSummary::from_payload(&p);
+    assert_eq!((s.matches, s.wins), (40, 18));
+    assert!((s.win_rate.unwrap() - 45.0).abs() < 1e-9);
+    assert!((s.kills_per_min.unwrap() - 0.5).abs() < 1e-9);
+    assert!((s.accuracy.unwrap() - 0.5).abs() < 1e-9);
+    assert_eq!(s.top_heroes.iter().map(|h| h.hero_id).collect::<Vec<_>>(), vec![7, 6]);
+    assert!(s.damage_per_min.is_none());
+}
+
+#[test]
+fn best_prefers_fewer_deaths_and_shares_ties() {
+    let a = player(1, 74, vec![hero(6, 10, 5, 100, 0.5, 0.3)]);
+    let b = player(2, 74, vec![hero(6, 10, 7, 100, 0.4, 0.1)]);
+    let c = Comparison::new(&[&a, &b]);
+
+    assert!(c.is_best(Metric::Rank, 1) && c.is_best(Metric::Rank, 2));
+    assert!(c.is_best(Metric::KillsPerMin, 1) && !c.is_best(Metric::KillsPerMin, 2));
+    assert!(c.is_best(Metric::DeathsPerMin, 2) && !c.is_best(Metric::DeathsPerMin, 1));
+    assert!(c.is_best(Metric::WinRate, 2));
+    assert!(compare::best_indices(&c.players, Metric::DamagePerMin).is_empty());
+
+    let v = serde_json::to_value(&c).unwrap();
+    assert_eq!(v["players"].as_array().unwrap().len(), 2);
+    let deaths = v["best"].as_array().unwrap().iter().find(|l| l["metric"] == "deaths_per_min").unwrap();
+    assert_eq!(deaths["account_ids"], serde_json::json!([2]));
+}
@@ -23,7 +23,7 @@ async fn ingest_roundtrip() {
         avatarfull: "".into(),
         countrycode: None,
         realname: None,
-        last_updated: Some((Utc::now().timestamp() as i64).to_string()),
+        last_updated: Some(Utc::now().timestamp().to_string()),
     };
 
     let latest_mmr = models::MMRHistory {
@@ -41,7 +41,7 @@ async fn ingest_roundtrip() {
         hero_id: 1,
         matches_played: Some(10),
         wins: Some(6),
-        last_played: Some(Utc::now().timestamp() as i64),
+        last_played: Some(Utc::now().timestamp()),
         time_played: Some(1000),
         ending_level: Some(29.5),
         kills: Some(20),
@@ -59,7 +59,7 @@ async fn ingest_roundtrip() {
         crit_shot_rate: Some(0.1),
     };
 
-    let combined = ui::CombinedPayload {
+    let combined = models::CombinedPayload {
         steamid64: steamid64.clone(),
         account_id,
         profile: profile.clone(),
@@ -73,6 +73,9 @@ async fn ingest_roundtrip() {
     assert_eq!(res1.heroes_upserted, 1);
     assert_eq!(res1.hero_history_added, 1);
 
+    let recent = db::recent_lookups(&pool, 100).await.unwrap();
+    assert!(recent.iter().any(|r| r.account_id == account_id && r.personaname == "tester"));
+
     // Idempotent re-ingest
     let res2 = db::ingest_player(&pool, &combined).await.unwrap();
     assert!(res2.mmr_updated);
@@ -94,3 +97,133 @@ async fn ingest_roundtrip() {
     let expected = 6.0f64 / 10.0;
     assert!((rec.win_rate.unwrap() - expected).abs() < 1e-9);
 }
+
+// Offline lookups rebuild the same payload that was ingested.
+#[tokio::test]
+#[ignore]
+async fn load_player_roundtrip() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let account_id: u32 = 388674066;
+    let steamid64 = deadlock_cli::steam::account_id_to_steamid64(account_id);
+    let updated = Utc::now().timestamp();
+
+    let combined = models::CombinedPayload {
+        steamid64: steamid64.clone(),
+        account_id,
+        profile: models::SteamProfile {
+            account_id: account_id as i32,
+            personaname: "offline".into(),
+            profileurl: format!("https://steamcommunity.com/profiles/{steamid64}/"),
+            countrycode: Some("SE".into()),
+            last_updated: Some(updated.to_string()),
+            ..Default::default()
+        },
+        latest_mmr: Some(models::MMRHistory {
+            account_id: account_id as i32,
+            match_id: 55,
+            start_time: updated as i32,
+            player_score: 12.5,
+            rank: 63,
+            division: 6,
+            division_tier: 3,
+        }),
+        hero_stats: vec![models::HeroStats {
+            account_id: account_id as i32,
+            hero_id: 2,
+            matches_played: Some(4),
+            wins: Some(3),
+            kills_per_min: Some(0.4),
+            ..Default::default()
+        }],
+    };
+    db::ingest_player(&pool, &combined).await.unwrap();
+
+    let stored = db::load_player(&pool, account_id).await.unwrap().expect("stored player");
+    assert_eq!(stored.payload.steamid64, steamid64);
+    assert_eq!(stored.payload.profile.personaname, "offline");
+    assert_eq!(stored.profile_updated_at.unwrap().timestamp(), updated);
+    let mmr = stored.payload.latest_mmr.unwrap();
+    assert_eq!((mmr.rank, mmr.division, mmr.division_tier), (63, 6, 3));
+    assert_eq!(stored.payload.hero_stats.len(), 1);
+    assert_eq!(stored.payload.hero_stats[0].wins, Some(3));
+    assert_eq!(
+        ui::Staleness::from_updated_at(stored.profile_updated_at, Utc::now()),
+        ui::Staleness::Fresh
+    );
+
+    assert!(db::load_player(&pool, 1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+#[ignore]
+async fn mmr_history_roundtrip() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let snap = |start_time: i32, rank: i32| models::MMRHistory {
+        account_id: 388674067,
+        match_id: start_time as i64,
+        start_time,
+        player_score: rank as f64,
+        rank,
+        division: rank / 10,
+        division_tier: rank % 10,
+    };
+    sqlx::query("DELETE FROM mmr_history WHERE account_id = 388674067").execute(&pool).await.unwrap();
+    assert_eq!(db::store_mmr_history(&pool, 388674067, &[snap(1_700_000_100, 44), snap(1_700_000_000, 43)]).await.unwrap(), 2);
+    assert_eq!(db::store_mmr_history(&pool, 388674067, &[snap(1_700_000_100, 44)]).await.unwrap(), 0);
+
+    let stored = db::load_mmr_history(&pool, 388674067).await.unwrap();
+    assert_eq!(stored.iter().map(|m| m.rank).collect::<Vec<_>>(), vec![43, 44]);
+}
+
+#[tokio::test]
+#[ignore]
+async fn known_players_skip_unnamed_stubs() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let named = models::PlayerInMatch { account_id: 388674068, ..Default::default() };
+    let stub = models::PlayerInMatch { account_id: 388674069, ..Default::default() };
+    let meta = models::MatchMeta {
+        match_id: 9876543215,
+        start_time: None,
+        duration_s: None,
+        winner_team: None,
+        average_badge: None,
+        region: None,
+        patch_version: None,
+        info: None,
+        players: Some(vec![named, stub]),
+    };
+    db::ingest_matches_batch(&pool, &[meta]).await.unwrap();
+    let profile = models::SteamProfile {
+        account_id: 388674068,
+        personaname: "known".into(),
+        ..Default::default()
+    };
+    db::fill_player_profiles(&pool, &[profile]).await.unwrap();
+
+    let known = db::known_players(&pool).await.unwrap();
+    assert!(known.contains(&(388674068, "known".to_string())));
+    assert!(!known.iter().any(|(id, _)| *id == 388674069));
+}
+
+#[tokio::test]
+#[ignore]
+async fn heroes_dropped_from_assets_do_not_age_the_catalog() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let hero = |id: i32| models::Hero { id, name: format!("Hero {}", id), class_name: None, role: None, icon_url: None };
+    db::upsert_heroes(&pool, &[hero(9901), hero(9902)]).await.unwrap();
+    // 9901 is no longer returned by the assets API, so its row is never touched again
+    sqlx::query("UPDATE heroes SET updated_at = now() - interval '30 days' WHERE hero_id = 9901").execute(&pool).await.unwrap();
+
+    let refreshed = db::heroes_refreshed_at(&pool).await.unwrap().unwrap();
+    assert!(Utc::now() - refreshed < chrono::Duration::days(1));
+
+    sqlx::query("DELETE FROM heroes WHERE hero_id IN (9901, 9902)").execute(&pool).await.unwrap();
+}
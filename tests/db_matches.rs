@@ -1,5 +1,6 @@
 #![cfg(feature = "db")]
 
+use deadlock_cli::score::Scores;
 use deadlock_cli::{db, models};
 
 // This test requires a running PostgreSQL at DATABASE_URL.
@@ -14,7 +15,7 @@ async fn persist_one_match() {
         match_id: 9876543210,
         start_time: Some(1_700_000_000),
         duration_s: Some(1200),
-        winner_team: Some("team2".into()),
+        winner_team: Some(models::Team::from(2)),
         average_badge: Some(35),
         region: Some("na".into()),
         patch_version: Some("1.2".into()),
@@ -22,7 +23,7 @@ async fn persist_one_match() {
         players: Some(vec![models::PlayerInMatch {
             account_id: 388674065,
             hero_id: Some(1),
-            team: Some("team2".into()),
+            team: Some(models::Team::from(2)),
             party_id: Some(1),
             lane: Some("mid".into()),
             is_victory: Some(false),
@@ -45,3 +46,399 @@ async fn persist_one_match() {
     assert_eq!(res.match_players_upserted, 1);
 }
 
+
+#[tokio::test]
+#[ignore]
+async fn persist_match_objectives() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let entry = models::PlayerMatchHistoryEntry {
+        account_id: 388674065,
+        match_id: 9876543211,
+        objectives_mask_team0: (1 << 10) | (1 << 11),
+        objectives_mask_team1: 1 << 3,
+        ..Default::default()
+    };
+    let res = db::ingest_matches_batch(&pool, &models::history_to_metas(vec![entry])).await.unwrap();
+    assert_eq!(res.objectives_upserted, 32);
+
+    let o = db::load_match_objectives(&pool, 9876543211).await.unwrap().unwrap();
+    assert_eq!(o.team0.0, (1 << 10) | (1 << 11));
+    assert_eq!(o.team1.0, 1 << 3);
+    assert!(db::load_match_objectives(&pool, 1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+#[ignore]
+async fn load_stored_match() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let player = |account_id: i32, team: models::Team| models::PlayerInMatch {
+        account_id,
+        hero_id: Some(6),
+        team: Some(team),
+        kills: Some(3),
+        networth: Some(30000),
+        ..Default::default()
+    };
+    let meta = models::MatchMeta {
+        match_id: 9876543212,
+        start_time: Some(1_700_000_000),
+        duration_s: Some(1800),
+        winner_team: Some(models::Team::Team1),
+        average_badge: Some(74),
+        region: Some("europe".into()),
+        patch_version: None,
+        info: None,
+        players: Some(vec![player(11, models::Team::Team0), player(12, models::Team::Team1)]),
+    };
+    db::ingest_matches_batch(&pool, &[meta]).await.unwrap();
+
+    let stored = db::load_match(&pool, 9876543212).await.unwrap().unwrap();
+    assert_eq!(stored.winner_team, Some(models::Team::Team1));
+    assert_eq!(stored.start_time, Some(1_700_000_000));
+    assert_eq!(stored.average_badge, Some(74));
+    let players = stored.players.unwrap();
+    assert_eq!(players.len(), 2);
+    assert_eq!(players[0].team, Some(models::Team::Team0));
+    assert_eq!(players[1].kills, Some(3));
+    assert!(db::load_match(&pool, 1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+#[ignore]
+async fn player_matches_carry_badge_and_region() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let meta = models::MatchMeta {
+        match_id: 9876543217,
+        start_time: Some(1_700_000_000),
+        duration_s: Some(1800),
+        winner_team: Some(models::Team::Team0),
+        average_badge: Some(74),
+        region: Some("europe".into()),
+        patch_version: None,
+        info: None,
+        players: Some(vec![models::PlayerInMatch {
+            account_id: 388674075,
+            hero_id: Some(6),
+            team: Some(models::Team::Team0),
+            ..Default::default()
+        }]),
+    };
+    db::ingest_matches_batch(&pool, &[meta]).await.unwrap();
+
+    let rows = db::load_player_matches(&pool, 388674075).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].average_badge, Some(74));
+    assert_eq!(rows[0].region.as_deref(), Some("europe"));
+}
+
+#[tokio::test]
+#[ignore]
+async fn load_player_matches_reads_history_modes() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let entry = models::PlayerMatchHistoryEntry {
+        account_id: 388674066,
+        match_id: 9876543213,
+        hero_id: 6,
+        start_time: 1_700_000_000,
+        match_mode: models::MatchMode::Ranked,
+        player_team: models::Team::Team0,
+        match_result: models::Team::Team0,
+        player_kills: 4,
+        ..Default::default()
+    };
+    db::ingest_matches_batch(&pool, &models::history_to_metas(vec![entry])).await.unwrap();
+
+    let rows = db::load_player_matches(&pool, 388674066).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].match_mode, Some(models::MatchMode::Ranked));
+    assert_eq!(rows[0].is_victory, Some(true));
+    assert_eq!(rows[0].start_time, Some(1_700_000_000));
+}
+
+#[tokio::test]
+#[ignore]
+async fn load_player_matches_filtered_pages_in_sql() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    // 2024-03-01 00:00 UTC
+    let day = 1_709_251_200;
+    let entry = |match_id: i64, offset: i32, won: bool, kills: i32| models::PlayerMatchHistoryEntry {
+        account_id: 388674089,
+        match_id,
+        hero_id: 6,
+        start_time: day + offset,
+        match_mode: models::MatchMode::Ranked,
+        player_team: models::Team::Team0,
+        match_result: if won { models::Team::Team0 } else { models::Team::Team1 },
+        player_kills: kills,
+        ..Default::default()
+    };
+    let entries = vec![entry(9876543228, -10, true, 3), entry(9876543229, 10, true, 5), entry(9876543230, 20, false, 9)];
+    db::ingest_matches_batch(&pool, &models::history_to_metas(entries)).await.unwrap();
+    // no kills and no mode on record
+    let bare = models::MatchMeta {
+        match_id: 9876543231,
+        start_time: Some(day as i64 + 30),
+        duration_s: None,
+        winner_team: None,
+        average_badge: None,
+        region: None,
+        patch_version: None,
+        info: None,
+        players: Some(vec![models::PlayerInMatch { account_id: 388674089, hero_id: Some(6), ..Default::default() }]),
+    };
+    db::ingest_matches_batch(&pool, &[bare]).await.unwrap();
+
+    let ids = |rows: Vec<models::PlayerMatch>| rows.iter().map(|m| m.match_id).collect::<Vec<_>>();
+    let mut q = models::MatchQuery { account_id: 388674089, sort: models::SortKey::Kills, ..Default::default() };
+    assert_eq!(db::count_player_matches(&pool, &q).await.unwrap(), 4);
+    assert_eq!(ids(db::load_player_matches_filtered(&pool, &q, None, 0).await.unwrap()), vec![9876543230, 9876543229, 9876543228, 9876543231]);
+    assert_eq!(ids(db::load_player_matches_filtered(&pool, &q, Some(2), 1).await.unwrap()), vec![9876543229, 9876543228]);
+    q.ascending = true;
+    assert_eq!(ids(db::load_player_matches_filtered(&pool, &q, None, 0).await.unwrap()), vec![9876543228, 9876543229, 9876543230, 9876543231]);
+
+    let won_that_day = models::MatchQuery { since: Some(day as i64), until: Some(day as i64 + 86_400), victory: Some(true), mode: Some(models::MatchMode::Ranked), ..q.clone() };
+    assert_eq!(db::count_player_matches(&pool, &won_that_day).await.unwrap(), 1);
+    assert_eq!(ids(db::load_player_matches_filtered(&pool, &won_that_day, Some(25), 0).await.unwrap()), vec![9876543229]);
+
+    let unranked = models::MatchQuery { mode: Some(models::MatchMode::Unranked), ..q };
+    assert_eq!(db::count_player_matches(&pool, &unranked).await.unwrap(), 0);
+}
+
+#[tokio::test]
+#[ignore]
+async fn load_all_player_matches_spans_accounts() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let player = |account_id: i32, hero_id: i32| models::PlayerInMatch {
+        account_id,
+        hero_id: Some(hero_id),
+        team: Some(models::Team::Team0),
+        ..Default::default()
+    };
+    let meta = models::MatchMeta {
+        match_id: 9876543216,
+        start_time: Some(1_700_000_000),
+        duration_s: None,
+        winner_team: None,
+        average_badge: None,
+        region: None,
+        patch_version: Some("5".into()),
+        info: None,
+        players: Some(vec![player(388674073, 2), player(388674074, 3)]),
+    };
+    db::ingest_matches_batch(&pool, &[meta]).await.unwrap();
+
+    let all = db::load_all_player_matches(&pool).await.unwrap();
+    let rows: Vec<_> = all.iter().filter(|r| r.match_id == 9876543216).collect();
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().all(|r| r.patch_version.as_deref() == Some("5")));
+    assert!(rows.iter().any(|r| r.hero_id == Some(3)));
+}
+
+#[tokio::test]
+#[ignore]
+async fn co_players_and_stub_profiles() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let player = |account_id: i32, team: models::Team, party_id: i64| models::PlayerInMatch {
+        account_id,
+        team: Some(team),
+        party_id: Some(party_id),
+        is_victory: Some(team == models::Team::Team0),
+        ..Default::default()
+    };
+    let meta = models::MatchMeta {
+        match_id: 9876543214,
+        start_time: Some(1_700_000_000),
+        duration_s: Some(1500),
+        winner_team: Some(models::Team::Team0),
+        average_badge: None,
+        region: None,
+        patch_version: None,
+        info: None,
+        players: Some(vec![
+            player(388674070, models::Team::Team0, 5),
+            player(388674071, models::Team::Team0, 5),
+            player(388674072, models::Team::Team1, 0),
+        ]),
+    };
+    db::ingest_matches_batch(&pool, &[meta]).await.unwrap();
+
+    let rows = db::load_co_players(&pool, 388674070).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    let mate = rows.iter().find(|r| r.account_id == 388674071).unwrap();
+    assert!(mate.same_party());
+    assert_eq!(mate.same_team(), Some(true));
+    assert_eq!(mate.is_victory, Some(true));
+    assert_eq!(rows.iter().find(|r| r.account_id == 388674072).unwrap().same_team(), Some(false));
+
+    let profile = models::SteamProfile {
+        account_id: 388674072,
+        personaname: "filled".into(),
+        ..Default::default()
+    };
+    assert_eq!(db::fill_player_profiles(&pool, &[profile]).await.unwrap(), 1);
+    let rows = db::load_co_players(&pool, 388674070).await.unwrap();
+    assert_eq!(rows.iter().find(|r| r.account_id == 388674072).unwrap().personaname.as_deref(), Some("filled"));
+}
+
+#[tokio::test]
+#[ignore]
+async fn ingest_scores_lobbies_and_ranks_within_hero() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    // heroes 9001-9006 are only used here, so percentiles see just these games
+    let player = |account_id: i32, hero_id: i32, kills: i32| models::PlayerInMatch {
+        account_id,
+        hero_id: Some(hero_id),
+        kills: Some(kills),
+        deaths: Some(5),
+        assists: Some(5),
+        networth: Some(20_000),
+        damage: Some(15_000),
+        obj_damage: Some(2_000),
+        last_hits: Some(100),
+        ..Default::default()
+    };
+    // hero 9001 (account 388674083) gets `first_kills`, everyone else 4
+    let lobby = |match_id: i64, first_kills: i32, size: i32| models::MatchMeta {
+        match_id,
+        start_time: Some(1_700_000_000),
+        duration_s: Some(1800),
+        winner_team: None,
+        average_badge: None,
+        region: None,
+        patch_version: None,
+        info: None,
+        players: Some((0..size).map(|i| player(388674083 + i, 9001 + i, if i == 0 { first_kills } else { 4 })).collect()),
+    };
+    let metas = [lobby(9876543225, 10, 6), lobby(9876543226, 0, 6), lobby(9876543227, 30, 5)];
+    db::ingest_matches_batch(&pool, &metas).await.unwrap();
+
+    let ids = [9876543225, 9876543226, 9876543227];
+    let scores = Scores::new(db::load_scores(&pool, &ids, None).await.unwrap());
+    let good = scores.get(9876543225, 388674083).unwrap();
+    let bad = scores.get(9876543226, 388674083).unwrap();
+    // kills z = ±sqrt(5); the other six stats are level
+    assert!((good.score - (50.0 + 10.0 * 5f64.sqrt() / 7.0)).abs() < 1e-9);
+    assert!((bad.score - (50.0 - 10.0 * 5f64.sqrt() / 7.0)).abs() < 1e-9);
+    assert_eq!((good.percentile, bad.percentile, good.hero_matches), (75.0, 25.0, 2));
+    assert!(scores.get(9876543225, 388674084).unwrap().score < 50.0);
+    // too few participants to score
+    assert!(scores.get(9876543227, 388674083).is_none());
+
+    let own = db::load_scores(&pool, &ids, Some(388674084)).await.unwrap();
+    assert_eq!(own.len(), 2);
+    assert!(own.iter().all(|s| s.account_id == 388674084));
+}
+
+#[tokio::test]
+#[ignore]
+async fn load_meta_totals_filters_and_groups_in_sql() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let player = |account_id: i32, hero_id: i32, won: bool| models::PlayerInMatch {
+        account_id,
+        hero_id: Some(hero_id),
+        team: Some(if won { models::Team::Team0 } else { models::Team::Team1 }),
+        is_victory: Some(won),
+        kills: Some(4),
+        deaths: Some(2),
+        assists: Some(6),
+        damage: Some(if hero_id == 1 { 20_000 } else { 10_000 }),
+        ..Default::default()
+    };
+    let game = |match_id: i64, badge: i32, region: &str| models::MatchMeta {
+        match_id,
+        start_time: Some(1_700_000_000),
+        duration_s: Some(1800),
+        winner_team: Some(models::Team::Team0),
+        average_badge: Some(badge),
+        region: Some(region.into()),
+        patch_version: Some("meta-totals".into()),
+        info: None,
+        players: Some(vec![player(388674077, 1, true), player(388674078, 2, false)]),
+    };
+    // only the first passes an Archon + Europe filter
+    let metas = [game(9876543219, 74, "Europe"), game(9876543220, 22, "Europe"), game(9876543221, 74, "NAmerica")];
+    db::ingest_matches_batch(&pool, &metas).await.unwrap();
+
+    let patch = models::MetaFilter { patch: Some("meta-totals".into()), ..Default::default() };
+    assert_eq!(db::load_meta_totals(&pool, &patch).await.unwrap().matches, 3);
+
+    let filter = models::MetaFilter { region: Some("europe".into()), ..patch }.with_rank(Some(models::Rank::Archon));
+    let mut totals = db::load_meta_totals(&pool, &filter).await.unwrap();
+    assert_eq!(totals.matches, 1);
+    totals.heroes.sort_by_key(|h| h.hero_id);
+    let one = &totals.heroes[0];
+    assert_eq!((one.hero_id, one.picks, one.decided, one.wins), (1, 1, 1, 1));
+    assert_eq!((one.kills, one.deaths, one.assists), (4, 2, 6));
+    assert_eq!(one.avg_damage, Some(20_000.0));
+    assert_eq!(one.avg_networth, None);
+    assert_eq!((totals.heroes[1].hero_id, totals.heroes[1].wins), (2, 0));
+}
+
+#[tokio::test]
+#[ignore]
+async fn load_synergy_totals_pairs_heroes_per_match() {
+    let db::DbPool(pool) = db::connect().await.unwrap();
+    db::migrate(&pool).await.unwrap();
+
+    let player = |account_id: i32, hero_id: i32, team: Option<models::Team>, won: bool| models::PlayerInMatch {
+        account_id,
+        hero_id: Some(hero_id),
+        team,
+        is_victory: Some(won),
+        ..Default::default()
+    };
+    let game = |match_id: i64, team0_won: bool, region: &str| models::MatchMeta {
+        match_id,
+        start_time: Some(1_700_000_000),
+        duration_s: Some(1800),
+        winner_team: None,
+        average_badge: Some(74),
+        region: Some(region.into()),
+        patch_version: Some("synergy-totals".into()),
+        info: None,
+        players: Some(vec![
+            player(388674079, 1, Some(models::Team::Team0), team0_won),
+            player(388674080, 2, Some(models::Team::Team0), team0_won),
+            player(388674081, 3, Some(models::Team::Team1), !team0_won),
+            // no team: left out of both the baseline and the pairs
+            player(388674082, 4, None, true),
+        ]),
+    };
+    let metas = [game(9876543222, true, "Europe"), game(9876543223, false, "Europe"), game(9876543224, true, "Asia")];
+    db::ingest_matches_batch(&pool, &metas).await.unwrap();
+
+    let filter = models::MetaFilter { region: Some("europe".into()), patch: Some("synergy-totals".into()), ..Default::default() };
+    let mut totals = db::load_synergy_totals(&pool, &filter).await.unwrap();
+    assert_eq!(totals.matches, 2);
+    totals.heroes.sort_by_key(|h| h.hero_id);
+    assert_eq!(totals.heroes.iter().map(|h| (h.hero_id, h.matches, h.wins)).collect::<Vec<_>>(), vec![(1, 2, 1), (2, 2, 1), (3, 2, 1)]);
+
+    totals.pairs.sort_by_key(|p| (p.hero_id, p.other_hero_id));
+    assert_eq!(totals.pairs.len(), 6);
+    let with = totals.pairs.iter().find(|p| (p.hero_id, p.other_hero_id) == (1, 2)).unwrap();
+    assert!(with.same_team);
+    assert_eq!((with.matches, with.wins), (2, 1));
+    let against = totals.pairs.iter().find(|p| (p.hero_id, p.other_hero_id) == (3, 1)).unwrap();
+    assert!(!against.same_team);
+
+    let report = deadlock_cli::synergy::analyze(&totals);
+    assert!((report.for_hero(1, 1, 10).with[0].stats.win_rate - 50.0).abs() < 1e-9);
+}
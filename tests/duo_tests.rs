@@ -0,0 +1,52 @@
+use deadlock_cli::duo;
+use deadlock_cli::models::{PlayerMatch, Team};
+
+fn row(match_id: i64, team: Option<Team>, hero_id: i32, won: bool, kda: (i32, i32, i32)) -> PlayerMatch {
+    PlayerMatch {
+        match_id,
+        start_time: Some(1_700_000_000 + match_id),
+        hero_id: Some(hero_id),
+        team,
+        is_victory: Some(won),
+        kills: Some(kda.0),
+        deaths: Some(kda.1),
+        assists: Some(kda.2),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn splits_shared_matches_by_team() {
+    let a = vec![
+        row(1, Some(Team::Team0), 6, true, (10, 2, 5)),
+        row(2, Some(Team::Team0), 6, false, (2, 6, 4)),
+        row(3, Some(Team::Team1), 7, true, (6, 3, 3)),
+        row(4, None, 6, true, (1, 1, 1)),
+        row(5, Some(Team::Team0), 6, true, (9, 9, 9)),
+    ];
+    let b = vec![
+        row(1, Some(Team::Team0), 11, true, (4, 4, 12)),
+        row(2, Some(Team::Team0), 11, false, (0, 8, 2)),
+        row(3, Some(Team::Team0), 11, false, (5, 7, 1)),
+        row(4, Some(Team::Team1), 11, false, (1, 1, 1)),
+        row(6, Some(Team::Team0), 11, true, (1, 1, 1)),
+    ];
+
+    let r = duo::analyze(1, &a, 2, &b);
+    assert_eq!(r.shared.iter().map(|m| m.match_id).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+    assert_eq!(r.unknown_team, 1);
+
+    assert_eq!((r.together.matches, r.together.wins), (2, 1));
+    assert!((r.together.win_rate.unwrap() - 50.0).abs() < 1e-9);
+    assert!((r.together.a.kills - 6.0).abs() < 1e-9);
+    assert!((r.together.b.kda - 18.0 / 12.0).abs() < 1e-9);
+    assert_eq!(r.together.pairings.len(), 1);
+    assert_eq!((r.together.pairings[0].a.hero_id, r.together.pairings[0].b.hero_id, r.together.pairings[0].matches), (6, 11, 2));
+
+    assert_eq!((r.against.matches, r.against.wins), (1, 1));
+    assert_eq!(r.against.pairings[0].a.hero_id, 7);
+
+    let none = duo::analyze(1, &a[..1], 3, &[]);
+    assert!(none.shared.is_empty());
+    assert!(none.together.win_rate.is_none());
+}
@@ -0,0 +1,93 @@
+use deadlock_cli::deadlock::{self, DeadlockApi};
+use deadlock_cli::fixture::FixtureApi;
+use deadlock_cli::models;
+
+fn profile(account_id: i32) -> models::SteamProfile {
+    models::SteamProfile {
+        account_id,
+        personaname: "tester".into(),
+        profileurl: "https://steamcommunity.com/profiles/76561198348939793/".into(),
+        countrycode: Some("DE".into()),
+        ..Default::default()
+    }
+}
+
+fn mmr(account_id: i32, match_id: i64, start_time: i32, rank: i32) -> models::MMRHistory {
+    models::MMRHistory {
+        account_id,
+        match_id,
+        start_time,
+        player_score: 10.0,
+        rank,
+        division: rank / 10,
+        division_tier: rank % 10,
+    }
+}
+
+#[tokio::test]
+async fn fetch_player_uses_latest_mmr() {
+    let api = FixtureApi::new()
+        .with_profile(profile(388674065))
+        .with_mmr(mmr(388674065, 1, 1_700_000_000, 41))
+        .with_mmr(mmr(388674065, 2, 1_700_000_500, 42))
+        .with_mmr(mmr(1, 3, 1_800_000_000, 99))
+        .with_hero_stats(models::HeroStats { account_id: 388674065, hero_id: 7, ..Default::default() });
+
+    let combined = deadlock::fetch_player(&api, "76561198348939793", 388674065).await.unwrap();
+    assert_eq!(combined.profile.personaname, "tester");
+    assert_eq!(combined.latest_mmr.unwrap().match_id, 2);
+    assert_eq!(combined.hero_stats.len(), 1);
+}
+
+#[tokio::test]
+async fn fetch_player_without_profile_is_not_found() {
+    let api = FixtureApi::new();
+    let err = deadlock::fetch_player(&api, "76561198348939793", 388674065).await.err().unwrap();
+    assert!(err.to_string().contains("Player not found"));
+}
+
+#[tokio::test]
+async fn history_groups_into_matches() {
+    let entry = |match_id: i64, account_id: i32| models::PlayerMatchHistoryEntry {
+        account_id,
+        match_id,
+        hero_id: 1,
+        start_time: 1_700_000_000,
+        match_duration_s: 1500,
+        player_team: models::Team::Team1,
+        match_result: models::Team::Team1,
+        ..Default::default()
+    };
+    let api = FixtureApi::new()
+        .with_history_entry(entry(20, 388674065))
+        .with_history_entry(entry(10, 388674065))
+        .with_history_entry(entry(30, 1));
+
+    let entries = api.get_player_match_history(388674065, false, true).await.unwrap();
+    let metas = models::history_to_metas(entries);
+    assert_eq!(metas.iter().map(|m| m.match_id).collect::<Vec<_>>(), vec![10, 20]);
+    assert_eq!(metas[0].duration_s, Some(1500));
+    assert_eq!(metas[0].players.as_ref().unwrap()[0].team, Some(models::Team::Team1));
+    assert_eq!(metas[0].players.as_ref().unwrap()[0].is_victory, Some(true));
+    assert_eq!(metas[0].winner_team, Some(models::Team::Team1));
+}
+
+#[tokio::test]
+async fn matches_metadata_respects_include_flags() {
+    let api = FixtureApi::new().with_match(models::MatchMeta {
+        match_id: 5,
+        start_time: None,
+        duration_s: None,
+        winner_team: None,
+        average_badge: None,
+        region: None,
+        patch_version: None,
+        info: Some(serde_json::json!({"k": 1})),
+        players: Some(vec![models::PlayerInMatch { account_id: 1, ..Default::default() }]),
+    });
+
+    let metas = api.get_matches_metadata(&[5, 6], false, true).await.unwrap();
+    assert_eq!(metas.len(), 1);
+    assert!(metas[0].info.is_none());
+    assert!(metas[0].players.is_some());
+}
@@ -0,0 +1,57 @@
+use deadlock_cli::heroes::{self, HeroCatalog};
+use deadlock_cli::models::Hero;
+use httpmock::prelude::*;
+
+#[test]
+fn bundled_catalog_resolves_names_and_roles() {
+    let catalog = HeroCatalog::bundled();
+    assert_eq!(catalog.name(6), "Abrams");
+    assert_eq!(catalog.role(6), Some("brawler"));
+    assert_eq!(catalog.name(9999), "Hero #9999");
+    assert_eq!(catalog.role(9999), None);
+}
+
+#[test]
+fn annotate_adds_names_next_to_hero_ids() {
+    let catalog = HeroCatalog::from_heroes(vec![Hero {
+        id: 1,
+        name: "Infernus".into(),
+        class_name: None,
+        role: Some("assassin".into()),
+        icon_url: None,
+    }]);
+    let mut v = serde_json::json!({
+        "hero_stats": [{ "hero_id": 1, "wins": 3 }, { "hero_id": 2 }],
+        "nested": { "players": [{ "hero_id": 1 }] }
+    });
+    catalog.annotate(&mut v);
+    assert_eq!(v["hero_stats"][0]["hero_name"], "Infernus");
+    assert_eq!(v["hero_stats"][0]["hero_role"], "assassin");
+    assert_eq!(v["hero_stats"][1]["hero_name"], "Hero #2");
+    assert!(v["hero_stats"][1]["hero_role"].is_null());
+    assert_eq!(v["nested"]["players"][0]["hero_name"], "Infernus");
+}
+
+#[tokio::test]
+async fn fetches_heroes_from_assets_api() {
+    let server = MockServer::start();
+    let _m = server.mock(|when, then| {
+        when.method(GET).path("/v2/heroes");
+        then.status(200).json_body_obj(&serde_json::json!([{
+            "id": 13,
+            "class_name": "hero_haze",
+            "name": "Haze",
+            "hero_type": "assassin",
+            "images": { "icon_image_small": "https://example.com/haze.png" }
+        }]));
+    });
+    unsafe {
+        std::env::set_var("DEADLOCK_ASSETS_BASE", server.base_url());
+    }
+
+    let heroes = heroes::fetch_assets(&reqwest::Client::new()).await.unwrap();
+    assert_eq!(heroes.len(), 1);
+    assert_eq!(heroes[0].name, "Haze");
+    assert_eq!(heroes[0].role.as_deref(), Some("assassin"));
+    assert_eq!(heroes[0].icon_url.as_deref(), Some("https://example.com/haze.png"));
+}
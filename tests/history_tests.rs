@@ -0,0 +1,43 @@
+use chrono::NaiveDate;
+use deadlock_cli::heroes::HeroCatalog;
+use deadlock_cli::history::{self, HistoryFilter};
+use deadlock_cli::models::{MatchMode, SortKey};
+
+#[test]
+fn filter_builds_whole_day_query() {
+    // 2024-03-01 00:00 UTC
+    let day = 1_709_251_200;
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1);
+    let filter = HistoryFilter { hero_id: Some(6), victory: Some(true), ..Default::default() }.with_dates(date, date);
+    let q = filter.query(42, SortKey::Kills, true);
+    assert_eq!((q.account_id, q.hero_id, q.victory, q.mode), (42, Some(6), Some(true), None));
+    assert_eq!((q.since, q.until), (Some(day), Some(day + 86_400)));
+    assert_eq!((q.sort, q.ascending), (SortKey::Kills, true));
+
+    let open = HistoryFilter::default().with_dates(None, None).query(42, SortKey::default(), false);
+    assert_eq!((open.since, open.until, open.sort), (None, None, SortKey::Date));
+}
+
+#[test]
+fn pages_clamp_and_offset() {
+    let page = history::paginate(vec![1, 2, 3], 9, 2);
+    assert_eq!((page.page, page.pages, page.total), (2, 2, 3));
+    assert_eq!(page.items, vec![3]);
+
+    let empty = history::Page::<i64>::empty(0, 3, 0);
+    assert_eq!((empty.page, empty.pages, empty.per_page, empty.offset()), (1, 1, 1, 0));
+    assert_eq!(history::Page::<i64>::empty(95, 4, 25).offset(), 75);
+}
+
+#[test]
+fn parses_modes_and_hero_names() {
+    assert_eq!("ranked".parse::<MatchMode>().unwrap(), MatchMode::Ranked);
+    assert_eq!("Hero-Labs".parse::<MatchMode>().unwrap(), MatchMode::HeroLabs);
+    assert_eq!("4".parse::<MatchMode>().unwrap(), MatchMode::Ranked);
+    assert!("ladder".parse::<MatchMode>().is_err());
+
+    let catalog = HeroCatalog::bundled();
+    assert_eq!(catalog.find("abrams").map(|h| h.id), Some(6));
+    assert_eq!(catalog.find("6").map(|h| h.name.as_str()), Some("Abrams"));
+    assert!(catalog.find("nobody").is_none());
+}
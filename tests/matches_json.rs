@@ -43,3 +43,55 @@ fn decode_match_meta_with_players() {
     assert_eq!(p.kills.unwrap(), 10);
 }
 
+
+#[test]
+fn decode_history_entry_enums() {
+    use deadlock_cli::models::{GameMode, MatchMode, PlayerMatchHistoryEntry, Team};
+
+    let json = serde_json::json!({
+        "account_id": 388674065, "match_id": 1, "hero_id": 6, "hero_level": 30,
+        "start_time": 1_700_000_000, "game_mode": 1, "match_mode": 4, "player_team": 0,
+        "player_kills": 5, "player_deaths": 2, "player_assists": 9, "denies": 3,
+        "net_worth": 40000, "last_hits": 150, "match_duration_s": 1900, "match_result": 1,
+        "objectives_mask_team0": 0, "objectives_mask_team1": 0
+    });
+    let e: PlayerMatchHistoryEntry = serde_json::from_value(json).expect("decode");
+    assert_eq!(e.game_mode, GameMode::Normal);
+    assert_eq!(e.match_mode, MatchMode::Ranked);
+    assert_eq!(e.player_team, Team::Team0);
+    assert_eq!(e.match_result, Team::Team1);
+    assert_eq!(e.is_victory(), Some(false));
+
+    let unknown: PlayerMatchHistoryEntry = serde_json::from_value(serde_json::json!({
+        "account_id": 1, "match_id": 2, "hero_id": 1, "hero_level": 1, "start_time": 0,
+        "game_mode": 42, "match_mode": 99, "player_team": 7, "player_kills": 0,
+        "player_deaths": 0, "player_assists": 0, "denies": 0, "net_worth": 0, "last_hits": 0,
+        "match_duration_s": 0, "match_result": 7, "objectives_mask_team0": 0, "objectives_mask_team1": 0
+    }))
+    .expect("decode unknown values");
+    assert_eq!(unknown.game_mode, GameMode::Unknown(42));
+    assert_eq!(unknown.match_mode, MatchMode::Unknown(99));
+    assert_eq!(unknown.is_victory(), None);
+    assert_eq!(serde_json::to_value(unknown.match_mode).unwrap(), 99);
+}
+
+#[test]
+fn decode_team_strings() {
+    use deadlock_cli::models::Team;
+
+    let t: Team = serde_json::from_value(serde_json::json!("team1")).unwrap();
+    assert_eq!(t, Team::Team1);
+    let t: Team = serde_json::from_value(serde_json::json!("k_ECitadelLobbyTeam_Team0")).unwrap();
+    assert_eq!(t, Team::Team0);
+    assert_eq!(Team::Team1.to_string(), "team1");
+    assert_eq!(serde_json::to_value(Team::Team0).unwrap(), "team0");
+}
+
+#[test]
+fn unparsable_team_is_an_error() {
+    use deadlock_cli::models::Team;
+
+    assert_eq!("team2".parse::<Team>(), Ok(Team::Unknown(2)));
+    assert!("spectators".parse::<Team>().is_err());
+    assert!(serde_json::from_value::<Team>(serde_json::json!("spectators")).is_err());
+}
@@ -0,0 +1,50 @@
+use deadlock_cli::mates;
+use deadlock_cli::models::{CoPlayer, Team};
+
+fn row(match_id: i64, account_id: u32, same_team: bool, party: i64, won: bool) -> CoPlayer {
+    CoPlayer {
+        match_id,
+        start_time: Some(1_700_000_000 + match_id),
+        account_id,
+        personaname: (account_id != 30).then(|| format!("p{}", account_id)),
+        team: Some(if same_team { Team::Team0 } else { Team::Team1 }),
+        party_id: Some(party),
+        my_team: Some(Team::Team0),
+        my_party_id: Some(7),
+        is_victory: Some(won),
+    }
+}
+
+#[test]
+fn ranks_mates_and_opponents() {
+    let rows = vec![
+        row(1, 10, true, 7, true),
+        row(2, 10, true, 7, false),
+        row(3, 10, true, 0, true),
+        row(1, 20, true, 0, true),
+        row(2, 20, true, 0, false),
+        row(1, 30, false, 0, true),
+        row(2, 30, false, 0, false),
+        row(3, 30, false, 0, true),
+        row(3, 40, false, 0, true),
+        row(3, 1, true, 7, true),
+    ];
+
+    let r = mates::analyze(1, &rows, 2, false, 10);
+    assert_eq!(r.matches, 3);
+    assert_eq!(r.mates.iter().map(|a| a.account_id).collect::<Vec<_>>(), vec![10, 20]);
+    let top = &r.mates[0];
+    assert_eq!((top.with, top.party, top.wins_with), (3, 2, 2));
+    assert!((top.win_rate_with.unwrap() - 200.0 / 3.0).abs() < 1e-9);
+    assert_eq!(r.opponents.len(), 1);
+    assert_eq!((r.opponents[0].account_id, r.opponents[0].against, r.opponents[0].wins_against), (30, 3, 2));
+    assert_eq!(r.stubs(), vec![30]);
+
+    let party = mates::analyze(1, &rows, 2, true, 10);
+    assert_eq!(party.mates.iter().map(|a| (a.account_id, a.with)).collect::<Vec<_>>(), vec![(10, 2)]);
+
+    let mut limited = mates::analyze(1, &rows, 1, false, 1);
+    assert_eq!(limited.opponents.iter().map(|a| a.account_id).collect::<Vec<_>>(), vec![30]);
+    limited.set_name(30, "found");
+    assert!(limited.stubs().is_empty());
+}
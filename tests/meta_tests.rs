@@ -0,0 +1,55 @@
+use deadlock_cli::meta::{self, MetaSort};
+use deadlock_cli::models::{HeroTotals, MetaFilter, MetaTotals, Rank};
+
+fn hero(hero_id: i32, picks: usize, wins: usize, damage: f64) -> HeroTotals {
+    HeroTotals {
+        hero_id,
+        picks,
+        decided: picks,
+        wins,
+        kills: 4 * picks as i64,
+        deaths: 2 * picks as i64,
+        assists: 6 * picks as i64,
+        avg_damage: Some(damage),
+        avg_networth: Some(damage * 2.0),
+    }
+}
+
+#[test]
+fn aggregates_heroes_and_flags_small_samples() {
+    // hero 1 in every one of 40 Archon matches, winning 30; hero 2 in 5 of them, winning 1
+    let totals = MetaTotals { matches: 40, heroes: vec![hero(2, 5, 1, 10_000.0), hero(1, 40, 30, 20_000.0)] };
+
+    let filter = MetaFilter { region: Some("europe".into()), ..Default::default() }.with_rank(Some(Rank::Archon));
+    assert_eq!((filter.min_badge, filter.max_badge), (Some(70), Some(76)));
+    let r = meta::analyze(totals.clone(), filter, 30, MetaSort::WinRate);
+    assert_eq!((r.matches, r.picks), (40, 45));
+    assert_eq!(r.heroes.iter().map(|h| h.hero_id).collect::<Vec<_>>(), vec![1, 2]);
+
+    let one = &r.heroes[0];
+    assert!(!one.low_sample);
+    assert!((one.win_rate.unwrap() - 75.0).abs() < 1e-9);
+    assert!((one.pick_rate - 100.0).abs() < 1e-9);
+    assert!((one.kda - 5.0).abs() < 1e-9);
+    assert!((one.avg_kills - 4.0).abs() < 1e-9);
+    assert_eq!(one.avg_networth, Some(40_000.0));
+    let ci = one.win_rate_ci.unwrap();
+    assert!(ci.low < 75.0 && ci.high > 75.0);
+    assert!((ci.low - 59.8).abs() < 0.1 && (ci.high - 85.8).abs() < 0.1);
+
+    let two = &r.heroes[1];
+    assert!(two.low_sample);
+    let wide = two.win_rate_ci.unwrap();
+    assert!(wide.high - wide.low > ci.high - ci.low);
+
+    let by_picks = meta::analyze(totals, MetaFilter::default(), 1, MetaSort::Picks);
+    assert_eq!(by_picks.heroes[0].hero_id, 1);
+    assert!(meta::wilson(0, 0).is_none());
+}
+
+#[test]
+fn parses_rank_names_and_divisions() {
+    assert_eq!("archon".parse::<Rank>().unwrap(), Rank::Archon);
+    assert_eq!("7".parse::<Rank>().unwrap(), Rank::Archon);
+    assert!("wizard".parse::<Rank>().is_err());
+}
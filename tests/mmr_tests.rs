@@ -0,0 +1,47 @@
+use deadlock_cli::mmr;
+use deadlock_cli::models::{MMRHistory, Rank};
+use deadlock_cli::ui;
+
+fn snap(start_time: i32, rank: i32, player_score: f64) -> MMRHistory {
+    MMRHistory {
+        account_id: 7,
+        match_id: start_time as i64,
+        start_time,
+        player_score,
+        rank,
+        division: rank / 10,
+        division_tier: rank % 10,
+    }
+}
+
+#[test]
+fn analyzes_deltas_peak_and_rolling_average() {
+    let series = vec![snap(1, 41, 40.0), snap(2, 45, 46.0), snap(3, 52, 51.0), snap(4, 45, 45.0)];
+    let report = mmr::analyze(7, &series, 2);
+
+    let deltas: Vec<_> = report.timeline.iter().map(|p| p.delta).collect();
+    assert_eq!(deltas, vec![None, Some(6.0), Some(5.0), Some(-6.0)]);
+    assert_eq!(report.timeline[0].rolling_avg, 40.0);
+    assert_eq!(report.timeline[3].rolling_avg, 48.0);
+    assert_eq!(report.peak.as_ref().unwrap().match_id, 3);
+    assert_eq!(report.peak.as_ref().unwrap().badge.unwrap().rank, Rank::Ritualist);
+    assert_eq!(report.lowest.as_ref().unwrap().match_id, 1);
+    assert_eq!(report.net_change, 5.0);
+    assert_eq!(report.latest().unwrap().match_id, 4);
+}
+
+#[test]
+fn merge_dedupes_by_start_time() {
+    let stored = vec![snap(2, 45, 46.0), snap(1, 41, 40.0)];
+    let fetched = vec![snap(2, 46, 47.0), snap(3, 52, 51.0)];
+    let merged = mmr::merge([stored, fetched]);
+    assert_eq!(merged.iter().map(|m| m.start_time).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(merged[1].rank, 46);
+}
+
+#[test]
+fn sparkline_scales_between_min_and_max() {
+    assert_eq!(ui::sparkline(&[1.0, 5.0, 3.0]), "▁█▅");
+    assert_eq!(ui::sparkline(&[2.0, 2.0]), "▅▅");
+    assert_eq!(ui::sparkline(&[]), "");
+}
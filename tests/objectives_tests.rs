@@ -0,0 +1,35 @@
+use deadlock_cli::models::{self, ObjectiveKind, ObjectivesMask, OBJECTIVES};
+
+#[test]
+fn decodes_objective_bits() {
+    // both shrines (bits 10, 11), lane 1 guardian (bit 1) and lane 3 walker (bit 7)
+    let mask = ObjectivesMask((1 << 10) | (1 << 11) | (1 << 1) | (1 << 7));
+    let destroyed: Vec<_> = mask.statuses().into_iter().filter(|s| s.destroyed).map(|s| s.objective).collect();
+    assert_eq!(destroyed.len(), 4);
+    assert_eq!(mask.destroyed_total(), 4);
+    assert!(destroyed.iter().any(|o| o.kind == ObjectiveKind::Guardian && o.lane == Some(1)));
+    assert!(destroyed.iter().any(|o| o.kind == ObjectiveKind::Walker && o.lane == Some(3)));
+    assert_eq!(destroyed.iter().filter(|o| o.kind == ObjectiveKind::Shrine).count(), 2);
+    assert!(!mask.destroyed(OBJECTIVES[9]));
+    assert_eq!(OBJECTIVES[9].label(), "Patron");
+    assert_eq!(OBJECTIVES[14].label(), "Base Guardian (lane 3)");
+}
+
+#[test]
+fn history_metas_carry_objectives() {
+    let entry = models::PlayerMatchHistoryEntry {
+        account_id: 1,
+        match_id: 42,
+        objectives_mask_team0: 0b11,
+        objectives_mask_team1: 0xffff,
+        ..Default::default()
+    };
+    let metas = models::history_to_metas(vec![entry]);
+    let o = metas[0].objectives().unwrap();
+    assert_eq!(o.team0.destroyed_total(), 2);
+    assert_eq!(o.team1.destroyed_total(), 16);
+
+    let v = serde_json::to_value(o.team0.statuses()[0]).unwrap();
+    assert_eq!(v["kind"], "core");
+    assert_eq!(v["destroyed"], true);
+}
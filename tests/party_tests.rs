@@ -0,0 +1,73 @@
+use deadlock_cli::models::{CoPlayer, PlayerMatch, Team};
+use deadlock_cli::party::{self, PartySize};
+
+fn own(match_id: i64, party_id: Option<i64>, won: bool, kills: i32) -> PlayerMatch {
+    PlayerMatch {
+        match_id,
+        party_id,
+        team: Some(Team::Team0),
+        is_victory: Some(won),
+        kills: Some(kills),
+        deaths: Some(1),
+        networth: Some(1000 * kills as i64),
+        ..Default::default()
+    }
+}
+
+/// A full 12-player scoreboard where accounts in `party` share the player's party 9.
+fn scoreboard(match_id: i64, party: &[u32]) -> Vec<CoPlayer> {
+    (100..111)
+        .map(|account_id| CoPlayer {
+            match_id,
+            account_id,
+            personaname: Some(format!("p{}", account_id)),
+            team: Some(if account_id < 105 { Team::Team0 } else { Team::Team1 }),
+            party_id: Some(if party.contains(&account_id) { 9 } else { 0 }),
+            my_team: Some(Team::Team0),
+            my_party_id: Some(9),
+            is_victory: None,
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[test]
+fn classifies_party_sizes() {
+    assert_eq!(PartySize::from_count(1), PartySize::Solo);
+    assert_eq!(PartySize::from_count(3), PartySize::Trio);
+    assert_eq!(PartySize::from_count(6), PartySize::FullStack);
+
+    let matches = vec![
+        own(1, Some(0), true, 2),
+        own(2, Some(9), true, 4),
+        own(3, Some(9), false, 6),
+        own(4, Some(9), true, 8),
+        own(5, None, true, 1),
+        own(6, Some(9), true, 0),
+    ];
+    let mut co = scoreboard(2, &[101]);
+    co.extend(scoreboard(3, &[101]));
+    co.extend(scoreboard(4, &[101, 102, 103, 104, 100]));
+    // partial scoreboard: party size unknown
+    co.extend(scoreboard(6, &[101]).into_iter().take(3));
+
+    let sizes = party::classify(&matches, &co);
+    assert_eq!(sizes.get(&1), Some(&PartySize::Solo));
+    assert_eq!(sizes.get(&2), Some(&PartySize::Duo));
+    assert_eq!(sizes.get(&4), Some(&PartySize::FullStack));
+    assert_eq!(sizes.get(&5), None);
+    assert_eq!(sizes.get(&6), None);
+
+    let r = party::analyze(1, &matches, &co, 2, 10);
+    assert_eq!(r.unclassified, 2);
+    let counts: Vec<(PartySize, usize)> = r.by_size.iter().map(|s| (s.size, s.performance.matches)).collect();
+    assert_eq!(counts, vec![(PartySize::Solo, 1), (PartySize::Duo, 2), (PartySize::FullStack, 1)]);
+    let duo = &r.by_size[1].performance;
+    assert!((duo.win_rate.unwrap() - 50.0).abs() < 1e-9);
+    assert!((duo.kda.kills - 5.0).abs() < 1e-9);
+    assert!((duo.avg_networth.unwrap() - 5000.0).abs() < 1e-9);
+
+    assert_eq!(r.members.len(), 1);
+    assert_eq!((r.members[0].account_id, r.members[0].performance.matches), (101, 4));
+    assert_eq!(r.members[0].personaname.as_deref(), Some("p101"));
+}
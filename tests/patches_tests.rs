@@ -0,0 +1,53 @@
+use deadlock_cli::models::PlayerMatch;
+use deadlock_cli::patches;
+
+fn row(match_id: i64, patch: Option<&str>, start_time: i64, hero_id: i32, won: bool, kda: (i32, i32, i32)) -> PlayerMatch {
+    PlayerMatch {
+        match_id,
+        start_time: Some(start_time),
+        hero_id: Some(hero_id),
+        is_victory: Some(won),
+        kills: Some(kda.0),
+        deaths: Some(kda.1),
+        assists: Some(kda.2),
+        patch_version: patch.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn groups_by_patch_with_deltas() {
+    let rows = vec![
+        // newest first, as loaded from the database; match 5 has two participants
+        row(5, Some("1.1"), 2_000, 6, false, (1, 4, 1)),
+        row(5, Some("1.1"), 2_000, 7, true, (5, 1, 5)),
+        row(4, Some("1.1"), 1_900, 7, true, (3, 1, 3)),
+        row(3, Some("1.0"), 1_200, 6, true, (6, 2, 2)),
+        row(2, Some("1.0"), 1_100, 6, false, (2, 2, 0)),
+        row(1, Some("1.0"), 1_000, 6, true, (4, 0, 4)),
+        row(9, None, 1_500, 6, true, (1, 1, 1)),
+    ];
+
+    let r = patches::analyze(None, &rows);
+    assert_eq!(r.unknown_patch, 1);
+    assert_eq!(r.patches.iter().map(|p| p.patch.as_str()).collect::<Vec<_>>(), vec!["1.0", "1.1"]);
+
+    let old = &r.patches[0];
+    assert_eq!((old.matches, old.picks, old.first_seen), (3, 3, Some(1_000)));
+    let old_hero = &old.heroes[0];
+    assert_eq!((old_hero.hero_id, old_hero.matches, old_hero.wins), (6, 3, 2));
+    assert!((old_hero.pick_rate - 100.0).abs() < 1e-9);
+    assert!((old_hero.kda - 18.0 / 4.0).abs() < 1e-9);
+    assert!(old_hero.delta.is_none());
+
+    let new = &r.patches[1];
+    assert_eq!((new.matches, new.picks), (2, 3));
+    assert_eq!(new.heroes.iter().map(|h| h.hero_id).collect::<Vec<_>>(), vec![7, 6]);
+    let seven = &new.heroes[0];
+    assert!((seven.pick_rate - 100.0).abs() < 1e-9);
+    assert!(seven.delta.is_none());
+    let six = new.heroes[1].delta.unwrap();
+    assert!((six.win_rate.unwrap() - (0.0 - 200.0 / 3.0)).abs() < 1e-9);
+    assert!((six.pick_rate - (50.0 - 100.0)).abs() < 1e-9);
+    assert!((six.kda - (0.5 - 4.5)).abs() < 1e-9);
+}
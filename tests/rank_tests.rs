@@ -0,0 +1,37 @@
+use deadlock_cli::models::{self, Badge, Rank};
+
+#[test]
+fn decodes_badge_numbers() {
+    let b = Badge::from_badge(74).unwrap();
+    assert_eq!(b.rank, Rank::Archon);
+    assert_eq!(b.tier, 4);
+    assert_eq!(b.to_string(), "Archon 4");
+    assert_eq!(b.badge(), 74);
+
+    assert_eq!(Badge::from_badge(0).unwrap().to_string(), "Obscurus");
+    assert_eq!(Badge::from_division(11, 6).unwrap().to_string(), "Eternus 6");
+    assert!(Badge::from_division(4, 7).is_none());
+    assert!(Badge::from_division(12, 1).is_none());
+    assert!(Badge::from_badge(-5).is_none());
+}
+
+#[test]
+fn badges_order_by_rank_then_tier() {
+    let low = Badge::from_division(1, 6).unwrap();
+    let mid = Badge::from_division(2, 1).unwrap();
+    let high = Badge::from_division(2, 3).unwrap();
+    assert!(low < mid && mid < high);
+    assert!(Badge::from_badge(0).unwrap() < low);
+}
+
+#[test]
+fn annotates_json_with_badges() {
+    let mut v = serde_json::json!({
+        "latest_mmr": { "rank": 42, "division": 4, "division_tier": 2 },
+        "matches": [{ "average_badge": 113 }]
+    });
+    models::annotate_badges(&mut v);
+    assert_eq!(v["latest_mmr"]["badge"]["name"], "Arcanist 2");
+    assert_eq!(v["latest_mmr"]["badge"]["badge"], 42);
+    assert_eq!(v["matches"][0]["average_badge_info"]["name"], "Eternus 3");
+}
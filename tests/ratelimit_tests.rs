@@ -0,0 +1,58 @@
+use deadlock_cli::deadlock::{DeadlockApi, DeadlockClient};
+use deadlock_cli::ratelimit::RateLimiter;
+use httpmock::prelude::*;
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn burst_then_steady_rate() {
+    let limiter = RateLimiter::new(20.0, 2);
+    let started = Instant::now();
+    for _ in 0..6 {
+        limiter.acquire().await;
+    }
+    // 2 free from the burst, 4 more at 50ms each
+    assert!(started.elapsed() >= Duration::from_millis(180));
+}
+
+#[tokio::test]
+async fn clones_share_one_bucket() {
+    let a = RateLimiter::new(10.0, 1);
+    let b = a.clone();
+    let started = Instant::now();
+    a.acquire().await;
+    b.acquire().await;
+    assert!(started.elapsed() >= Duration::from_millis(90));
+}
+
+#[tokio::test]
+async fn exhausted_remaining_header_blocks_until_reset() {
+    let limiter = RateLimiter::new(100.0, 10);
+    let mut headers = HeaderMap::new();
+    headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+    headers.insert("x-ratelimit-reset", HeaderValue::from_static("1"));
+    limiter.observe_headers(&headers);
+
+    let started = Instant::now();
+    limiter.acquire().await;
+    assert!(started.elapsed() >= Duration::from_millis(900));
+}
+
+#[tokio::test]
+async fn concurrent_client_calls_are_paced() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET);
+        then.status(200).json_body_obj(&serde_json::json!([]));
+    });
+
+    let dl = DeadlockClient::new(server.base_url(), None, reqwest::Client::new()).with_rate_limit(10.0, 1);
+    let ids = [1u32];
+    let started = Instant::now();
+    let (a, b, c) = tokio::join!(dl.get_steam_profiles(&ids), dl.get_mmr(&ids), dl.get_player_hero_stats(&ids));
+    a.unwrap();
+    b.unwrap();
+    c.unwrap();
+    m.assert_hits(3);
+    assert!(started.elapsed() >= Duration::from_millis(180));
+}
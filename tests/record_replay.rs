@@ -0,0 +1,87 @@
+use deadlock_cli::deadlock::{DeadlockApi, DeadlockClient, DeadlockError};
+use deadlock_cli::recorder::Recorder;
+use deadlock_cli::steam;
+use httpmock::prelude::*;
+use std::path::PathBuf;
+
+fn temp_fixture_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("deadlock-cli-fixtures-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn deadlock_responses_replay_without_network() {
+    let server = MockServer::start();
+    let mut m = server.mock(|when, then| {
+        when.method(GET).path("/v1/players/hero-stats").query_param("account_ids", "7");
+        then.status(200).json_body_obj(&serde_json::json!([{ "account_id": 7, "hero_id": 3, "wins": 4 }]));
+    });
+
+    let dir = temp_fixture_dir("deadlock");
+    let recording = DeadlockClient::new(server.base_url(), None, reqwest::Client::new()).with_recorder(Recorder::record(&dir));
+    recording.get_player_hero_stats(&[7]).await.unwrap();
+    m.assert_hits(1);
+    m.delete();
+
+    let replaying = DeadlockClient::new(server.base_url(), None, reqwest::Client::new()).with_recorder(Recorder::replay(&dir));
+    let stats = replaying.get_player_hero_stats(&[7]).await.unwrap();
+    assert_eq!(stats[0].hero_id, 3);
+    assert_eq!(stats[0].wins, Some(4));
+
+    let err = replaying.get_player_hero_stats(&[8]).await.err().unwrap();
+    assert!(matches!(err, DeadlockError::ReplayMiss(_)));
+}
+
+#[tokio::test]
+async fn recorded_http_errors_replay_as_errors() {
+    let server = MockServer::start();
+    let mut m = server.mock(|when, then| {
+        when.method(GET).path("/v1/players/steam");
+        then.status(404).body("not found");
+    });
+
+    let dir = temp_fixture_dir("errors");
+    let recording = DeadlockClient::new(server.base_url(), None, reqwest::Client::new()).with_recorder(Recorder::record(&dir));
+    assert!(recording.get_steam_profiles(&[1]).await.is_err());
+    m.delete();
+
+    let replaying = DeadlockClient::new(server.base_url(), None, reqwest::Client::new()).with_recorder(Recorder::replay(&dir));
+    match replaying.get_steam_profiles(&[1]).await {
+        Err(DeadlockError::Http { status, message }) => {
+            assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+            assert_eq!(message, "not found");
+        }
+        other => panic!("expected 404, got {:?}", other.map(|v| v.len())),
+    }
+}
+
+#[tokio::test]
+async fn vanity_resolution_records_without_api_key() {
+    let server = MockServer::start();
+    let mut m = server.mock(|when, then| {
+        when.method(GET).path("/ISteamUser/ResolveVanityURL/v1/").query_param("vanityurl", "replayme");
+        then.status(200)
+            .json_body_obj(&serde_json::json!({ "response": { "success": 1, "steamid": "76561197960287930" } }));
+    });
+    unsafe {
+        std::env::set_var("STEAM_WEB_API_BASE", server.base_url());
+        std::env::set_var("STEAM_WEB_API_KEY", "SECRETKEY");
+    }
+
+    let dir = temp_fixture_dir("steam");
+    let http = reqwest::Client::new();
+    let rec = Recorder::record(&dir);
+    let sid = steam::to_steamid64_recorded("replayme", &http, Some(&rec)).await.unwrap();
+    assert_eq!(sid, "76561197960287930");
+    m.delete();
+
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let raw = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        assert!(!raw.contains("SECRETKEY"));
+    }
+
+    let replay = Recorder::replay(&dir);
+    let sid = steam::to_steamid64_recorded("replayme", &http, Some(&replay)).await.unwrap();
+    assert_eq!(sid, "76561197960287930");
+}
@@ -0,0 +1,73 @@
+use deadlock_cli::fixture::FixtureApi;
+use deadlock_cli::heroes::HeroCatalog;
+use deadlock_cli::models;
+use deadlock_cli::repl::{self, ReplCommand, ReplHelper, Session};
+use deadlock_cli::tui;
+use deadlock_cli::ui::ChartStyle;
+
+#[test]
+fn parses_commands_and_bare_lookups() {
+    assert_eq!(repl::parse("   ").unwrap(), None);
+    assert_eq!(repl::parse("76561197960435530").unwrap(), Some(ReplCommand::Lookup("76561197960435530".into())));
+    assert_eq!(repl::parse("lookup \"Big Boss\"").unwrap(), Some(ReplCommand::Lookup("Big Boss".into())));
+    assert_eq!(repl::parse("heroes").unwrap(), Some(ReplCommand::Heroes(None)));
+    assert_eq!(
+        repl::parse("matches -n 5 gabe").unwrap(),
+        Some(ReplCommand::Matches { player: Some("gabe".into()), limit: 5 })
+    );
+    assert_eq!(repl::parse("compare a 'b c'").unwrap(), Some(ReplCommand::Compare(vec!["a".into(), "b c".into()])));
+    assert_eq!(repl::parse("EXIT").unwrap(), Some(ReplCommand::Quit));
+
+    assert!(repl::parse("matches -n zero").is_err());
+    assert!(repl::parse("heroes a b").is_err());
+    assert!(repl::parse("frobnicate a b").is_err());
+    assert!(repl::parse("lookup \"open").is_err());
+}
+
+#[test]
+fn completes_commands_and_seen_names() {
+    let mut helper = ReplHelper::new(["Gabe".to_string(), "388674065".to_string()]);
+    helper.add_name("gaben fan".into());
+
+    assert_eq!(helper.candidates("ma", 2), (0, vec!["matches".to_string()]));
+    assert_eq!(helper.candidates("heroes ga", 9), (7, vec!["Gabe".to_string(), "\"gaben fan\"".to_string()]));
+    assert_eq!(helper.candidates("compare Gabe \"gaben f", 21), (13, vec!["\"gaben fan\"".to_string()]));
+    assert_eq!(helper.candidates("3886", 4), (0, vec!["388674065".to_string()]));
+}
+
+#[tokio::test]
+async fn session_reuses_loaded_players() {
+    let api = FixtureApi::new()
+        .with_profile(models::SteamProfile {
+            account_id: 42,
+            personaname: "shelltester".into(),
+            ..Default::default()
+        })
+        .with_hero_stats(models::HeroStats { account_id: 42, hero_id: 6, matches_played: Some(3), wins: Some(2), ..Default::default() });
+    let http = reqwest::Client::new();
+    let env = tui::Env {
+        api: &api,
+        http: &http,
+        recorder: None,
+        offline: false,
+        #[cfg(feature = "db")]
+        pool: None,
+    };
+    let catalog = HeroCatalog::bundled();
+    let out = repl::Output { catalog: &catalog, style: ChartStyle { width: 80, color: false }, json: false };
+
+    let mut session = Session::new([(7, "Someone Else".to_string())]);
+    assert_eq!(session.resolve_name("someone else"), "7");
+    assert!(repl::execute(&env, &mut session, ReplCommand::Heroes(None), &out).await.is_err());
+
+    assert!(repl::execute(&env, &mut session, ReplCommand::Lookup("42".into()), &out).await.unwrap());
+    assert_eq!(session.current, Some(42));
+    assert_eq!(session.resolve_name("ShellTester"), "42");
+
+    assert!(repl::execute(&env, &mut session, ReplCommand::Matches { player: Some("shelltester".into()), limit: 5 }, &out).await.unwrap());
+    assert!(repl::execute(&env, &mut session, ReplCommand::Lookup("42".into()), &out).await.unwrap());
+    assert_eq!(session.history, vec![42]);
+    assert_eq!(session.players()[0].profile.personaname, "shelltester");
+
+    assert!(!repl::execute(&env, &mut session, ReplCommand::Quit, &out).await.unwrap());
+}
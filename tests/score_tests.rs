@@ -0,0 +1,21 @@
+use deadlock_cli::models::{PerformanceScore, PlayerMatch, StoredScore};
+use deadlock_cli::score::Scores;
+
+fn stored(match_id: i64, account_id: u32, score: f64) -> StoredScore {
+    StoredScore { match_id, account_id, score: PerformanceScore { score, percentile: 50.0, hero_matches: 2 } }
+}
+
+#[test]
+fn annotates_one_accounts_rows() {
+    let scores = Scores::new([stored(1, 1, 61.0), stored(2, 1, 39.0), stored(2, 2, 55.0)]);
+    assert_eq!(scores.get(2, 2).map(|s| s.score), Some(55.0));
+    assert!(scores.get(1, 2).is_none());
+
+    let mut history = vec![
+        PlayerMatch { match_id: 2, ..Default::default() },
+        PlayerMatch { match_id: 3, ..Default::default() },
+    ];
+    scores.annotate(1, &mut history);
+    assert_eq!(history[0].score.map(|s| s.score), Some(39.0));
+    assert!(history[1].score.is_none());
+}
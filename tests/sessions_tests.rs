@@ -0,0 +1,62 @@
+use chrono::FixedOffset;
+use deadlock_cli::models::PlayerMatch;
+use deadlock_cli::sessions;
+
+/// Monday 1970-01-05 00:00 UTC.
+const MONDAY: i64 = 4 * 86_400;
+
+fn m(match_id: i64, start_time: Option<i64>, won: bool) -> PlayerMatch {
+    PlayerMatch { match_id, start_time, duration_s: Some(1800), is_victory: Some(won), kills: Some(5), deaths: Some(5), ..Default::default() }
+}
+
+fn history() -> Vec<PlayerMatch> {
+    vec![
+        // one Monday night session (matches 200 s apart), then a lone Tuesday match
+        m(1, Some(MONDAY), true),
+        m(2, Some(MONDAY + 2_000), false),
+        m(3, Some(MONDAY + 4_000), false),
+        m(4, Some(MONDAY + 6_000), true),
+        m(5, Some(MONDAY + 86_400), false),
+        m(6, None, true),
+    ]
+}
+
+#[test]
+fn splits_sessions_with_streaks_tilt_and_decay() {
+    let utc = FixedOffset::east_opt(0).unwrap();
+    let r = sessions::analyze(1, &history(), 30, 4, 1, utc);
+    assert_eq!(r.matches, 5);
+    assert_eq!(r.sessions.iter().map(|s| s.performance.matches).collect::<Vec<_>>(), vec![1, 4]);
+    assert_eq!((r.sessions[1].start, r.sessions[1].end), (MONDAY, MONDAY + 7_800));
+
+    let loss = r.streaks.longest_loss.as_ref().unwrap();
+    assert_eq!((loss.length, loss.start, loss.end), (2, MONDAY + 2_000, MONDAY + 4_000));
+    assert_eq!(r.streaks.longest_win.as_ref().unwrap().length, 1);
+    let current = r.streaks.current.as_ref().unwrap();
+    assert!(!current.won && current.length == 1);
+
+    let tilt: Vec<(usize, usize, usize)> = r.tilt.iter().map(|t| (t.after_losses, t.performance.matches, t.performance.wins)).collect();
+    assert_eq!(tilt, vec![(0, 1, 0), (1, 1, 0), (2, 1, 1)]);
+    assert_eq!((r.by_position[0].position, r.by_position[0].performance.matches, r.by_position[0].performance.wins), (1, 2, 1));
+
+    let decay = r.decay.as_ref().unwrap();
+    assert_eq!(decay.sessions, 1);
+    assert_eq!((decay.first_half.wins, decay.second_half.wins), (1, 1));
+
+    assert_eq!(r.by_time.iter().map(|w| (w.label.as_str(), w.performance.matches)).collect::<Vec<_>>(), vec![("00-04", 5)]);
+    assert_eq!(r.by_weekday.iter().map(|w| w.label.as_str()).collect::<Vec<_>>(), vec!["Mon", "Tue"]);
+    assert_eq!((r.best_weekday.as_deref(), r.worst_weekday.as_deref()), (Some("Mon"), Some("Tue")));
+    assert_eq!((r.best_time.as_deref(), r.worst_time), (Some("00-04"), None));
+
+    // a shorter gap splits the Monday session
+    assert_eq!(sessions::analyze(1, &history(), 1, 4, 1, utc).sessions.len(), 5);
+}
+
+#[test]
+fn windows_follow_the_utc_offset() {
+    let r = sessions::analyze(1, &history(), 30, 4, 1, FixedOffset::west_opt(2 * 3600).unwrap());
+    assert_eq!(r.utc_offset_minutes, -120);
+    assert_eq!(r.by_weekday[0].label, "Mon");
+    assert_eq!(r.by_weekday.last().unwrap().label, "Sun");
+    assert_eq!(r.by_time.iter().map(|w| w.label.as_str()).collect::<Vec<_>>(), vec!["20-24"]);
+}
@@ -32,8 +32,10 @@ async fn resolves_vanity_from_id_url() {
             }));
     });
 
-    std::env::set_var("STEAM_WEB_API_BASE", server.base_url());
-    std::env::set_var("STEAM_WEB_API_KEY", "TESTKEY");
+    unsafe {
+        std::env::set_var("STEAM_WEB_API_BASE", server.base_url());
+        std::env::set_var("STEAM_WEB_API_KEY", "TESTKEY");
+    }
 
     let http = reqwest::Client::new();
     let url = "https://steamcommunity.com/id/gabelogannewell/";
@@ -55,8 +57,10 @@ async fn vanity_resolution_handles_failure() {
             }));
     });
 
-    std::env::set_var("STEAM_WEB_API_BASE", server.base_url());
-    std::env::set_var("STEAM_WEB_API_KEY", "TESTKEY");
+    unsafe {
+        std::env::set_var("STEAM_WEB_API_BASE", server.base_url());
+        std::env::set_var("STEAM_WEB_API_KEY", "TESTKEY");
+    }
     let http = reqwest::Client::new();
     let url = "https://steamcommunity.com/id/nonexistent";
     let err = steam::to_steamid64_with_client(url, &http).await.err().unwrap();
@@ -0,0 +1,75 @@
+use deadlock_cli::heroes::HeroCatalog;
+use deadlock_cli::models::{HeroRecord, PairRecord, SynergyTotals};
+use deadlock_cli::synergy::{self, MatrixKind};
+use deadlock_cli::ui;
+
+fn pair(hero_id: i32, other_hero_id: i32, same_team: bool, matches: usize, wins: usize) -> PairRecord {
+    PairRecord { hero_id, other_hero_id, same_team, matches, wins }
+}
+
+/// Heroes 1 and 2 on team 0, hero 3 on team 1, over `team0_wins` wins out of `games`.
+fn games(games: usize, team0_wins: usize) -> Vec<PairRecord> {
+    let losses = games - team0_wins;
+    vec![
+        pair(1, 2, true, games, team0_wins),
+        pair(2, 1, true, games, team0_wins),
+        pair(1, 3, false, games, team0_wins),
+        pair(2, 3, false, games, team0_wins),
+        pair(3, 1, false, games, losses),
+        pair(3, 2, false, games, losses),
+    ]
+}
+
+#[test]
+fn pairs_heroes_by_team_against_expectation() {
+    // four games as above winning three, plus one where hero 1 lost to hero 3 without hero 2
+    let mut pairs = games(4, 3);
+    for p in pairs.iter_mut().filter(|p| (p.hero_id, p.other_hero_id) == (1, 3) || (p.hero_id, p.other_hero_id) == (3, 1)) {
+        p.matches += 1;
+        p.wins += usize::from(p.hero_id == 3);
+    }
+    let heroes = vec![
+        HeroRecord { hero_id: 1, matches: 5, wins: 3 },
+        HeroRecord { hero_id: 2, matches: 4, wins: 3 },
+        HeroRecord { hero_id: 3, matches: 5, wins: 2 },
+    ];
+
+    let r = synergy::analyze(&SynergyTotals { matches: 5, heroes, pairs });
+    assert_eq!(r.matches, 5);
+    assert_eq!(r.heroes.iter().map(|h| (h.hero_id, h.matches, h.wins)).collect::<Vec<_>>(), vec![(1, 5, 3), (2, 4, 3), (3, 5, 2)]);
+
+    let h = r.for_hero(1, 1, 10);
+    assert_eq!(h.with.len(), 1);
+    let with2 = &h.with[0].stats;
+    assert_eq!((h.with[0].other.hero_id, with2.matches, with2.wins), (2, 4, 3));
+    // expected (60 + 75) / 2
+    assert!((with2.expected.unwrap() - 67.5).abs() < 1e-9);
+    assert!((with2.delta.unwrap() - 7.5).abs() < 1e-9);
+
+    let vs3 = &h.against[0].stats;
+    assert_eq!((vs3.matches, vs3.wins), (5, 3));
+    // expected (60 + (100 - 40)) / 2
+    assert!((vs3.expected.unwrap() - 60.0).abs() < 1e-9);
+    let back = r.for_hero(3, 1, 10);
+    assert!((back.against[0].stats.delta.unwrap() + vs3.delta.unwrap()).abs() < 1e-9);
+
+    assert!(r.for_hero(1, 6, 10).against.is_empty());
+}
+
+#[test]
+fn exports_matrix_as_csv() {
+    let heroes = (1..=3).map(|hero_id| HeroRecord { hero_id, matches: 2, wins: 1 }).collect();
+    let m = synergy::analyze(&SynergyTotals { matches: 2, heroes, pairs: games(2, 1) }).matrix(MatrixKind::Counter, 1);
+    assert_eq!(m.cells.len(), 3);
+    assert!(m.cells[0][0].is_none() && m.cells[0][1].is_none());
+    assert_eq!(m.cells[0][2].unwrap().matches, 2);
+
+    let catalog = HeroCatalog::from_heroes([]);
+    let mut out = Vec::new();
+    ui::write_matrix_csv(&m, &catalog, &mut out).unwrap();
+    let csv = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "hero,Hero #1,Hero #2,Hero #3");
+    assert_eq!(lines[1], "Hero #1,,,0.0");
+    assert_eq!(lines.len(), 4);
+}
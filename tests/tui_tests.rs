@@ -0,0 +1,97 @@
+use deadlock_cli::fixture::FixtureApi;
+use deadlock_cli::heroes::HeroCatalog;
+use deadlock_cli::models::{self, RecentLookup};
+use deadlock_cli::tui::{self, Action, Dashboard, Focus, Tab};
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn fixture() -> FixtureApi {
+    FixtureApi::new()
+        .with_profile(models::SteamProfile {
+            account_id: 42,
+            personaname: "dashtester".into(),
+            ..Default::default()
+        })
+        .with_mmr(models::MMRHistory {
+            account_id: 42,
+            match_id: 1,
+            start_time: 1_700_000_000,
+            player_score: 74.0,
+            rank: 74,
+            division: 7,
+            division_tier: 4,
+        })
+        .with_hero_stats(models::HeroStats { account_id: 42, hero_id: 6, matches_played: Some(10), wins: Some(6), ..Default::default() })
+        .with_history_entry(models::PlayerMatchHistoryEntry {
+            account_id: 42,
+            match_id: 77,
+            hero_id: 6,
+            start_time: 1_700_000_000,
+            player_kills: 8,
+            ..Default::default()
+        })
+}
+
+fn screen(dash: &mut Dashboard) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(110, 30)).unwrap();
+    terminal.draw(|f| dash.draw(f)).unwrap();
+    let buf = terminal.backend().buffer();
+    buf.content().chunks(buf.area.width as usize).map(|row| row.iter().map(|c| c.symbol()).collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn search_box_and_navigation_keys() {
+    let mut dash = Dashboard::new(HeroCatalog::bundled(), false);
+    for c in "gabe".chars() {
+        dash.handle_key(key(KeyCode::Char(c)));
+    }
+    assert_eq!(dash.handle_key(key(KeyCode::Enter)), Action::Load("gabe".into()));
+
+    dash.handle_key(key(KeyCode::Tab));
+    assert_eq!(dash.focus, Focus::Recent);
+    dash.set_recent(vec![RecentLookup { account_id: 9, personaname: "old".into(), looked_up_at: chrono::Utc::now() }]);
+    assert_eq!(dash.handle_key(key(KeyCode::Enter)), Action::Load("9".into()));
+
+    dash.handle_key(key(KeyCode::Char('3')));
+    assert_eq!(dash.tab, Tab::Matches);
+    dash.handle_key(key(KeyCode::Left));
+    dash.handle_key(key(KeyCode::Left));
+    dash.handle_key(key(KeyCode::Left));
+    assert_eq!(dash.tab, Tab::Mmr);
+    assert_eq!(dash.handle_key(key(KeyCode::Char('r'))), Action::None);
+    assert_eq!(dash.handle_key(key(KeyCode::Char('q'))), Action::Quit);
+}
+
+#[tokio::test]
+async fn loads_and_renders_every_tab() {
+    let api = fixture();
+    let http = reqwest::Client::new();
+    let env = tui::Env {
+        api: &api,
+        http: &http,
+        recorder: None,
+        offline: false,
+        #[cfg(feature = "db")]
+        pool: None,
+    };
+    let view = tui::load_view(&env, "42").await.unwrap();
+    assert_eq!(view.matches.len(), 1);
+    assert_eq!(view.mmr.as_ref().unwrap().timeline.len(), 1);
+
+    let mut dash = Dashboard::new(HeroCatalog::bundled(), false);
+    dash.set_player(view);
+    assert!(screen(&mut dash).contains("dashtester"));
+    assert!(screen(&mut dash).contains("Archon 4"));
+
+    dash.handle_key(key(KeyCode::Char('2')));
+    assert!(screen(&mut dash).contains("60.0%"));
+    dash.handle_key(key(KeyCode::Char('3')));
+    assert!(screen(&mut dash).contains("8/0/0"));
+    dash.handle_key(key(KeyCode::Char('4')));
+    assert!(screen(&mut dash).contains("Rank over time"));
+}